@@ -0,0 +1,380 @@
+//! Parsing `cfor!` invocations, so that mistakes can be reported
+//! precisely, rather than as a failure to match any macro rule.
+
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream, Parser};
+use syn::punctuated::Punctuated;
+use syn::{parenthesized, token, Block, Expr, Ident, Label, Pat, Token, Type};
+
+use crate::incdec;
+
+/// A whole `cfor!` invocation: `'label: init; cond; step; { body }`,
+/// the parenthesised `'label: (init; cond; step) { body }`, or the
+/// range `'label: i in a..b step s { body }`, followed by optional
+/// `else` and `finally` blocks. Either kind of header can start with
+/// `enumerate k;`, and a C header can be followed by `=> expr`
+/// instead of a body.
+pub struct Loop {
+    label: Option<Label>,
+    enumerate: Option<Ident>,
+    form: Form,
+    body: Body,
+}
+
+enum Form {
+    Header(Header),
+    Range(Range),
+}
+
+enum Body {
+    Block {
+        body: Block,
+        els: Option<Block>,
+        finally: Option<Block>,
+    },
+    Iter(Token![=>], Expr),
+}
+
+struct Header {
+    init: Init,
+    cond: Option<Expr>,
+    step: Punctuated<Expr, Token![,]>,
+}
+
+/// `i in a..b`, `i in a..=b` or `i in b downto a`, and maybe
+/// `step s`. The bounds are kept as tokens, since `downto` isn't
+/// Rust.
+struct Range {
+    var: Ident,
+    in_token: Token![in],
+    bounds: TokenStream,
+    step: Option<(Ident, Expr)>,
+}
+
+enum Init {
+    Block(Block),
+    List(Punctuated<InitItem, Token![,]>),
+}
+
+enum InitItem {
+    Let(Box<Let>),
+    Expr(Expr),
+}
+
+struct Let {
+    let_token: Token![let],
+    pat: Pat,
+    ty: Option<(Token![:], Type)>,
+    init: Option<(Token![=], Expr)>,
+}
+
+impl Parse for Loop {
+    fn parse(input: ParseStream) -> syn::Result<Loop> {
+        let label = if input.peek(syn::Lifetime) {
+            Some(input.parse()?)
+        } else {
+            None
+        };
+
+        let enumerate = if is_keyword(input, "enumerate") && input.peek2(Ident) && input.peek3(Token![;]) {
+            input.parse::<Ident>()?;
+            let k = input.parse()?;
+            input.parse::<Token![;]>()?;
+            Some(k)
+        } else {
+            None
+        };
+
+        let form = if input.peek(Ident) && input.peek2(Token![in]) {
+            Form::Range(input.parse()?)
+        } else if is_parenthesised_header(input) {
+            let content;
+            parenthesized!(content in input);
+            Form::Header(Header::parse(&content, false)?)
+        } else {
+            Form::Header(Header::parse(input, true)?)
+        };
+
+        if let Form::Header(_) = form {
+            if input.peek(Token![=>]) {
+                let arrow = input.parse()?;
+                let e = input.parse()?;
+                if !input.is_empty() {
+                    return Err(input.error("unexpected token after the iterator's `=> expr`"));
+                }
+                return Ok(Loop { label, enumerate, form, body: Body::Iter(arrow, e) });
+            }
+        }
+
+        let body = parse_block(
+            input,
+            match form {
+                Form::Range(Range { step: None, .. }) => {
+                    "expected `step` or the loop body, in braces: `{ ... }`"
+                }
+                _ => "expected the loop body, in braces: `{ ... }`",
+            },
+        )?;
+
+        let els = if input.peek(Token![else]) {
+            input.parse::<Token![else]>()?;
+            Some(parse_block(input, "expected a block after `else`")?)
+        } else {
+            None
+        };
+        let finally = if is_keyword(input, "finally") {
+            input.parse::<Ident>()?;
+            Some(parse_block(input, "expected a block after `finally`")?)
+        } else {
+            None
+        };
+
+        if input.peek(Token![;]) {
+            input.parse::<Token![;]>()?;
+        }
+
+        if !input.is_empty() {
+            return Err(input.error(
+                "unexpected token after the loop; only `else { ... }` and `finally { ... }` \
+                 can follow the body",
+            ));
+        }
+
+        Ok(Loop { label, enumerate, form, body: Body::Block { body, els, finally } })
+    }
+}
+
+/// Whether `input` is `(...) { ... }` or `(...) => ...`, as opposed
+/// to an initialiser that happens to start with parentheses, like
+/// `(); ...`.
+fn is_parenthesised_header(input: ParseStream) -> bool {
+    input.peek(token::Paren) && (input.peek2(token::Brace) || input.peek2(Token![=>]))
+}
+
+/// Whether the next token is the identifier `word`, for the words
+/// that `cfor!` uses that aren't Rust keywords.
+fn is_keyword(input: ParseStream, word: &str) -> bool {
+    input.peek(Ident) && input.fork().parse::<Ident>().map_or(false, |i| i == word)
+}
+
+fn parse_block(input: ParseStream, message: &str) -> syn::Result<Block> {
+    if !input.peek(token::Brace) {
+        return Err(input.error(message));
+    }
+    input.parse()
+}
+
+fn parse_semi(input: ParseStream, message: &str) -> syn::Result<()> {
+    if input.peek(Token![;]) {
+        input.parse::<Token![;]>()?;
+        Ok(())
+    } else if input.is_empty() {
+        Err(input.error(format!("{}, but the input ended", message)))
+    } else {
+        Err(input.error(message))
+    }
+}
+
+impl Header {
+    /// Parses `init; cond; step`, and then a `;` too if
+    /// `trailing_semi`.
+    fn parse(input: ParseStream, trailing_semi: bool) -> syn::Result<Header> {
+        let init = Init::parse(input)?;
+        parse_semi(input, "expected `;` after the initialiser")?;
+
+        let cond = if input.peek(Token![;]) {
+            None
+        } else {
+            let tokens = incdec::rewrite(take_part(input)?, false);
+            Some(Parser::parse2(
+                |input: ParseStream| {
+                    let cond = Expr::parse_without_eager_brace(input)?;
+                    if !input.is_empty() {
+                        return Err(input.error("expected `;` between the condition and the step"));
+                    }
+                    Ok(cond)
+                },
+                tokens,
+            )?)
+        };
+        parse_semi(input, "expected `;` between the condition and the step")?;
+
+        let tokens = incdec::rewrite(take_part(input)?, true);
+        let step = Parser::parse2(
+            |input: ParseStream| {
+                let mut step = Punctuated::new();
+                while !input.is_empty() {
+                    step.push_value(Expr::parse_without_eager_brace(input)?);
+                    if !input.peek(Token![,]) {
+                        break;
+                    }
+                    step.push_punct(input.parse()?);
+                }
+                if !input.is_empty() {
+                    return Err(input.error(if trailing_semi {
+                        "expected `;` between the step and the loop body, or `=>` and a value"
+                    } else {
+                        "unexpected token after the step"
+                    }));
+                }
+                Ok(step)
+            },
+            tokens,
+        )?;
+        if trailing_semi && !input.peek(Token![=>]) {
+            parse_semi(input, "expected `;` between the step and the loop body, or `=>` and a value")?;
+        }
+
+        Ok(Header { init, cond, step })
+    }
+}
+
+/// The tokens up to the next `;` or `=>` (or the end), so that `++`
+/// and `--` can be rewritten before they're parsed.
+fn take_part(input: ParseStream) -> syn::Result<TokenStream> {
+    let mut tokens = TokenStream::new();
+    while !input.is_empty() && !input.peek(Token![;]) && !input.peek(Token![=>]) {
+        tokens.extend(Some(input.parse::<TokenTree>()?));
+    }
+    Ok(tokens)
+}
+
+impl Parse for Range {
+    fn parse(input: ParseStream) -> syn::Result<Range> {
+        let var = input.parse()?;
+        let in_token = input.parse()?;
+
+        const MESSAGE: &str = "expected a range with both ends, like `a..b`, `a..=b` or `b downto a`";
+        let start = Expr::parse_without_eager_brace(input)?;
+        let bounds = match start {
+            Expr::Range(syn::ExprRange { start: Some(_), end: Some(_), .. }) => start.into_token_stream(),
+            _ if is_keyword(input, "downto") => {
+                let downto = input.parse::<Ident>()?;
+                let end = Expr::parse_without_eager_brace(input)?;
+                quote!(#start #downto #end)
+            }
+            _ => return Err(syn::Error::new_spanned(start, MESSAGE)),
+        };
+
+        let step = if is_keyword(input, "step") {
+            Some((input.parse()?, Expr::parse_without_eager_brace(input)?))
+        } else {
+            None
+        };
+        Ok(Range { var, in_token, bounds, step })
+    }
+}
+
+impl Init {
+    fn parse(input: ParseStream) -> syn::Result<Init> {
+        if input.peek(token::Brace) {
+            let fork = input.fork();
+            fork.parse::<Block>()?;
+            if fork.peek(Token![;]) {
+                return Ok(Init::Block(input.parse()?));
+            }
+        }
+
+        let mut items = Punctuated::new();
+        while !input.peek(Token![;]) && !input.is_empty() {
+            let item = if input.peek(Token![let]) {
+                InitItem::Let(Box::new(input.parse()?))
+            } else {
+                InitItem::Expr(Expr::parse_without_eager_brace(input)?)
+            };
+            items.push_value(item);
+            if !input.peek(Token![,]) {
+                break;
+            }
+            items.push_punct(input.parse()?);
+        }
+        Ok(Init::List(items))
+    }
+}
+
+impl Parse for Let {
+    fn parse(input: ParseStream) -> syn::Result<Let> {
+        let let_token = input.parse()?;
+        let pat = Pat::parse_single(input)?;
+        let ty = if input.peek(Token![:]) {
+            Some((input.parse()?, input.parse()?))
+        } else {
+            None
+        };
+        let init = if input.peek(Token![=]) {
+            Some((input.parse()?, Expr::parse_without_eager_brace(input)?))
+        } else {
+            None
+        };
+        Ok(Let { let_token, pat, ty, init })
+    }
+}
+
+impl Loop {
+    /// A loop with the given header, which is parsed like the
+    /// contents of the parenthesised form.
+    pub fn from_parts(label: Option<Label>, header: TokenStream, body: Block) -> syn::Result<Loop> {
+        let header = Parser::parse2(|input: ParseStream| Header::parse(input, false), header)?;
+        Ok(Loop {
+            label,
+            enumerate: None,
+            form: Form::Header(header),
+            body: Body::Block { body, els: None, finally: None },
+        })
+    }
+
+    /// The equivalent `cfor!` invocation.
+    pub fn expand(&self) -> TokenStream {
+        let Loop { label, enumerate, form, body } = self;
+        let enumerate = enumerate.as_ref().map(|k| quote!(enumerate #k;));
+        let form = match form {
+            Form::Header(Header { init, cond, step }) => match body {
+                Body::Block { .. } => quote!(#init; #cond; #step;),
+                Body::Iter(..) => quote!(#init; #cond; #step),
+            },
+            Form::Range(Range { var, in_token, bounds, step }) => {
+                let step = step.as_ref().map(|(word, s)| quote!(#word #s));
+                quote!(#var #in_token #bounds #step)
+            }
+        };
+        let body = match body {
+            Body::Block { body, els, finally } => {
+                let els = els.as_ref().map(|b| quote!(else #b));
+                let finally = finally.as_ref().map(|b| quote!(finally #b));
+                quote!(#body #els #finally)
+            }
+            Body::Iter(arrow, e) => quote!(#arrow #e),
+        };
+        quote! {
+            ::cfor::cfor!{#label #enumerate #form #body}
+        }
+    }
+}
+
+impl ToTokens for Init {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            Init::Block(b) => b.to_tokens(tokens),
+            Init::List(items) => items.to_tokens(tokens),
+        }
+    }
+}
+
+impl ToTokens for InitItem {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            InitItem::Let(l) => l.to_tokens(tokens),
+            InitItem::Expr(e) => e.to_tokens(tokens),
+        }
+    }
+}
+
+impl ToTokens for Let {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let Let { let_token, pat, ty, init } = self;
+        let ty = ty.as_ref().map(|(colon, ty)| quote!(#colon #ty));
+        let init = init.as_ref().map(|(eq, e)| quote!(#eq #e));
+        tokens.extend(quote!(#let_token #pat #ty #init));
+    }
+}
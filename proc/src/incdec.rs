@@ -0,0 +1,191 @@
+//! Rewriting C's `++` and `--` operators into Rust, so that
+//! conditions and steps can be pasted from C unchanged.
+
+use proc_macro2::{Delimiter, Group, Ident, Punct, Spacing, Span, TokenStream, TokenTree};
+use quote::quote;
+use std::iter::Peekable;
+use std::vec::IntoIter;
+
+/// Rewrites the `i++`, `++i`, `i--` and `--i`s in `tokens`.
+///
+/// If `statements`, an increment that makes up a whole
+/// comma-separated part on its own (like each of `i++, j--`) only
+/// updates the variable, becoming `i += 1`. Every other one becomes
+/// an expression with C's value: the new value for `++i`, and the
+/// old one for `i++`.
+///
+/// Braced groups are left alone, since they're Rust blocks, where
+/// `--x` already means something.
+pub fn rewrite(tokens: TokenStream, statements: bool) -> TokenStream {
+    let mut out = vec![];
+    let mut iter = tokens.into_iter().collect::<Vec<_>>().into_iter().peekable();
+    while let Some(tt) = iter.next() {
+        let op = match &tt {
+            TokenTree::Punct(p) => doubled(p, iter.peek()),
+            _ => None,
+        };
+        let op = match op {
+            Some(op) => op,
+            None => {
+                out.push(recurse(tt));
+                continue;
+            }
+        };
+
+        let post = operand_before(&mut out);
+        let (operand, postfix) = if !post.is_empty() {
+            (post, true)
+        } else {
+            let pre = operand_after(&mut iter);
+            if pre.is_empty() {
+                // not an increment after all, like `--5`
+                out.push(tt);
+                continue;
+            }
+            (pre, false)
+        };
+        // the second `+` or `-`, if it wasn't already taken
+        if postfix {
+            iter.next();
+        }
+
+        let starts_part = out.last().map_or(true, is_comma);
+        let ends_part = iter.peek().map_or(true, is_comma);
+        let expanded = if statements && starts_part && ends_part {
+            update(&operand, op)
+        } else {
+            value(&operand, op, postfix)
+        };
+        out.extend(expanded);
+    }
+    out.into_iter().collect()
+}
+
+fn recurse(tt: TokenTree) -> TokenTree {
+    match tt {
+        TokenTree::Group(g) if g.delimiter() != Delimiter::Brace => {
+            let mut new = Group::new(g.delimiter(), rewrite(g.stream(), false));
+            new.set_span(g.span());
+            TokenTree::Group(new)
+        }
+        tt => tt,
+    }
+}
+
+/// Whether `p` and `next` are `++` or `--`, returning the `+` or `-`.
+fn doubled(p: &Punct, next: Option<&TokenTree>) -> Option<char> {
+    let c = p.as_char();
+    if (c != '+' && c != '-') || p.spacing() != Spacing::Joint {
+        return None;
+    }
+    match next {
+        Some(TokenTree::Punct(n)) if n.as_char() == c => Some(c),
+        _ => None,
+    }
+}
+
+fn is_comma(tt: &TokenTree) -> bool {
+    matches!(tt, TokenTree::Punct(p) if p.as_char() == ',')
+}
+
+fn is_place_suffix(tt: &TokenTree) -> bool {
+    matches!(tt, TokenTree::Group(g) if g.delimiter() != Delimiter::Brace)
+}
+
+/// Whether `tt` can be a tuple field after a `.`, like the `0` in
+/// `self.0` (or the `0.1` in `x.0.1`, which is lexed as one literal).
+fn is_field_index(tt: &TokenTree) -> bool {
+    match tt {
+        TokenTree::Literal(l) => l.to_string().bytes().all(|b| b.is_ascii_digit() || b == b'.'),
+        _ => false,
+    }
+}
+
+/// Removes the place that a postfix `++` applies to, like `x`,
+/// `self.count`, `c.0` or `a[i]`, from the end of `out`.
+fn operand_before(out: &mut Vec<TokenTree>) -> Vec<TokenTree> {
+    let mut operand = vec![];
+    while let Some(last) = out.last() {
+        if is_place_suffix(last) {
+            operand.push(out.pop().unwrap());
+            continue;
+        }
+        let field_index = is_field_index(last);
+        if field_index || matches!(last, TokenTree::Ident(_)) {
+            let name = out.pop().unwrap();
+            match out.last() {
+                Some(TokenTree::Punct(p)) if p.as_char() == '.' => {
+                    operand.push(name);
+                    operand.push(out.pop().unwrap());
+                    continue;
+                }
+                // a field index on its own is a number, like `5++`
+                _ if field_index => out.push(name),
+                _ => operand.push(name),
+            }
+        }
+        break;
+    }
+    operand.reverse();
+    operand
+}
+
+/// Takes the place that a prefix `++` applies to from the front of
+/// `iter`, with the second `+` still to come.
+fn operand_after(iter: &mut Peekable<IntoIter<TokenTree>>) -> Vec<TokenTree> {
+    let mut rest = iter.clone();
+    rest.next();
+
+    let mut operand = vec![];
+    while let Some(TokenTree::Punct(p)) = rest.peek() {
+        if p.as_char() != '*' {
+            break;
+        }
+        operand.push(rest.next().unwrap());
+    }
+    match rest.peek() {
+        Some(TokenTree::Ident(_)) => operand.push(rest.next().unwrap()),
+        Some(tt) if is_place_suffix(tt) => operand.push(recurse(rest.next().unwrap())),
+        _ => return vec![],
+    }
+    loop {
+        match rest.peek() {
+            Some(tt) if is_place_suffix(tt) => operand.push(recurse(rest.next().unwrap())),
+            Some(TokenTree::Punct(p)) if p.as_char() == '.' => {
+                let dot = rest.next().unwrap();
+                match rest.peek() {
+                    Some(tt) if matches!(tt, TokenTree::Ident(_)) || is_field_index(tt) => {
+                        operand.push(dot);
+                        operand.push(rest.next().unwrap());
+                    }
+                    _ => {
+                        operand.push(dot);
+                        break;
+                    }
+                }
+            }
+            _ => break,
+        }
+    }
+    *iter = rest;
+    operand
+}
+
+/// `operand += 1` or `operand -= 1`.
+fn update(operand: &[TokenTree], op: char) -> TokenStream {
+    let op = Punct::new(op, Spacing::Joint);
+    quote!(#(#operand)* #op= 1)
+}
+
+/// C's `++operand` (if `!postfix`) or `operand++`, evaluating the
+/// place only once.
+fn value(operand: &[TokenTree], op: char, postfix: bool) -> TokenStream {
+    let place = Ident::new("__cfor_place", Span::mixed_site());
+    let old = Ident::new("__cfor_old", Span::mixed_site());
+    let update = update(&[TokenTree::Punct(Punct::new('*', Spacing::Alone)), place.clone().into()], op);
+    if postfix {
+        quote!(({ let #place = &mut #(#operand)*; let #old = *#place; #update; #old }))
+    } else {
+        quote!(({ let #place = &mut #(#operand)*; #update; *#place }))
+    }
+}
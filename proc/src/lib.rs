@@ -0,0 +1,122 @@
+//! Procedural macros for [`cfor`](https://crates.io/crates/cfor).
+//!
+//! These are re-exported from `cfor` when its `cfor-proc` feature is
+//! enabled, and should be used from there, since the code they
+//! generate refers to `::cfor`.
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::visit_mut::{self, VisitMut};
+use syn::{parse_macro_input, Attribute, Expr, ExprLoop, Item, Meta};
+
+mod header;
+mod incdec;
+
+/// A `cfor!` that points out mistakes in the loop precisely.
+///
+/// This takes exactly the same input as `cfor!` (and expands to it),
+/// but, rather than a generic "no rules expected this token", an
+/// invalid loop gets an error naming what was expected at the
+/// offending token.
+///
+/// It also accepts C's `++` and `--` operators in the condition and
+/// the step; see the `cfor` docs.
+///
+/// ```compile_fail
+/// # use cfor_proc::cfor_proc;
+/// // error: expected `;` between the condition and the step
+/// cfor_proc!{let mut i = 0; i < 10 i += 1; {}}
+/// ```
+///
+/// ```compile_fail
+/// # use cfor_proc::cfor_proc;
+/// // error: expected the loop body, in braces: `{ ... }`
+/// cfor_proc!{let mut i = 0; i < 10; i += 1; println!("{}", i)}
+/// ```
+#[proc_macro]
+pub fn cfor_proc(input: TokenStream) -> TokenStream {
+    parse_macro_input!(input as header::Loop).expand().into()
+}
+
+/// Rewrites the C-style loops in an item into `cfor!` invocations.
+///
+/// See the `cfor` docs for more information.
+#[proc_macro_attribute]
+pub fn c_for(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut item = parse_macro_input!(item as Item);
+
+    let mut rewriter = Rewriter { errors: vec![] };
+    if !attr.is_empty() {
+        rewriter.errors.push(syn::Error::new(
+            TokenStream2::from(attr).into_iter().next().unwrap().span(),
+            "`#[c_for]` takes no arguments here; headers go on the loops \
+             inside, like `#[c_for(init; cond; step)] loop { ... }`",
+        ));
+    }
+    rewriter.visit_item_mut(&mut item);
+
+    let errors = rewriter.errors.iter().map(syn::Error::to_compile_error);
+    quote!(#item #(#errors)*).into()
+}
+
+struct Rewriter {
+    errors: Vec<syn::Error>,
+}
+
+impl VisitMut for Rewriter {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        // handle nested loops first, so that the bodies are plain
+        // Rust by the time they're handed to `cfor!`.
+        visit_mut::visit_expr_mut(self, expr);
+
+        let attrs = match expr {
+            Expr::Loop(l) => {
+                if let Some(i) = l.attrs.iter().position(is_c_for) {
+                    let header = l.attrs.remove(i);
+                    match rewrite_loop(header, l) {
+                        Ok(tokens) => *expr = Expr::Verbatim(tokens),
+                        Err(e) => self.errors.push(e),
+                    }
+                }
+                return;
+            }
+            Expr::ForLoop(l) => &l.attrs,
+            Expr::While(l) => &l.attrs,
+            _ => return,
+        };
+        if let Some(attr) = attrs.iter().find(|a| is_c_for(a)) {
+            self.errors.push(syn::Error::new_spanned(
+                attr,
+                "`#[c_for(...)]` should be placed on a `loop`",
+            ));
+        }
+    }
+}
+
+fn is_c_for(attr: &Attribute) -> bool {
+    attr.path().is_ident("c_for")
+}
+
+/// Turns `#[c_for(header)] 'label: loop { body }` into
+/// `::cfor::cfor!{'label: header; { body }}`.
+fn rewrite_loop(header: Attribute, l: &ExprLoop) -> syn::Result<TokenStream2> {
+    let header = match header.meta {
+        Meta::List(list) => list.tokens,
+        meta => {
+            return Err(syn::Error::new_spanned(
+                meta,
+                "expected a loop header, like `#[c_for(init; cond; step)]`",
+            ))
+        }
+    };
+
+    let ExprLoop { attrs, label, body, .. } = l;
+    let expanded = header::Loop::from_parts(label.clone(), header, body.clone())?.expand();
+    Ok(quote! {
+        #(#attrs)*
+        #expanded
+    })
+}
@@ -0,0 +1,65 @@
+use cfor_proc::c_for;
+
+#[c_for]
+fn sum_to(n: u32) -> u32 {
+    let mut sum = 0;
+    #[c_for(let mut i = 0; i < n; i += 1)]
+    loop {
+        if i % 2 == 0 { continue }
+        sum += i;
+    }
+    sum
+}
+
+#[test]
+fn function() {
+    assert_eq!(sum_to(10), 1 + 3 + 5 + 7 + 9);
+}
+
+#[test]
+#[c_for]
+fn nested_and_labelled() {
+    let mut pairs = vec![];
+    #[c_for(let mut i = 0; i < 4; i += 1)]
+    'outer: loop {
+        #[c_for(let mut j = 0; j < 4; j += 1)]
+        loop {
+            if j > i { continue 'outer }
+            if i == 3 { break 'outer }
+            pairs.push((i, j));
+        }
+    }
+    assert_eq!(pairs, [(0, 0), (1, 0), (1, 1), (2, 0), (2, 1), (2, 2)]);
+}
+
+#[test]
+#[c_for]
+fn other_loops_untouched() {
+    let mut tick = 0;
+    #[c_for(;;)]
+    loop {
+        for _ in 0..3 { tick += 1 }
+        while tick < 5 { tick += 1 }
+        break
+    }
+    assert_eq!(tick, 5);
+}
+
+struct Counter(u32);
+
+#[c_for]
+impl Counter {
+    fn count_down(&mut self) -> u32 {
+        let mut steps = 0;
+        #[c_for(; self.0 > 0; self.0 -= 1)]
+        loop {
+            steps += 1;
+        }
+        steps
+    }
+}
+
+#[test]
+fn methods() {
+    assert_eq!(Counter(5).count_down(), 5);
+}
@@ -0,0 +1,158 @@
+use cfor_proc::cfor_proc;
+
+#[test]
+fn smoketest() {
+    let mut tick = 0;
+    cfor_proc!{let mut i = 1; i <= 0b1000_0000; i *= 2; {
+        tick += 1;
+    }}
+    assert_eq!(tick, 8);
+
+    tick = 0;
+    cfor_proc!{let (mut a, mut b): (u32, u32) = (0, 0); a + b < 20; { a += 1; b += 1; }; {
+        tick += 1;
+    }}
+    assert_eq!(tick, 10);
+}
+
+#[test]
+fn missing_parts() {
+    let mut tick = 0;
+    cfor_proc!{;; (); {
+        tick += 1;
+        break
+    }}
+    cfor_proc!{; false; ; {
+        panic!()
+    }}
+    cfor_proc!{let mut i = 0; i < 3;; {
+        tick += 1;
+        i += 1;
+    }}
+    assert_eq!(tick, 4);
+}
+
+#[test]
+fn all_forms() {
+    let v = [Some(1), Some(3), None, Some(2)];
+    let mut seen = vec![];
+    cfor_proc!{'outer: let mut i = 0, let mut j = 10; let Some(&Some(x)) = v.get(i) && x < 10; i += 1, j -= 1; {
+        for _ in 0..i {
+            if j == 0 { break 'outer }
+        }
+        seen.push((x, j));
+    } else {
+        seen.push((0, 0));
+    } finally {
+        seen.push((i, j));
+    }}
+    assert_eq!(seen, [(1, 10), (3, 9), (0, 0), (2, 8)]);
+
+    seen.clear();
+    cfor_proc!{(let mut x = 1; x < 10; x *= 3) {
+        seen.push((x, 0));
+    }}
+    cfor_proc!{{ let n = v.len(); let mut k = n; }; { let m = k; m > 2 }; k -= 1; {
+        seen.push((k, n));
+    }}
+    assert_eq!(seen, [(1, 0), (3, 0), (9, 0), (4, 4), (3, 4)]);
+}
+
+#[test]
+fn increments() {
+    let mut v = vec![];
+    cfor_proc!{let mut i = 0, let mut j = 10; i < 3; i++, j--; {
+        v.push((i, j));
+    }}
+    assert_eq!(v, [(0, 10), (1, 9), (2, 8)]);
+
+    // the values that C would give
+    let mut v = vec![];
+    let mut i = 0;
+    cfor_proc!{; i++ < 3;; {
+        v.push(i);
+    }}
+    assert_eq!(v, [1, 2, 3]);
+    assert_eq!(i, 4);
+
+    let mut v = vec![];
+    let mut i = 0;
+    cfor_proc!{; ++i < 3;; {
+        v.push(i);
+    }}
+    assert_eq!(v, [1, 2]);
+
+    let mut v = vec![];
+    let mut a = [0, 0, 0];
+    cfor_proc!{let mut i = 0; i < 3; v.push(a[i]++), ++i; {
+        a[i] += 5;
+    }}
+    assert_eq!(v, [5, 5, 5]);
+    assert_eq!(a, [6, 6, 6]);
+
+    let mut n = 0;
+    cfor_proc!{let mut i = 3; i-- > 0;; {
+        n += 1;
+    }}
+    assert_eq!(n, 3);
+}
+
+#[test]
+fn increments_tuple_fields() {
+    struct Counter(u32, (i32, i32));
+
+    let mut c = Counter(0, (0, 5));
+    let mut v = vec![];
+    cfor_proc!{; c.0 < 3; c.0++, --c.1.1; {
+        v.push((c.0, c.1.1));
+    }}
+    assert_eq!(v, [(0, 5), (1, 4), (2, 3)]);
+
+    let mut t = (0, 0);
+    cfor_proc!{; t.1++ < 2; ++t.0; {}}
+    assert_eq!(t, (2, 3));
+}
+
+#[test]
+fn trailing_separators() {
+    let mut v = vec![];
+    cfor_proc!{let mut i = 0, let mut j = 2,; i < j; i += 1, j -= 1,; {
+        v.push((i, j));
+    };}
+    assert_eq!(v, [(0, 2)]);
+}
+
+#[test]
+fn ranges() {
+    let mut v = vec![];
+    cfor_proc!{i in 0..10 step 5 {
+        v.push(i);
+    }}
+    cfor_proc!{'outer: i in 1..=3 {
+        if i == 2 { continue 'outer }
+        v.push(i);
+    } else {
+        v.push(100);
+    }}
+    cfor_proc!{i in 9u8 downto 1 step 4 {
+        v.push(i);
+    }}
+    assert_eq!(v, [0, 5, 1, 3, 100, 9, 5, 1]);
+}
+
+#[test]
+fn enumerate_and_iterators() {
+    let mut v = vec![];
+    cfor_proc!{enumerate k; let mut x = 1; x < 100; x *= 3; {
+        v.push((k, x));
+    }}
+    cfor_proc!{enumerate k; i in 10..12 {
+        v.push((k, i));
+    }}
+    assert_eq!(v, [(0, 1), (1, 3), (2, 9), (3, 27), (4, 81), (0, 10), (1, 11)]);
+
+    let powers: Vec<_> = cfor_proc!(let mut x = 1; x < 100; x *= 3 => x).collect();
+    assert_eq!(powers, [1, 3, 9, 27, 81]);
+    let counts: Vec<_> = cfor_proc!((let mut i = 0; i < 3; i++) => i * 10).collect();
+    assert_eq!(counts, [0, 10, 20]);
+}
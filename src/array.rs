@@ -0,0 +1,42 @@
+//! The runtime part of `array_init_cfor!`, which fills in an array an
+//! element at a time, in a way that works in `const fn`s.
+
+use core::mem::{ManuallyDrop, MaybeUninit};
+
+pub struct ArrayBuilder<T, const N: usize> {
+    array: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+union Transmute<T, const N: usize> {
+    uninit: ManuallyDrop<[MaybeUninit<T>; N]>,
+    init: ManuallyDrop<[T; N]>,
+}
+
+impl<T, const N: usize> ArrayBuilder<T, N> {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        ArrayBuilder {
+            // SAFETY: an array of `MaybeUninit`s doesn't need to be
+            // initialised.
+            array: unsafe { MaybeUninit::<[MaybeUninit<T>; N]>::uninit().assume_init() },
+            len: 0,
+        }
+    }
+
+    /// Sets the next element (this takes and returns `self`, since
+    /// `&mut` isn't allowed in a `const fn` on older compilers).
+    pub const fn push(mut self, value: T) -> Self {
+        self.array[self.len] = MaybeUninit::new(value);
+        self.len += 1;
+        self
+    }
+
+    pub const fn finish(self) -> [T; N] {
+        assert!(self.len == N, "`array_init_cfor!` didn't set every element");
+        let uninit = Transmute { uninit: ManuallyDrop::new(self.array) };
+        // SAFETY: every element has been set, and the two array types
+        // have the same layout.
+        ManuallyDrop::into_inner(unsafe { uninit.init })
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// An exponential backoff policy, giving the delays between the
+/// attempts of a `retry!`.
+///
+/// The first delay is `initial`, and each later one is the previous
+/// one times the multiplier (`2.0`, by default), up to the cap (by
+/// default, there is none). With jitter, each delay is instead chosen
+/// uniformly at random between zero and that value, so that many
+/// clients failing at once don't all retry at once too.
+///
+/// ```rust
+/// use std::time::Duration;
+/// use cfor::Backoff;
+///
+/// let mut backoff = Backoff::new(Duration::from_millis(100))
+///     .multiplier(3.0)
+///     .cap(Duration::from_secs(1));
+///
+/// let delays: Vec<_> = (0..4).map(|_| backoff.next_delay().as_millis()).collect();
+/// assert_eq!(delays, [100, 300, 900, 1000]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Backoff {
+    next: Duration,
+    multiplier: f64,
+    cap: Duration,
+    jitter: Option<RandomState>,
+    count: u64,
+}
+
+impl Backoff {
+    /// A policy whose first delay is `initial`.
+    pub fn new(initial: Duration) -> Backoff {
+        Backoff {
+            next: initial,
+            multiplier: 2.0,
+            cap: Duration::MAX,
+            jitter: None,
+            count: 0,
+        }
+    }
+
+    /// Sets the factor by which each delay is longer than the last.
+    pub fn multiplier(mut self, multiplier: f64) -> Backoff {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Sets the longest delay.
+    pub fn cap(mut self, cap: Duration) -> Backoff {
+        self.cap = cap;
+        self.next = self.next.min(cap);
+        self
+    }
+
+    /// Sets whether each delay is randomised.
+    pub fn jitter(mut self, jitter: bool) -> Backoff {
+        self.jitter = if jitter { Some(RandomState::new()) } else { None };
+        self
+    }
+
+    /// The delay before the next attempt.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.next;
+
+        // (this is careful to not overflow a `Duration` when there's
+        // no cap.)
+        let secs = delay.as_secs_f64() * self.multiplier;
+        self.next = if secs < self.cap.as_secs_f64() {
+            Duration::from_secs_f64(secs.max(0.0))
+        } else {
+            self.cap
+        };
+
+        match self.jitter {
+            None => delay,
+            Some(ref state) => {
+                // a hash of the count is random enough for this, and
+                // avoids needing a dependency for it.
+                let mut hasher = state.build_hasher();
+                hasher.write_u64(self.count);
+                self.count += 1;
+                let fraction = (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64;
+                delay.mul_f64(fraction)
+            }
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use core::fmt;
+
+/// The error from a loop that gave up before its condition held.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Timeout;
+
+impl fmt::Display for Timeout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("timed out")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Timeout {}
+
+/// The error from a loop that stopped because it reached its limit on
+/// iterations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Exhausted;
+
+impl fmt::Display for Exhausted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("iteration limit reached")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Exhausted {}
+
+/// The error from a numeric iteration that reached its limit on
+/// iterations before its error was within the tolerance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DidNotConverge;
+
+impl fmt::Display for DidNotConverge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("did not converge")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DidNotConverge {}
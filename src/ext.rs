@@ -0,0 +1,117 @@
+use core::fmt;
+use core::iter::FusedIterator;
+
+use crate::{CRange, Integer};
+
+/// Methods on integers for writing C-style loops as a chain, rather
+/// than with a macro.
+///
+/// `start.c_to(end)` is the start of `for (i = start; i < end; ...)`,
+/// and it's finished off with the step: `.step(s)` for `i += s`, or
+/// `.step_with(f)` for `i = f(i)`. On its own, it is a loop with `i++`.
+///
+/// ```rust
+/// use cfor::CForExt;
+///
+/// let mut powers = vec![];
+/// 1u32.c_to(1 << 20).step_with(|x| x * 3).for_each(|x| powers.push(x));
+/// assert_eq!(powers.len(), 13);
+/// assert_eq!(powers[..5], [1, 3, 9, 27, 81]);
+///
+/// let evens: Vec<i32> = 0.c_to(10).step(2).collect();
+/// assert_eq!(evens, [0, 2, 4, 6, 8]);
+///
+/// let all: Vec<u8> = 250.c_to(255).into_iter().collect();
+/// assert_eq!(all, [250, 251, 252, 253, 254]);
+/// ```
+pub trait CForExt: Integer {
+    /// The start of a loop from `self` while below `end`.
+    fn c_to(self, end: Self) -> CTo<Self> {
+        CTo { start: self, end }
+    }
+}
+
+impl<T: Integer> CForExt for T {}
+
+/// A loop from [`CForExt::c_to`], waiting for its step.
+#[derive(Clone, Copy, Debug)]
+pub struct CTo<T> {
+    start: T,
+    end: T,
+}
+
+impl<T: Integer> CTo<T> {
+    /// The loop with `i += step`, like `CRange::new`.
+    ///
+    /// # Panics
+    ///
+    /// If `step` is zero.
+    pub fn step(self, step: T) -> CRange<T> {
+        CRange::new(self.start, self.end, step)
+    }
+
+    /// The loop with `i = step(i)`.
+    ///
+    /// Like the C loop, this goes on forever if `step` never reaches
+    /// the end, e.g. multiplying from zero, and `step` panics in the
+    /// same way as the expression would, e.g. if it overflows. It only
+    /// runs once the value after it is needed, so the loop can stop
+    /// early without the step overflowing.
+    pub fn step_with<F: FnMut(T) -> T>(self, step: F) -> StepWith<T, F> {
+        StepWith {
+            next: Some(self.start),
+            first: true,
+            end: self.end,
+            step,
+        }
+    }
+}
+
+impl<T: Integer> IntoIterator for CTo<T> {
+    type Item = T;
+    type IntoIter = CRange<T>;
+
+    /// The loop with `i++`.
+    fn into_iter(self) -> CRange<T> {
+        self.step(T::from_bits(1))
+    }
+}
+
+/// The iterator from [`CTo::step_with`].
+#[derive(Clone)]
+pub struct StepWith<T, F> {
+    // `None` once the end has been reached.
+    next: Option<T>,
+    first: bool,
+    end: T,
+    step: F,
+}
+
+impl<T: Integer, F: FnMut(T) -> T> Iterator for StepWith<T, F> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let mut current = self.next?;
+        if !self.first {
+            current = (self.step)(current);
+        }
+        self.first = false;
+        if current < self.end {
+            self.next = Some(current);
+        } else {
+            self.next = None;
+        }
+        self.next
+    }
+}
+
+impl<T: Integer, F: FnMut(T) -> T> FusedIterator for StepWith<T, F> {}
+
+impl<T: fmt::Debug, F> fmt::Debug for StepWith<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StepWith")
+            .field("next", &self.next)
+            .field("end", &self.end)
+            .finish_non_exhaustive()
+    }
+}
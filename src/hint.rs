@@ -0,0 +1,18 @@
+/// Tells the optimiser that `invariant` is true, for use in the header
+/// of a `cfor_assume!` (with the `unchecked` feature).
+///
+/// In debug builds, `invariant` is checked instead, and this panics if
+/// it is false.
+///
+/// # Safety
+///
+/// `invariant` must be true. If it isn't, the behaviour is undefined.
+#[track_caller]
+#[inline]
+pub unsafe fn assume(invariant: bool) {
+    debug_assert!(invariant, "`cfor_assume!` invariant is false");
+    if !invariant {
+        // SAFETY: the caller promised that this is true.
+        unsafe { core::hint::unreachable_unchecked() }
+    }
+}
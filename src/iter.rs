@@ -0,0 +1,105 @@
+use core::fmt;
+use core::iter::FusedIterator;
+
+/// A C-style `for` loop as an iterator.
+///
+/// `iter(init, cond, step)` yields the state of the loop at the start
+/// of each iteration: first `init`, then the result of running `step`
+/// on it, and so on, for as long as `cond` holds, like the loop
+/// variable of `for (s = init; cond(s); step(s))`. It's lazy, so
+/// `step` only runs when the next value is needed, and it can feed
+/// into iterator adaptors like `map`, `zip` and `collect`.
+///
+/// ```rust
+/// let powers: Vec<u32> = cfor::iter(1, |x| *x < 100, |x| *x *= 3).collect();
+/// assert_eq!(powers, [1, 3, 9, 27, 81]);
+///
+/// // the state can be anything, like a pair for the Fibonacci numbers.
+/// let fib: Vec<u64> = cfor::iter((0, 1), |&(a, _)| a < 50, |s| *s = (s.1, s.0 + s.1))
+///     .map(|(a, _)| a)
+///     .collect();
+/// assert_eq!(fib, [0, 1, 1, 2, 3, 5, 8, 13, 21, 34]);
+/// ```
+pub fn iter<S, C, F>(init: S, cond: C, step: F) -> CFor<S, C, F>
+where
+    S: Clone,
+    C: FnMut(&S) -> bool,
+    F: FnMut(&mut S),
+{
+    CFor {
+        state: Some(init),
+        first: true,
+        cond,
+        step,
+    }
+}
+
+/// The iterator from [`iter`], a C-style `for` loop.
+///
+/// This can be named, unlike an `impl Iterator`, so it can be stored
+/// in a struct, or returned from a trait method. It is `Clone` when
+/// the state and closures are, to fork a loop part way through, and
+/// always gives `None` once the condition has been false.
+///
+/// ```rust
+/// use cfor::CFor;
+///
+/// struct Halvings {
+///     inner: CFor<u32, fn(&u32) -> bool, fn(&mut u32)>,
+/// }
+///
+/// fn halvings(n: u32) -> Halvings {
+///     Halvings { inner: cfor::iter(n, |x| *x > 0, |x| *x /= 2) }
+/// }
+///
+/// let mut h = halvings(20);
+/// let rest = h.inner.clone();
+/// assert_eq!(h.inner.next(), Some(20));
+/// assert_eq!(h.inner.collect::<Vec<_>>(), [10, 5, 2, 1]);
+/// assert_eq!(rest.count(), 5);
+/// ```
+#[derive(Clone)]
+pub struct CFor<S, C, F> {
+    // `None` once the condition has been false.
+    state: Option<S>,
+    first: bool,
+    cond: C,
+    step: F,
+}
+
+impl<S, C, F> Iterator for CFor<S, C, F>
+where
+    S: Clone,
+    C: FnMut(&S) -> bool,
+    F: FnMut(&mut S),
+{
+    type Item = S;
+
+    fn next(&mut self) -> Option<S> {
+        let current = self.state.as_mut()?;
+        if !self.first {
+            (self.step)(current);
+        }
+        self.first = false;
+        if (self.cond)(current) {
+            Some(current.clone())
+        } else {
+            self.state = None;
+            None
+        }
+    }
+}
+
+impl<S, C, F> FusedIterator for CFor<S, C, F>
+where
+    S: Clone,
+    C: FnMut(&S) -> bool,
+    F: FnMut(&mut S),
+{
+}
+
+impl<S: fmt::Debug, C, F> fmt::Debug for CFor<S, C, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CFor").field("state", &self.state).finish_non_exhaustive()
+    }
+}
@@ -62,6 +62,10 @@
 //! }
 //! ```
 //!
+//! (This relies on `0x1000` being below the largest power of 2 that
+//! fits in `x`; otherwise `x *= 2` overflows. [`powers!`] stops
+//! before overflowing instead.)
+//!
 //! ## Intrabody condition
 //!
 //! If a condition requires some extra computation to be checked (or
@@ -112,6 +116,22 @@
 //! }
 //! ```
 //!
+//! ## C-style header
+//!
+//! The header can also be wrapped in parentheses, followed by the
+//! body, which makes translating C code nearly mechanical.
+//!
+//! ```rust
+//! #[macro_use] extern crate cfor;
+//!
+//! fn main() {
+//!     // for (int x = 1; x < 0x1000; x *= 2) { ... }
+//!     cfor!{(let mut x = 1; x < 0x1000; x *= 2) {
+//!         println!("power of 2: {}", x);
+//!     }}
+//! }
+//! ```
+//!
 //! # Handling `continue`
 //!
 //! (Or, "why is the macro so complicated?")
@@ -164,6 +184,122 @@
 //! contains a `continue`, the `$step` at the end of the loop body
 //! will never be evaluated.
 //! 
+//! # Pattern conditions
+//!
+//! Like `while let`, the condition can be a refutable pattern match,
+//! `let pattern = expression`, with the loop stopping when the match
+//! fails. Any bindings from the pattern can be used in the body and
+//! the step.
+//!
+//! ```rust
+//! #[macro_use] extern crate cfor;
+//!
+//! fn main() {
+//!     let words = ["a", "bb", "", "ccc"];
+//!
+//!     cfor!{let mut it = words.iter(), let mut i = 0; let Some(w) = it.next(); i += w.len(); {
+//!         if w.is_empty() { continue }
+//!         println!("{} starts at {}", w, i);
+//!     }}
+//! }
+//! ```
+//!
+//! A condition can also be a chain of `let`s and boolean expressions
+//! separated by `&&`, like a `let` chain, with each part being
+//! checked in order, and the bindings from each `let` being visible
+//! in the later parts.
+//!
+//! ```rust
+//! #[macro_use] extern crate cfor;
+//!
+//! fn main() {
+//!     let v = [Some(1), Some(5), None, Some(2)];
+//!
+//!     // stop at the first `None`, or anything too large.
+//!     cfor!{let mut i = 0; let Some(&Some(x)) = v.get(i) && x < 4; i += 1; {
+//!         println!("{}: {}", i, x);
+//!     }}
+//! }
+//! ```
+//!
+//! # `else`
+//!
+//! Like Python, a `cfor!` can have an `else` block after the body,
+//! which runs only if the loop stopped because `condition` was
+//! `false`, not because of a `break`. Variables declared in the
+//! initialiser are usable in the `else` block too.
+//!
+//! ```rust
+//! #[macro_use] extern crate cfor;
+//!
+//! fn main() {
+//!     let n = 97;
+//!     cfor!{let mut d = 2; d * d <= n; d += 1; {
+//!         if n % d == 0 {
+//!             println!("{} = {} * {}", n, d, n / d);
+//!             break
+//!         }
+//!     } else {
+//!         println!("{} is prime (checked up to {})", n, d);
+//!     }}
+//! }
+//! ```
+//!
+//! # `finally`
+//!
+//! A `finally` block can be placed after the body (and after the
+//! `else` block, if there is one), and runs whenever the loop
+//! finishes, whether by `condition` becoming `false` or by a `break`.
+//! It can use the variables declared in the initialiser.
+//!
+//! ```rust
+//! #[macro_use] extern crate cfor;
+//!
+//! fn main() {
+//!     let data = [1, 2, 3, 0, 4];
+//!     let mut out = vec![];
+//!
+//!     cfor!{let mut buf = vec![], let mut i = 0; i < data.len(); i += 1; {
+//!         if data[i] == 0 { break }
+//!         buf.push(data[i]);
+//!     } finally {
+//!         // flush whatever was buffered.
+//!         out.extend(buf);
+//!     }}
+//!
+//!     assert_eq!(out, [1, 2, 3]);
+//! }
+//! ```
+//!
+//! Since it is placed after the loop, a `finally` block does not run
+//! if the body leaves the whole function (e.g. via `return` or `?`),
+//! or panics. See [`try_cfor!`] for a loop where a `?` runs the
+//! `finally` block.
+//!
+//! # Labels
+//!
+//! A `cfor!` can be given a label, written before the initialiser,
+//! which can be used with `break` and `continue` anywhere in the
+//! body, including inside nested loops. Like an unlabelled
+//! `continue`, a `continue 'label` evaluates `step` before checking
+//! `condition`.
+//!
+//! ```rust
+//! #[macro_use] extern crate cfor;
+//!
+//! fn main() {
+//!     cfor!{'outer: let mut i = 0; i < 10; i += 1; {
+//!         for j in 0..10 {
+//!             // skip to the next `i`, incrementing it.
+//!             if j > i { continue 'outer }
+//!             println!("({}, {})", i, j);
+//!
+//!             if i * j > 50 { break 'outer }
+//!         }
+//!     }}
+//! }
+//! ```
+//!
 //! # Handling multiple initializations and steps
 //!
 //! Like C loops, `cfor!` supports specfying multiple initializations and steps seperated by a comma.
@@ -177,60 +313,5810 @@
 //!     }}
 //! }
 //! ```
+//!
+//! Both lists can have a trailing comma, and the whole invocation a
+//! trailing `;` after the body, which is convenient for generated code.
+//!
+//! If the initialisation needs more than a list of declarations, it
+//! can be written as a block of statements, any bindings from which
+//! are visible in the rest of the loop (unlike a normal block).
+//!
+//! ```rust
+//! #[macro_use] extern crate cfor;
+//!
+//! fn main() {
+//!     let v = [1, 2, 3, 4, 5, 6, 7];
+//!
+//!     cfor!{{ let n = v.len(); let mut i = n / 2; }; i < n; i += 1; {
+//!         println!("second half: {}", v[i]);
+//!     }}
+//! }
+//! ```
+//!
+//! Similarly, the condition is an arbitrary expression, so a block
+//! can be used when it needs some temporaries.
+//!
+//! ```rust
+//! #[macro_use] extern crate cfor;
+//!
+//! fn main() {
+//!     let v = [3, 1, 4, 0, 5];
+//!
+//!     cfor!{let mut i = 0; { let x = v.get(i); matches!(x, Some(&x) if x != 0) }; i += 1; {
+//!         println!("nonzero prefix: {}", v[i]);
+//!     }}
+//! }
+//! ```
+//!
+//! The step is an arbitrary expression, so it can also be a block
+//! containing several statements, which can be clearer than a long
+//! list of comma-separated steps.
+//!
+//! ```rust
+//! #[macro_use] extern crate cfor;
+//!
+//! fn main() {
+//!     let mut log = vec![];
+//!
+//!     cfor!{let mut x = 1; x < 100; { x *= 3; log.push(x); }; {
+//!         if x % 2 == 0 { continue }
+//!         println!("x: {}", x);
+//!     }}
+//!
+//!     assert_eq!(log, [3, 9, 27, 81, 243]);
+//! }
+//! ```
+//!
+//! # Counting iterations
+//!
+//! A loop can start with `enumerate k;`, which gives it a counter
+//! `k`, starting at zero and going up by one each iteration, no
+//! matter what the step does. It's a `usize`, and it's separate from
+//! the loop's own variables, so changing it in the body doesn't
+//! affect the next iteration.
+//!
+//! ```rust
+//! #[macro_use] extern crate cfor;
+//!
+//! fn main() {
+//!     let mut powers = vec![];
+//!     cfor!(enumerate k; let mut x = 1; x < 100; x *= 3; {
+//!         powers.push((k, x));
+//!     });
+//!     assert_eq!(powers, [(0, 1), (1, 3), (2, 9), (3, 27), (4, 81)]);
+//! }
+//! ```
+//!
+//! # Ranges
+//!
+//! The most common loop, counting through a range, can also be
+//! written with the range itself, as `cfor!(i in a..b step s { body
+//! })`, which runs `body` for `i` equal to `a`, `a + s`, `a + 2 * s`,
+//! ..., for as long as it is less than `b`, like C's `for (i = a; i <
+//! b; i += s) { body }`. With `a..=b`, `i` goes up to `b` too, like
+//! `for (i = a; i <= b; i += s) { body }`, and the `step s` can be
+//! left out, for a step of `1`.
+//!
+//! ```rust
+//! #[macro_use] extern crate cfor;
+//!
+//! fn main() {
+//!     let v = [5, 1, 4, 2, 3, 0];
+//!     let mut evens = vec![];
+//!     cfor!(i in 0..v.len() step 2 {
+//!         evens.push(v[i]);
+//!     });
+//!     assert_eq!(evens, [5, 4, 3]);
+//!
+//!     let mut seen = vec![];
+//!     cfor!(i in 1..=10 step 3 {
+//!         seen.push(i);
+//!     });
+//!     assert_eq!(seen, [1, 4, 7, 10]);
+//! }
+//! ```
+//!
+//! Counting down is written `cfor!(i in b downto a step s { body })`,
+//! like Pascal, running `body` for `i` equal to `b`, `b - s`, ..., for
+//! as long as it is at least `a`, like C's `for (i = b; i >= a; i -=
+//! s) { body }`. Both ends are included, so `i in n - 1 downto 0` is
+//! the same as `(0..n).rev()`, and [`cfor_rev!`] counts down a
+//! half-open range directly.
+//!
+//! ```rust
+//! #[macro_use] extern crate cfor;
+//!
+//! fn main() {
+//!     let mut seen = vec![];
+//!     cfor!(i in 10u32 downto 0 step 4 {
+//!         seen.push(i);
+//!     });
+//!     assert_eq!(seen, [10, 6, 2]);
+//! }
+//! ```
+//!
+//! Unlike those C loops, this never steps `i` past the far end, so it
+//! doesn't overflow when that is near the largest (or, counting down,
+//! smallest) value of the type, like `0` for an unsigned `i`. The C
+//! loops would instead never end or, in a debug build of a direct
+//! translation, panic. An inclusive range still includes the far end
+//! itself when it's reached exactly. The bounds and step are
+//! evaluated once, before the loop, and the step must be positive;
+//! any integer type works, even when `b - a` doesn't fit in it.
+//! `i` is declared `mut`, and can be changed in the body, as in C.
+//! Everything else is the same as for a full `cfor!`, including
+//! labels, `continue`, `else` and `finally`.
+//!
+//! ```rust
+//! #[macro_use] extern crate cfor;
+//!
+//! fn main() {
+//!     let mut count = 0;
+//!     cfor!(_i in 0..=u8::MAX step 5 {
+//!         count += 1;
+//!     });
+//!     assert_eq!(count, 52);
+//! }
+//! ```
+//!
+//! The same progressions are available as an iterator, [`CRange`],
+//! for when they need to go through iterator adaptors, or be stored.
+//! [`FRange`] is the equivalent for floating point numbers, avoiding
+//! the drift of repeatedly adding the step.
+//!
+//! ```rust
+//! use cfor::CRange;
+//!
+//! let squares: Vec<i32> = CRange::inclusive(10, 0, -5).map(|i| i * i).collect();
+//! assert_eq!(squares, [100, 25, 0]);
+//! ```
+//!
+//! # As an iterator
+//!
+//! Instead of a body, the header can be followed by `=> expr`, and
+//! then the loop is an iterator that evaluates `expr` at the start of
+//! each iteration, producing it as the next value. It's lazy, so it
+//! only runs the step when the next value is needed, and can be
+//! stored with `let`, collected, or used in an iterator chain.
+//!
+//! ```rust
+//! #[macro_use] extern crate cfor;
+//!
+//! fn main() {
+//!     let v = [3, 1, 4, 1, 5];
+//!     let data = &v;
+//!
+//!     let pairs: Vec<_> = cfor!(let mut i = 0; i + 1 < data.len(); i += 1 => data[i] + data[i + 1])
+//!         .collect();
+//!     assert_eq!(pairs, [4, 5, 5, 6]);
+//!
+//!     let mut big = cfor!(let mut x = 1u64; ; x *= 10 => x).filter(|x| x % 7 == 6);
+//!     assert_eq!(big.next(), Some(1_000));
+//! }
+//! ```
+//!
+//! The iterator owns the variables from the initialiser, and anything
+//! else the loop uses (it's a `move` closure), so data that's needed
+//! after the loop should be borrowed first, like `data` above.
+//!
+//! # Without macros
+//!
+//! Any loop can be written without a macro with [`iter`], which takes
+//! the initial state of the loop and closures for the condition and
+//! the step, and gives an iterator over the state at the start of
+//! each iteration.
+//!
+//! ```rust
+//! let v: Vec<_> = cfor::iter(1u32, |x| *x < 1000, |x| *x = *x * 4 + 1).collect();
+//! assert_eq!(v, [1, 5, 21, 85, 341]);
+//! ```
+//!
+//! [`run`] runs a loop whose body is a closure too, returning a
+//! `ControlFlow` to say when to `break`, and [`Loop`] builds the same
+//! loops up as a value. For counting loops, [`CForExt`] spells the
+//! header as methods on the starting integer, like
+//! `1u32.c_to(n).step_with(|x| x * 3)`.
+//!
+//! # Mistakes
+//!
+//! The common mistakes (a missing step, a body without braces, an
+//! extra `;`, or stray tokens after the body) are reported with a
+//! message saying what was expected, e.g. this fails to compile with
+//! "expected the loop body, in braces: `{ ... }`".
+//!
+//! ```rust,compile_fail
+//! #[macro_use] extern crate cfor;
+//!
+//! fn main() {
+//!     cfor!{let mut i = 0; i < 10; i += 1; println!("{}", i)}
+//! }
+//! ```
+//!
+//! # `#[c_for]`
+//!
+//! With the `cfor-proc` feature enabled, the `#[c_for]` attribute can
+//! be placed on a function (or any other item), to allow writing
+//! its C-style loops without the `cfor!{...}` wrapper. Each such
+//! loop is a `loop` with its header in a `#[c_for(...)]` attribute,
+//! and is rewritten to the equivalent `cfor!`.
+//!
+//! ```toml
+//! [dependencies]
+//! cfor = { version = "1.1", features = ["cfor-proc"] }
+//! ```
+//!
+//! ```rust
+//! # #[cfg(feature = "cfor-proc")] mod m {
+//! use cfor::c_for;
+//!
+//! #[c_for]
+//! fn sum_odd(n: u32) -> u32 {
+//!     let mut sum = 0;
+//!     #[c_for(let mut i = 0; i < n; i += 1)]
+//!     loop {
+//!         if i % 2 == 0 { continue }
+//!         sum += i;
+//!     }
+//!     sum
+//! }
+//! # }
+//! ```
+//!
+//! (The header needs to be in an attribute because the ideal
+//! `for (let mut i = 0; i < n; i += 1)` isn't valid Rust syntax, and
+//! so can't be given to an attribute at all.)
+//!
+//! The feature also provides `cfor_proc!`, which is the same as
+//! `cfor!`, but gives precise error messages for invalid loops, like
+//! "expected `;` between the condition and the step", pointing at the
+//! offending token.
+//!
+//! Both of these also accept C's `++` and `--` in the condition and
+//! the step, so loops can be ported without being rewritten:
+//!
+//! ```rust
+//! # #[cfg(feature = "cfor-proc")] {
+//! use cfor::cfor_proc;
+//!
+//! let mut v = vec![];
+//! cfor_proc!{let mut i = 0; i < 3; i++; {
+//!     v.push(i);
+//! }}
+//! assert_eq!(v, [0, 1, 2]);
+//! # }
+//! ```
+//!
+//! An `i++` or `--i` on its own in the step just updates `i`;
+//! anywhere else, it has the same value as in C (the old value for
+//! the postfix forms, the new one for the prefix forms). This means
+//! that `--x` there is a decrement, not a double negation.
+//!
+//! # Other loop shapes
+//!
+//! The same step-on-`continue` guarantee is provided for some other
+//! C loops:
+//!
+//! - [`cdo!`]: `do { ... } while (...)`, where the
+//!   body always runs at least once.
+//! - [`cwhile!`]: `for (; ...; ...) { ... }`, a `while` loop with a
+//!   step.
+//! - [`cloop!`]: `for (;; ...) { ... }`, an infinite loop with a
+//!   step.
+//! - [`crepeat!`]: `for (i = 0; i < n; i++) { ... }`, running a
+//!   body `n` times.
+//! - [`countdown!`]: `for (i = n; i-- > 0;) { ... }`, counting
+//!   down to zero.
+//! - [`cfor_rev!`]: `for (i = b - 1; i >= a; i -= s) { ... }`,
+//!   counting down a range in steps.
+//! - [`two_ptr!`]: `for (lo = a, hi = b; lo < hi; lo++, hi--) { ...
+//!   }`, two indices converging from either end.
+//! - [`cfor_zip!`]: `for (i = a, j = b; i < n && j < m; i += s, j
+//!   += t) { ... }`, two progressions in lockstep.
+//! - [`worklist!`]: `while ((x = pop(queue))) { ... }`, working
+//!   through a queue or stack that the body can add to.
+//! - [`divisors!`]: `for (d = 1; d * d <= n; d++) { ... }`, a loop
+//!   over the pairs of divisors of `n`.
+//! - [`digits!`]: `while (n) { d = n % 10; ...; n /= 10; }`, a loop
+//!   over the digits of `n`.
+//! - [`bitloop!`]: `for (; x; x &= x - 1) { ... }`, a loop over the
+//!   set bits of `x`.
+//! - [`submasks!`]: `for (s = m; s; s = (s - 1) & m) { ... }`, a
+//!   loop over the submasks of `m`.
+//! - [`powers!`]: `for (p = 1; ; p *= base) { ... }`, a loop over
+//!   the powers of `base` that stops before overflowing.
+//! - [`repeat_until!`]: Pascal's `repeat ... until ...`, a `cdo!`
+//!   that stops when its condition becomes true.
+//!
+//! There are also variants of `cfor!` itself:
+//!
+//! - [`cfor_val!`]: evaluates to `Some(value)` when the body executes
+//!   `break value`, and `None` otherwise.
+//! - [`cfor_collect!`]: collects a value from each iteration into a
+//!   `Vec`, or another collection.
+//! - [`cfold!`]: folds a value over the iterations, like
+//!   `Iterator::fold`.
+//! - [`cfor_sum!`] and [`cfor_product!`]: add up, or multiply
+//!   together, a value from each iteration.
+//! - [`cfor_scan!`]: an iterator over the running values of a
+//!   `cfold!`, like `Iterator::scan`.
+//! - [`cfor_find!`]: evaluates to the first value that matches, as
+//!   an `Option`.
+//! - [`cfor_any!`] and [`cfor_all!`]: whether a predicate holds for
+//!   any, or every, iteration, stopping as soon as that's known.
+//! - [`try_cfor!`]: allows `?` anywhere in the loop, evaluating to a
+//!   `Result`.
+//! - [`counted_cfor!`]: evaluates to the number of iterations that
+//!   ran.
+//! - [`timed_cfor!`]: evaluates to how long the loop took.
+//! - [`stats_cfor!`]: evaluates to [`LoopStats`], with both of those,
+//!   and whether the loop ended with a `break`.
+//! - [`observed_cfor!`]: reports the loop's progress to a
+//!   [`LoopObserver`].
+//! - `traced_cfor!`: runs the loop in a [`tracing`](https://docs.rs/tracing)
+//!   span, with an event for each iteration (with the `tracing`
+//!   feature).
+//! - `logged_cfor!`: logs the loop's variables with
+//!   [`log`](https://docs.rs/log) as it runs (with the `log`
+//!   feature).
+//! - `progress_cfor!`: shows the loop's progress with an
+//!   [`indicatif`](https://docs.rs/indicatif) progress bar (with the
+//!   `indicatif` feature).
+//!
+//! And some common loops are packaged up:
+//!
+//! - [`watchdog_cfor!`]: a `cfor!` that feeds a watchdog every so
+//!   many iterations.
+//! - [`budgeted_cfor!`]: a `cfor!` that stops when a time budget
+//!   runs out.
+//! - [`cfor_timeout!`]: a `cfor!` that gives up after a while.
+//! - [`cfor_bounded!`]: a `cfor!` that gives up after a number of
+//!   iterations.
+//! - [`until_stable!`]: applies a transformation until it reaches a
+//!   fixed point.
+//! - [`converge!`]: an iterative numeric method, which stops once
+//!   its error is small enough, or gives up.
+//! - [`cfor_checked!`]: a `cfor!` whose step stops the loop, rather
+//!   than overflowing.
+//! - [`cfor_wrapping!`]: a `cfor!` whose step wraps around, like C's
+//!   unsigned arithmetic.
+//! - [`cfor_saturating!`]: a `cfor!` whose step stops at the bounds
+//!   of the type, ending the loop.
+//! - [`retry!`]: retries a fallible operation a limited number of
+//!   times, optionally with exponential [`Backoff`] between attempts.
+//! - `retry_eintr!`: retries a system call interrupted by a signal
+//!   (with the `libc` feature).
+//! - [`spin_wait!`]: busy-waits until a condition holds.
+//! - [`busy_wait_for!`]: polls a condition a limited number of times.
+//! - [`fixed_timestep!`]: a game loop's fixed-timestep updates.
+//! - [`simd_cfor!`]: a loop over a range in chunks of a fixed number
+//!   of lanes, with a scalar loop for the remainder.
+//! - `cfor_assume!`: a `cfor!` that tells the optimiser an invariant
+//!   holds in each iteration (with the `unchecked` feature).
+//! - `prefetch_cfor!`: a `cfor!` that prefetches memory ahead of each
+//!   iteration (with the `prefetch` feature).
+//! - [`slice_cfor!`]: a loop over the elements of a slice, with its
+//!   bounds checked once.
+//! - [`stride_cfor!`]: a loop over every so many elements of a slice,
+//!   forwards or backwards.
+//! - [`interleaved_cfor!`]: a loop over the frames of an interleaved
+//!   buffer, like stereo audio, with a variable for each channel.
+//! - [`window_cfor!`]: a loop over windows of a slice, overlapping
+//!   or not.
+//! - [`matrix_cfor!`]: a loop over the rows and columns of a matrix
+//!   in a flat slice.
+//! - [`chunked_cfor!`]: a loop over a slice in fixed-size chunks,
+//!   with a separate body for the remainder.
+//! - [`unroll!`]: a loop over a range with its body unrolled a fixed
+//!   number of times.
+//! - [`nested_cfor!`]: nested loops, one for each header, which can
+//!   all be exited at once with `break_all!()`.
+//! - [`triangular_cfor!`]: a loop over the pairs `i < j` of indices
+//!   in a range.
+//! - [`tile_cfor!`]: nested loops over two or three ranges, blocked
+//!   into tiles for the cache.
+//! - [`static_for!`]: a body repeated for each number in a range, at
+//!   compile time.
+//! - [`array_init_cfor!`]: builds an array from its index, even in a
+//!   `const`.
+//! - [`seq_cfor!`]: hands the numbers in a range to another macro, to
+//!   generate code (like match arms, fields or impls) for each.
+//! - `par_cfor!`: runs the iterations of a loop over a range in
+//!   parallel, with [`rayon`](https://docs.rs/rayon) (with the `rayon`
+//!   feature).
+//! - `par_cfor_reduce!`: `par_cfor!` combining a value from each
+//!   iteration (with the `rayon` feature).
+//! - [`scoped_par_cfor!`]: the same as `par_cfor!`, but on a given
+//!   number of scoped threads, without needing `rayon`.
+//! - [`dynamic_par_cfor!`]: `scoped_par_cfor!`, with the iterations
+//!   handed out to the threads as they go.
+//! - `block_with_step!`: polls an [`nb`](https://docs.rs/nb)
+//!   operation, like `nb::block!`, but with a `cfor!` header (with the
+//!   `nb` feature).
+//!
+//! # Runaway loops
+//!
+//! With the `runaway-detector` feature, [`runaway_cfor!`] is a
+//! `cfor!` that (in debug builds) counts its iterations, and panics
+//! once there have been more than a limit, with a message giving the
+//! location and header of the loop, and the values of its variables.
+//! The limit is `RUNAWAY_LIMIT` (one billion), unless overridden by
+//! the `CFOR_RUNAWAY_LIMIT` environment variable, read when the first
+//! loop starts. This is for catching accidental infinite loops in
+//! tests. Only the loops written with it are changed, so turning the
+//! feature on doesn't change any other `cfor!`.
+//!
+//! Similarly, with the `paranoid` feature, [`paranoid_cfor!`] is a
+//! `cfor!` that (in debug builds) checks that the variables declared
+//! with `let mut` in its initialiser (when it is a list, rather than
+//! a block) change. If they all stay the same for more than
+//! `PARANOID_WINDOW` (one million) iterations in a row, like when a
+//! step has silently stopped making progress because of saturation,
+//! it panics. Values that can't be cloned and compared are assumed to
+//! always change.
+//!
+//! # `const`
+//!
+//! `cfor!` expands to code that can run in a `const fn` or a `const`
+//! item (as long as the loop's own code can), and so do the other
+//! loops built on it that don't need anything more, like [`cwhile!`],
+//! [`cloop!`], [`cdo!`], [`crepeat!`], [`countdown!`],
+//! [`repeat_until!`], [`cfor_val!`], [`counted_cfor!`],
+//! [`cfor_bounded!`], [`unroll!`] and [`simd_cfor!`], including with
+//! labels, `else` and `finally`. This is tested, and won't change,
+//! including with any of the crate's features enabled.
+//! [`static_for!`] and [`array_init_cfor!`] work in `const` too.
+//!
+//! # `no_std`
+//!
+//! The `std` feature is enabled by default, and disabling it makes
+//! the crate `no_std`. Everything only needs `core`, other than:
+//!
+//! - [`Backoff`], and so `retry!` with a backoff,
+//! - [`spin_wait!`] with escalation to yielding,
+//! - [`cfor_collect!`] without a collection type,
+//! - the macros that measure time, like [`budgeted_cfor!`],
+//! - the parallel loops, like [`scoped_par_cfor!`].
 
 
-/// A C-style `for` loop in macro form.
-///
-/// See crates docs for more information.
-#[macro_export]
-macro_rules! cfor {
-    // for (; ...; ...) { ... }
-    (; $($rest: tt)*) => {
-        cfor!((); $($rest)*)
-    };
-    // for ($init; ; ...) { ... }
-    ($($init: stmt),+; ; $($rest: tt)*) => {
-        // avoid the `while true` lint
-        cfor!($($init),+; !false; $($rest)*)
-    };
+#![cfg_attr(not(feature = "std"), no_std)]
 
-    // for ($init; $cond; ) { ... }
-    ($($init: stmt),+; $cond: expr; ; $body: block) => {
-        cfor!{$($init),+; $cond; (); $body}
-    };
+#[cfg(feature = "cfor-proc")]
+pub use cfor_proc::{c_for, cfor_proc};
 
-    // for ($init; $cond; $step) { $body }
-    ($($init: stmt),+; $cond: expr; $($step: expr),+; $body: block) => {
-        {
-            $($init;)+
-            while $cond {
-                let mut _first = true;
-                let mut _continue = false;
-                // this loop runs once, allowing us to use `break` and
-                // `continue` as `goto` to skip forward to the
-                // condition.
-                //
-                // the booleans above are very transparent to the
-                // optimiser, since they are modified exactly once,
-                // with nice control flow, and this this optimises to
-                // be similar to C for loop.
-                loop {
-                    // if we *don't* hit this, there was a `break` in
-                    // the body (otherwise the loop fell-through or
-                    // was `continue`d.)
-                    if !_first { _continue = true; break }
-                    _first = false;
+#[cfg(feature = "std")]
+pub use backoff::Backoff;
+pub use error::{DidNotConverge, Exhausted, Timeout};
+pub use ext::{CForExt, CTo, StepWith};
+#[cfg(feature = "unchecked")]
+pub use hint::assume;
+pub use iter::{iter, CFor};
+pub use observer::LoopObserver;
+pub use range::{CRange, FRange, Float, Integer};
+pub use run::{run, Flow, Loop};
+pub use stats::LoopStats;
+pub use worklist::Worklist;
 
-                    $body
-                }
-                if !_continue {
-                    // the `if` wasn't hit, so we should propagate the
-                    // `break`.
-                    break
+/// The default number of iterations after which a `runaway_cfor!`
+/// is considered to be a runaway.
+#[cfg(feature = "runaway-detector")]
+pub const RUNAWAY_LIMIT: u64 = 1_000_000_000;
+
+/// The number of consecutive iterations without its variables
+/// changing after which a `paranoid_cfor!` is considered to be
+/// stagnant.
+#[cfg(feature = "paranoid")]
+pub const PARANOID_WINDOW: u64 = 1_000_000;
+
+mod array;
+#[cfg(feature = "std")]
+mod backoff;
+mod error;
+mod ext;
+#[cfg(feature = "unchecked")]
+mod hint;
+mod iter;
+mod observer;
+#[cfg(feature = "std")]
+mod par;
+mod range;
+mod run;
+mod stats;
+mod worklist;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use core::option::Option::{None, Some};
+    pub use core::result::Result::{self, Err, Ok};
+
+    #[cfg(feature = "std")]
+    pub use std::vec::Vec;
+
+    pub use crate::array::ArrayBuilder;
+
+    #[cfg(feature = "indicatif")]
+    pub use indicatif;
+    #[cfg(feature = "std")]
+    pub mod par {
+        pub use crate::par::{dynamic, scoped};
+    }
+    #[cfg(feature = "log")]
+    pub use log;
+    #[cfg(feature = "nb")]
+    pub use nb;
+    #[cfg(feature = "rayon")]
+    pub use rayon;
+    #[cfg(feature = "tracing")]
+    pub use tracing;
+
+    /// The total of a `cfor_sum!`, or zero if there were no values.
+    pub fn sum<T: core::iter::Sum>(acc: Option<T>) -> T {
+        acc.unwrap_or_else(|| core::iter::empty().sum())
+    }
+
+    /// The total of a `cfor_product!`, or one if there were no values.
+    pub fn product<T: core::iter::Product>(acc: Option<T>) -> T {
+        acc.unwrap_or_else(|| core::iter::empty().product())
+    }
+
+    /// `current + step`, if that is at most `hi`, for a `cfor!` range.
+    ///
+    /// Like `step_down`, this works in 128 bits, so `hi - current`
+    /// doesn't have to fit in the type, and needs `current <= hi`.
+    pub fn step_up<T: crate::Integer>(current: T, hi: T, step: T) -> Option<T> {
+        let (current, hi, step) = (current.to_bits(), hi.to_bits(), step.to_bits());
+        if hi.wrapping_sub(current) >= step {
+            Some(T::from_bits(current.wrapping_add(step)))
+        } else {
+            None
+        }
+    }
+
+    /// `current - step`, if that is at least `lo`, for `cfor_rev!` and
+    /// a `cfor!` `downto` range.
+    ///
+    /// `current - lo` might not fit in the type (like `100 - -100` in
+    /// an `i8`), so this works in 128 bits, where it does: both are
+    /// sign extended, and `current >= lo`.
+    pub fn step_down<T: crate::Integer>(current: T, lo: T, step: T) -> Option<T> {
+        let (current, lo, step) = (current.to_bits(), lo.to_bits(), step.to_bits());
+        if current.wrapping_sub(lo) >= step {
+            Some(T::from_bits(current.wrapping_sub(step)))
+        } else {
+            None
+        }
+    }
+
+    /// The `1` of the same type as `like`, for `powers!`.
+    pub fn one<T: crate::Integer>(_like: &T) -> T {
+        T::from_bits(1)
+    }
+
+    /// The number of iterations after which a `runaway_cfor!` is
+    /// considered to be a runaway: `CFOR_RUNAWAY_LIMIT` from the
+    /// environment, or
+    /// `RUNAWAY_LIMIT` if that isn't set.
+    #[cfg(feature = "runaway-detector")]
+    pub fn runaway_limit() -> u64 {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        // 0 means not read yet.
+        static LIMIT: AtomicU64 = AtomicU64::new(0);
+        match LIMIT.load(Ordering::Relaxed) {
+            0 => {
+                let limit = std::env::var("CFOR_RUNAWAY_LIMIT")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(crate::RUNAWAY_LIMIT)
+                    .max(1);
+                LIMIT.store(limit, Ordering::Relaxed);
+                limit
+            }
+            limit => limit,
+        }
+    }
+
+    /// Printing the variables of a `runaway_cfor!`.
+    ///
+    /// `(&Probe(&x)).show()` uses `Show` if `x` is `Debug`, and
+    /// otherwise falls back to `Hidden` (via an autoref), which prints
+    /// `..`.
+    #[cfg(feature = "runaway-detector")]
+    pub mod runaway {
+        use core::fmt;
+
+        pub struct Probe<'a, T>(pub &'a T);
+
+        pub trait Show {
+            fn show(&self) -> &dyn fmt::Debug;
+        }
+        impl<T: fmt::Debug> Show for Probe<'_, T> {
+            fn show(&self) -> &dyn fmt::Debug {
+                self.0
+            }
+        }
+
+        struct Unprintable;
+        impl fmt::Debug for Unprintable {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("..")
+            }
+        }
+
+        pub trait Hidden {
+            fn show(&self) -> &dyn fmt::Debug {
+                &Unprintable
+            }
+        }
+        impl<T> Hidden for &Probe<'_, T> {}
+    }
+
+    /// Tracking whether a value changes, for `paranoid_cfor!`.
+    ///
+    /// `(&Probe(&x)).snapshot()` uses `Track` if `x` can be compared,
+    /// and otherwise falls back to `Untracked` (via an autoref),
+    /// which treats it as always changing.
+    #[cfg(feature = "paranoid")]
+    pub mod paranoid {
+        pub struct Probe<'a, T>(pub &'a T);
+
+        pub trait Track {
+            type Snapshot;
+            fn snapshot(&self) -> Self::Snapshot;
+            /// Whether the value is different to `last`, updating it
+            /// if so.
+            fn changed(&self, last: &mut Self::Snapshot) -> bool;
+        }
+        impl<T: Clone + PartialEq> Track for Probe<'_, T> {
+            type Snapshot = T;
+            fn snapshot(&self) -> T {
+                self.0.clone()
+            }
+            fn changed(&self, last: &mut T) -> bool {
+                let changed = self.0 != last;
+                if changed {
+                    last.clone_from(self.0);
                 }
+                changed
+            }
+        }
 
-                $($step;)+
+        pub trait Untracked {
+            fn snapshot(&self) {}
+            fn changed(&self, _last: &mut ()) -> bool {
+                true
             }
         }
+        impl<T> Untracked for &Probe<'_, T> {}
+    }
+
+    #[cfg(feature = "indicatif")]
+    pub mod progress {
+        use core::convert::TryInto;
+
+        /// The start, end and step of a loop like `let mut i = start;
+        /// i < end; i += step`.
+        pub struct Range<'a, A, B, C>(pub &'a A, pub &'a B, pub &'a C);
+
+        pub trait Len {
+            /// The number of iterations, with `i <= end` if `inclusive`.
+            fn len(&self, inclusive: bool) -> Option<u64>;
+        }
+        impl<A, B, C> Len for Range<'_, A, B, C>
+        where
+            A: Copy + TryInto<i128>,
+            B: Copy + TryInto<i128>,
+            C: Copy + TryInto<i128>,
+        {
+            fn len(&self, inclusive: bool) -> Option<u64> {
+                let start = (*self.0).try_into().ok()?;
+                let mut end: i128 = (*self.1).try_into().ok()?;
+                let step = (*self.2).try_into().ok()?;
+                if inclusive {
+                    end = end.checked_add(1)?;
+                }
+                if step <= 0 || end <= start {
+                    return if step > 0 { Some(0) } else { None };
+                }
+                let n = (end.checked_sub(start)?).checked_add(step - 1)? / step;
+                n.try_into().ok()
+            }
+        }
+
+        /// Anything else, like floats, has an unknown length.
+        pub trait Unknown {
+            fn len(&self, _inclusive: bool) -> Option<u64> {
+                None
+            }
+        }
+        impl<A, B, C> Unknown for &Range<'_, A, B, C> {}
+    }
+
+    /// Hints that the memory `ahead` elements after `p` will be read
+    /// soon, if the target has a way to do so (currently x86 with SSE,
+    /// and AArch64).
+    #[cfg(feature = "prefetch")]
+    #[inline(always)]
+    pub fn prefetch<T>(p: *const T, ahead: usize) {
+        let p = p.wrapping_add(ahead);
+        #[cfg(all(target_arch = "x86_64", target_feature = "sse"))]
+        // SAFETY: prefetching never faults, even for invalid addresses.
+        unsafe {
+            use core::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+            _mm_prefetch::<_MM_HINT_T0>(p.cast())
+        }
+        #[cfg(all(target_arch = "x86", target_feature = "sse"))]
+        // SAFETY: as above.
+        unsafe {
+            use core::arch::x86::{_mm_prefetch, _MM_HINT_T0};
+            _mm_prefetch::<_MM_HINT_T0>(p.cast())
+        }
+        #[cfg(target_arch = "aarch64")]
+        // SAFETY: as above.
+        unsafe {
+            core::arch::asm!("prfm pldl1keep, [{0}]", in(reg) p, options(nostack, readonly, preserves_flags))
+        }
+        #[cfg(not(any(
+            all(any(target_arch = "x86_64", target_arch = "x86"), target_feature = "sse"),
+            target_arch = "aarch64",
+        )))]
+        let _ = p;
+    }
+
+    /// Whether the last OS error was `EINTR`.
+    #[cfg(feature = "libc")]
+    pub fn is_eintr() -> bool {
+        std::io::Error::last_os_error().raw_os_error() == Some(libc::EINTR)
+    }
+}
+
+/// A C-style `for` loop in macro form.
+///
+/// See crates docs for more information.
+#[macro_export]
+macro_rules! cfor {
+    // enumerate $k; for (...) { ... }, with a hidden counter that is
+    // copied into `$k` once the condition holds.
+    ($($label: lifetime:)? enumerate $k: ident; $($rest: tt)*) => {
+        $crate::cfor!(
+            $($label:)? @hook (plain [] [({ $k = count; count += 1; true })] [
+                (#[allow(unused_assignments)] let mut $k: usize = 0)
+                (let mut count: usize = 0)
+            ]);
+            $($rest)*
+        )
+    };
+    ($label: lifetime: @hook $kind: tt; $($rest: tt)*) => {
+        $crate::__cfor!($kind $label; $($rest)*)
+    };
+    (@hook $kind: tt; $($rest: tt)*) => {
+        $crate::__cfor!($kind 'cfor; $($rest)*)
+    };
+    // $label: for (...) { ... }
+    ($label: lifetime: $($rest: tt)*) => {
+        $crate::__cfor!(plain $label; $($rest)*)
+    };
+    // for (...) { ... }
+    ($($rest: tt)*) => {
+        // unlabelled loops get a label that can't be named by the
+        // body (labels are hygienic).
+        $crate::__cfor!(plain 'cfor; $($rest)*)
+    };
+}
+
+/// A C-style `for` loop that evaluates to the value it `break`s with.
+///
+/// This takes the same form as `cfor!`, but a `break value` in the
+/// body makes the whole loop evaluate to `Some(value)`, while the
+/// loop running until `condition` is `false` gives `None`. A plain
+/// `break` breaks with `()`. It supports labels and a `finally`
+/// block, in the same manner as `cfor!`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let v = [3, 1, 4, 1, 5, 9, 2, 6];
+///
+///     let first_even = cfor_val!{let mut i = 0; i < v.len(); i += 1; {
+///         if v[i] % 2 == 0 { break (i, v[i]) }
+///     }};
+///     assert_eq!(first_even, Some((2, 4)));
+///
+///     let first_big = cfor_val!{let mut i = 0; i < v.len(); i += 1; {
+///         if v[i] > 10 { break i }
+///     }};
+///     assert_eq!(first_big, None);
+/// }
+/// ```
+#[macro_export]
+macro_rules! cfor_val {
+    ($label: lifetime: $($rest: tt)*) => {
+        $crate::__cfor!(val $label; $($rest)*)
+    };
+    ($($rest: tt)*) => {
+        $crate::__cfor!(val 'cfor; $($rest)*)
+    };
+}
+
+/// Collects a value from each iteration of a C-style `for` loop.
+///
+/// This is the iterator form of `cfor!`, `init; cond; step => expr`,
+/// collected into a `Vec`, rather than declaring one before the loop
+/// and pushing to it in the body. Any other collection that
+/// implements `FromIterator` can be given first, like a turbofish:
+/// `cfor_collect!(::<HashSet<_>> init; cond; step => expr)`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// use std::collections::BTreeSet;
+///
+/// # #[cfg(feature = "std")]
+/// fn main() {
+///     let n = 100;
+///     let powers = cfor_collect!(let mut x = 1; x < n; x *= 2 => x);
+///     assert_eq!(powers, [1, 2, 4, 8, 16, 32, 64]);
+///
+///     let residues = cfor_collect!(::<BTreeSet<_>> let mut i = 0; i < 10; i += 1 => i * i % 7);
+///     assert_eq!(residues.into_iter().collect::<Vec<_>>(), [0, 1, 2, 4]);
+/// }
+/// # #[cfg(not(feature = "std"))] fn main() {}
+/// ```
+///
+/// Without the `std` feature, the collection must always be given.
+#[macro_export]
+macro_rules! cfor_collect {
+    (:: < $collection: ty > $($rest: tt)*) => {
+        ::core::iter::Iterator::collect::<$collection>($crate::cfor!($($rest)*))
+    };
+    ($($rest: tt)*) => {
+        $crate::cfor_collect!(::<$crate::__private::Vec<_>> $($rest)*)
+    };
+}
+
+/// Folds a value over the iterations of a C-style `for` loop.
+///
+/// `cfold!(acc = start; init; cond; step => expr)` declares `acc` as
+/// `start`, then sets it to `expr` at each iteration, and evaluates
+/// to its final value, like `Iterator::fold`. This covers sums,
+/// products and other reductions, without a `let mut` outside the
+/// loop. The accumulator can be given a type, `acc: T = start`, and
+/// the header can be in C's parentheses, as with `cfor!`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let n = 10;
+///     let sum_squares = cfold!(acc = 0; let mut i = 0; i < n; i += 1 => acc + i * i);
+///     assert_eq!(sum_squares, 285);
+///
+///     let factorial = cfold!(acc: u64 = 1; (let mut i = 1; i <= 20; i += 1) => acc * i);
+///     assert_eq!(factorial, 2_432_902_008_176_640_000);
+///
+///     let digits = cfold!(acc = String::new(); let mut x = 1234; x > 0; x /= 10 => {
+///         acc.push(char::from(b'0' + (x % 10) as u8));
+///         acc
+///     });
+///     assert_eq!(digits, "4321");
+/// }
+/// ```
+#[macro_export]
+macro_rules! cfold {
+    ($acc: ident $(: $ty: ty)? = $start: expr; $($rest: tt)+) => {
+        {
+            let mut $acc $(: $ty)? = $start;
+            $crate::cfold!(@header $acc []; $($rest)+);
+            $acc
+        }
+    };
+    // the header is collected up to the `=>`.
+    (@header $acc: ident [($($header: tt)*)]; => $e: expr) => {
+        $crate::cfold!(@header $acc [$($header)*]; => $e)
+    };
+    (@header $acc: ident [$($header: tt)*]; => $e: expr) => {
+        $crate::cfor!($($header)*; { $acc = $e; })
+    };
+    (@header $acc: ident [$($header: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::cfold!(@header $acc [$($header)* $next]; $($rest)*)
+    };
+}
+
+/// Adds up a value from each iteration of a C-style `for` loop.
+///
+/// `cfor_sum!(init; cond; step => expr)` is `cfold!` with `acc +
+/// expr`, starting from zero, which is what a loop that doesn't run
+/// evaluates to. `cfor_product!` is the same, for `acc * expr`,
+/// starting from one. The zero and one come from the `Sum` and
+/// `Product` traits, so these work for any type that implements
+/// those and the operator, including floats.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let n = 10;
+///     assert_eq!(cfor_sum!(let mut i = 1; i <= n; i += 1 => i * i), 385);
+///     assert_eq!(cfor_product!(let mut i = 1u64; i <= n; i += 1 => i), 3_628_800);
+///
+///     let v = vec![1.5, 2.0, 0.25];
+///     let dot = cfor_sum!(let mut i = 0; i < v.len(); i += 1 => v[i] * v[i]);
+///     assert_eq!(dot, 6.3125);
+///
+///     assert_eq!(cfor_product!(let mut i = 0; i < 0; i += 1 => i), 1);
+/// }
+/// ```
+#[macro_export]
+macro_rules! cfor_sum {
+    ($($rest: tt)+) => {
+        $crate::__cfor_reduce!(sum + $($rest)+)
+    };
+}
+
+/// Multiplies together a value from each iteration of a C-style `for`
+/// loop. See `cfor_sum!`.
+#[macro_export]
+macro_rules! cfor_product {
+    ($($rest: tt)+) => {
+        $crate::__cfor_reduce!(product * $($rest)+)
+    };
+}
+
+/// `cfor_sum!` and `cfor_product!`, which fold into an `Option`, so
+/// the type of the zero (or one) is only needed at the end, when
+/// it's known from the values.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfor_reduce {
+    ($kind: ident $op: tt $($rest: tt)+) => {
+        $crate::__private::$kind($crate::__cfor_reduce!(@header $op []; $($rest)+))
+    };
+    (@header $op: tt [$($header: tt)*]; => $e: expr) => {
+        $crate::cfold!(acc = $crate::__private::None; $($header)* => {
+            let value = $e;
+            $crate::__private::Some(match acc {
+                $crate::__private::Some(acc) => acc $op value,
+                $crate::__private::None => value,
+            })
+        })
+    };
+    (@header $op: tt [$($header: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::__cfor_reduce!(@header $op [$($header)* $next]; $($rest)*)
+    };
+}
+
+/// An iterator over the running values of a fold over a C-style
+/// `for` loop.
+///
+/// `cfor_scan!(acc = start; init; cond; step => expr)` is the
+/// iterator form of `cfold!`: at each iteration, `acc` is set to
+/// `expr`, and then produced as the next value, like
+/// `Iterator::scan`. This gives prefix sums and other running totals
+/// lazily, without collecting into a `Vec` along the way. The values
+/// are clones of the accumulator.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let v = [3, 1, 4, 1, 5];
+///     let data = &v;
+///
+///     let prefix: Vec<_> = cfor_scan!(acc = 0; let mut i = 0; i < data.len(); i += 1 => acc + data[i])
+///         .collect();
+///     assert_eq!(prefix, [3, 4, 8, 9, 14]);
+///
+///     // the first running maximum over 4.
+///     let mut peaks = cfor_scan!(acc = 0; let mut i = 0; i < data.len(); i += 1 => acc.max(data[i]));
+///     assert_eq!(peaks.find(|&m| m > 3), Some(4));
+/// }
+/// ```
+///
+/// As with `cfor!(... => expr)`, the iterator owns the accumulator,
+/// the variables from the initialiser, and everything else the loop
+/// uses, so data needed after the loop should be borrowed first.
+#[macro_export]
+macro_rules! cfor_scan {
+    ($acc: ident $(: $ty: ty)? = $start: expr; $($rest: tt)+) => {
+        {
+            // the accumulator is taken out of the closure's state for
+            // each iteration, so that `expr` can move it.
+            let mut state $(: ::core::option::Option<$ty>)? = $crate::__private::Some($start);
+            $crate::cfor_scan!(@header $acc state []; $($rest)+)
+        }
+    };
+    (@header $acc: ident $state: ident [$($header: tt)*]; => $e: expr) => {
+        $crate::cfor!($($header)* => {
+            let $crate::__private::Some($acc) = $state.take() else { ::core::unreachable!() };
+            let value = $e;
+            $state = $crate::__private::Some(::core::clone::Clone::clone(&value));
+            value
+        })
+    };
+    (@header $acc: ident $state: ident [$($header: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::cfor_scan!(@header $acc $state [$($header)* $next]; $($rest)*)
+    };
+}
+
+/// Searches the iterations of a C-style `for` loop.
+///
+/// `cfor_find!(init; cond; step => pred => value)` evaluates to
+/// `Some(value)` for the first iteration where `pred` holds, or
+/// `None` if the loop's condition fails first. With a single
+/// expression, `cfor_find!(init; cond; step => expr)`, the `expr` is
+/// an `Option`, and the first `Some` is found, like
+/// `Iterator::find_map`. Either stops the loop as soon as it finds a
+/// value.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let v = [3, 1, 4, 1, 5, 9, 2, 6];
+///
+///     let first_even = cfor_find!(let mut i = 0; i < v.len(); i += 1 => v[i] % 2 == 0 => i);
+///     assert_eq!(first_even, Some(2));
+///
+///     // the first power of two that is at least 1000.
+///     let power = cfor_find!(let mut x = 1u32; x != 0; x <<= 1 => x >= 1000 => x);
+///     assert_eq!(power, Some(1024));
+///
+///     let parsed = ["a", "-", "17", "3"];
+///     let number = cfor_find!((let mut i = 0; i < parsed.len(); i += 1) => parsed[i].parse::<u8>().ok());
+///     assert_eq!(number, Some(17));
+///
+///     assert_eq!(cfor_find!(let mut i = 0; i < v.len(); i += 1 => v[i] > 10 => i), None);
+/// }
+/// ```
+#[macro_export]
+macro_rules! cfor_find {
+    (@header [($($header: tt)*)]; => $($rest: tt)+) => {
+        $crate::cfor_find!(@header [$($header)*]; => $($rest)+)
+    };
+    (@header [$($header: tt)*]; => $pred: expr => $value: expr) => {
+        $crate::cfor_val!($($header)*; {
+            if $pred {
+                break $value;
+            }
+        })
+    };
+    (@header [$($header: tt)*]; => $e: expr) => {
+        $crate::cfor_val!($($header)*; {
+            if let $crate::__private::Some(value) = $e {
+                break value;
+            }
+        })
+    };
+    (@header [$($header: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::cfor_find!(@header [$($header)* $next]; $($rest)*)
+    };
+    ($($rest: tt)+) => {
+        $crate::cfor_find!(@header []; $($rest)+)
+    };
+}
+
+/// Whether a predicate holds for any iteration of a C-style `for`
+/// loop.
+///
+/// `cfor_any!(init; cond; step => pred)` is `true` as soon as `pred`
+/// holds, without running any more of the loop, and `false` if the
+/// loop's condition fails first, like `Iterator::any`. `cfor_all!`
+/// is the opposite, `false` as soon as `pred` doesn't hold, like
+/// `Iterator::all`. These suit loops whose progression isn't a plain
+/// range, which would otherwise need a flag.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let v = [1, 2, 4, 8, 16, 33];
+///
+///     // are any of the entries at powers-of-two indices odd?
+///     assert!(!cfor_any!(let mut i = 1; i < v.len(); i *= 2 => v[i] % 2 == 1));
+///     assert!(cfor_all!(let mut i = 1; i < v.len(); i *= 2 => v[i] % 2 == 0));
+///
+///     let n = 97u32;
+///     let prime = cfor_all!(let mut d = 2; d * d <= n; d += 1 => n % d != 0);
+///     assert!(prime);
+/// }
+/// ```
+#[macro_export]
+macro_rules! cfor_any {
+    ($($rest: tt)+) => {
+        ::core::option::Option::is_some(&$crate::__cfor_any!([]; $($rest)+))
+    };
+}
+
+/// Whether a predicate holds for every iteration of a C-style `for`
+/// loop. See `cfor_any!`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let sorted = [1, 1, 2, 3, 5, 8];
+///     assert!(cfor_all!(let mut i = 1; i < sorted.len(); i += 1 => sorted[i - 1] <= sorted[i]));
+///     assert!(cfor_all!(let mut i = 0; i < 0; i += 1 => false));
+/// }
+/// ```
+#[macro_export]
+macro_rules! cfor_all {
+    ($($rest: tt)+) => {
+        ::core::option::Option::is_none(&$crate::__cfor_any!([]; $($rest)+, not))
+    };
+}
+
+/// `cfor_find!` of the first iteration where the predicate holds (or
+/// not, for `cfor_all!`).
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfor_any {
+    ([$($header: tt)*]; => $pred: expr) => {
+        $crate::cfor_find!($($header)* => $pred => ())
+    };
+    ([$($header: tt)*]; => $pred: expr, not) => {
+        $crate::cfor_find!($($header)* => ::core::ops::Not::not($pred) => ())
+    };
+    ([$($header: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::__cfor_any!([$($header)* $next]; $($rest)*)
+    };
+}
+
+/// A C-style `for` loop that panics if it runs for too long, in macro
+/// form (with the `runaway-detector` feature).
+///
+/// This takes the same form as `cfor!`, including labels, `else` and
+/// `finally`, but (in debug builds) counts the iterations, and panics
+/// once there have been more than `RUNAWAY_LIMIT`, or the
+/// `CFOR_RUNAWAY_LIMIT` environment variable if it is set. The
+/// message gives the location and header of the loop, and the values
+/// of the variables declared with `let mut` in its initialiser (when
+/// it is a list, rather than a block), or `..` for those that aren't
+/// `Debug`. Release builds are the same as `cfor!`.
+///
+/// Since the check isn't `const`, this can't be used in a `const fn`
+/// in debug builds.
+///
+/// ```rust,no_run
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     // oops: doubling never gets `i` past 0, so this panics with
+///     // "runaway loop at src/main.rs:7:5: more than 1000000000
+///     // iterations of `let mut i = 0u32; (i < 10); i *= 2`, i = 0".
+///     runaway_cfor!{let mut i = 0u32; i < 10; i *= 2; {}}
+/// }
+/// ```
+#[cfg(feature = "runaway-detector")]
+#[macro_export]
+macro_rules! runaway_cfor {
+    ($label: lifetime: $($rest: tt)*) => {
+        $crate::__cfor!((runaway plain) $label; $($rest)*)
+    };
+    ($($rest: tt)*) => {
+        $crate::__cfor!((runaway plain) 'cfor; $($rest)*)
+    };
+}
+
+/// A C-style `for` loop that panics if its variables stop changing, in
+/// macro form (with the `paranoid` feature).
+///
+/// This takes the same form as `cfor!`, including labels, `else` and
+/// `finally`, but (in debug builds) checks that the variables declared
+/// with `let mut` in its initialiser (when it is a list, rather than a
+/// block) change, and panics if they all stay the same for more than
+/// `PARANOID_WINDOW` iterations in a row. This catches a step that has
+/// silently stopped making progress, like `x *= 1`, or an integer
+/// that has saturated. Values that can't be cloned and compared are
+/// assumed to always change. Release builds are the same as `cfor!`.
+///
+/// Since the check isn't `const`, this can't be used in a `const fn`
+/// in debug builds.
+///
+/// ```rust,no_run
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     // oops: `i` gets stuck at 255, so this panics with "stagnant
+///     // loop at ...: `i` unchanged for 1000000 iterations".
+///     paranoid_cfor!{let mut i = 250u8; i as u32 <= 255; i = i.saturating_add(1); {}}
+/// }
+/// ```
+#[cfg(feature = "paranoid")]
+#[macro_export]
+macro_rules! paranoid_cfor {
+    ($label: lifetime: $($rest: tt)*) => {
+        $crate::__cfor!((paranoid plain) $label; $($rest)*)
+    };
+    ($($rest: tt)*) => {
+        $crate::__cfor!((paranoid plain) 'cfor; $($rest)*)
+    };
+}
+
+/// A C-style `for` loop in which `?` can be used to stop the loop
+/// early with an error.
+///
+/// This takes the same form as `cfor!`, and evaluates to
+/// `Result<(), E>`: `Err(e)` if a `?` in the initialiser, condition,
+/// step or body encountered an error `e`, and `Ok(())` otherwise.
+///
+/// As with `?` elsewhere, the error is converted with `From`, so
+/// there may not be enough information for the error type `E` to be
+/// inferred. It can be given explicitly before the header, as in
+/// `try_cfor!(<E> initialiser; condition; step; { body })`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// use std::num::ParseIntError;
+///
+/// fn sum_prefix(words: &[&str]) -> Result<i32, ParseIntError> {
+///     let mut sum = 0;
+///     // keep going until a word that is 0.
+///     try_cfor!{<ParseIntError> let mut i = 0; words[i].parse::<i32>()? != 0; i += 1; {
+///         sum += words[i].parse::<i32>()?;
+///     }}?;
+///     Ok(sum)
+/// }
+///
+/// fn main() {
+///     assert_eq!(sum_prefix(&["1", "2", "0", "x"]), Ok(3));
+///     assert!(sum_prefix(&["1", "x", "0"]).is_err());
+/// }
+/// ```
+///
+/// The loop is run inside a closure, and so a `return` in the body
+/// only leaves the loop (with the value of the `return`, which must
+/// be a `Result<(), E>`), and a `break` or `continue` can only target
+/// the `try_cfor!` itself or loops inside it.
+///
+/// A `try_cfor!` can also have a label, and `else` and `finally`
+/// blocks. Unlike `cfor!`, the `finally` block also runs if a `?`
+/// stops the loop, but it cannot use variables declared in the
+/// initialiser.
+#[macro_export]
+macro_rules! try_cfor {
+    (< $err: ty > $label: lifetime: $($rest: tt)*) => {
+        $crate::__cfor!(fallible $label; $($rest)* -> $err)
+    };
+    (< $err: ty > $($rest: tt)*) => {
+        $crate::__cfor!(fallible 'cfor; $($rest)* -> $err)
+    };
+    ($label: lifetime: $($rest: tt)*) => {
+        $crate::__cfor!(fallible $label; $($rest)* -> _)
+    };
+    ($($rest: tt)*) => {
+        $crate::__cfor!(fallible 'cfor; $($rest)* -> _)
+    };
+}
+
+/// Fills in any missing parts of a `cfor!`-style header, and then
+/// hands off to `__cfor_expand!` for the expansion of the given
+/// kind (`plain`, `val` or `fallible`, or one of those with extra
+/// conditions, like `(plain [(a)] [(b)])`).
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfor {
+    // for (...; ...; ...) { ... }, with the C parentheses.
+    ($kind: tt $label: lifetime; ($($header: tt)*) $body: block $($rest: tt)*) => {
+        $crate::__cfor!($kind $label; $($header)*; $body $($rest)*)
+    };
+    // for (...; ...; ...) => $e
+    ($kind: tt $label: lifetime; ($($header: tt)*) => $($rest: tt)*) => {
+        $crate::__cfor!($kind $label; $($header)* => $($rest)*)
+    };
+    // for ({ ... }; ...; ...) { ... }
+    //
+    // the statements are unwrapped from the block, so that their
+    // bindings are visible in the rest of the loop.
+    ($kind: tt $label: lifetime; { $($init: stmt);* $(;)? }; $($rest: tt)*) => {
+        $crate::__cfor!($kind $label; $($init),*; $($rest)*)
+    };
+    // for (; ...; ...) { ... }
+    ($kind: tt $label: lifetime; ; $($rest: tt)*) => {
+        $crate::__cfor!($kind $label; (); $($rest)*)
+    };
+    // for $i in $range step $step { ... }
+    ($kind: tt $label: lifetime; $i: ident in $($rest: tt)*) => {
+        $crate::__cfor!(@range $kind $label $i []; $($rest)*)
+    };
+    // for ($init; ...; ...) { ... }
+    //
+    // a trailing `,` can't be matched directly, since `;` is also a
+    // statement, so the initialiser is collected up to the `;` first.
+    ($kind: tt $label: lifetime; $($rest: tt)*) => {
+        $crate::__cfor!(@init $kind $label []; $($rest)*)
+    };
+    (@init $kind: tt $label: lifetime [$($init: tt)*]; , ; $($rest: tt)*) => {
+        $crate::__cfor!(@init $kind $label [$($init)*]; ; $($rest)*)
+    };
+    // `runaway_cfor!`, which needs the names in the initialiser.
+    (@init (runaway $kind: tt) $label: lifetime [$($init: tt)*]; ; $($rest: tt)*) => {
+        $crate::__cfor_runaway!($kind $label [$($init)*]; $($rest)*)
+    };
+    // `paranoid_cfor!`, likewise.
+    (@init (paranoid $kind: tt) $label: lifetime [$($init: tt)*]; ; $($rest: tt)*) => {
+        $crate::__cfor_paranoid!($kind $label [$($init)*]; $($rest)*)
+    };
+    (@init $kind: tt $label: lifetime [$($init: tt)*]; ; $($rest: tt)*) => {
+        $crate::__cfor!(@stmts $kind $label; $($init)*; $($rest)*)
+    };
+    (@init $kind: tt $label: lifetime [$($init: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::__cfor!(@init $kind $label [$($init)* $next]; $($rest)*)
+    };
+    (@stmts $kind: tt $label: lifetime; $($init: stmt),+; $($rest: tt)*) => {
+        $crate::__cfor!(@cond $kind $label ($($init),+) [] [] [] []; $($rest)*)
+    };
+
+    // a range is collected up to the step or the body, and then split
+    // at the `..=`, `..` or `downto`. The exact step is so that `$i` never goes past the
+    // end (`$i < end` first, in case the body has changed it). The
+    // initialiser is passed as already collected, to save recursion
+    // for nested loops.
+    (@range $kind: tt $label: lifetime $i: ident [$($range: tt)+]; step $($rest: tt)+) => {
+        $crate::__cfor!(@range_step $kind $label $i [$($range)+] []; $($rest)+)
+    };
+    (@range $kind: tt $label: lifetime $i: ident $range: tt; { $($body: tt)* } $($rest: tt)*) => {
+        $crate::__cfor!(@range_split $kind $label $i (1) { $($body)* } [] $range; $($rest)*)
+    };
+    (@range $kind: tt $label: lifetime $i: ident [$($range: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::__cfor!(@range $kind $label $i [$($range)* $next]; $($rest)*)
+    };
+    (@range_step $kind: tt $label: lifetime $i: ident $range: tt [$($step: tt)+]; { $($body: tt)* } $($rest: tt)*) => {
+        $crate::__cfor!(@range_split $kind $label $i ($($step)+) { $($body)* } [] $range; $($rest)*)
+    };
+    (@range_step $kind: tt $label: lifetime $i: ident $range: tt [$($step: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::__cfor!(@range_step $kind $label $i $range [$($step)* $next]; $($rest)*)
+    };
+    (@range_split $kind: tt $label: lifetime $i: ident $step: tt $body: tt [$($start: tt)+] [..= $($end: tt)+]; $($rest: tt)*) => {
+        $crate::__cfor!(
+            @init $kind $label [let end = $($end)+, let step = $step, ::core::assert!(step > 0, "`cfor!` needs a positive step"),
+                                let mut $i = $($start)+, let mut more = $i <= end]; ;
+            more;
+            match if $i < end { $crate::__private::step_up($i, end, step) } else { $crate::__private::None } {
+                $crate::__private::Some(next) => $i = next,
+                $crate::__private::None => more = false,
+            };
+            $body $($rest)*
+        )
+    };
+    (@range_split $kind: tt $label: lifetime $i: ident $step: tt $body: tt [$($start: tt)+] [downto $($end: tt)+]; $($rest: tt)*) => {
+        $crate::__cfor!(
+            @init $kind $label [let end = $($end)+, let step = $step, ::core::assert!(step > 0, "`cfor!` needs a positive step"),
+                                let mut $i = $($start)+, let mut more = $i >= end]; ;
+            more;
+            match if $i > end { $crate::__private::step_down($i, end, step) } else { $crate::__private::None } {
+                $crate::__private::Some(next) => $i = next,
+                $crate::__private::None => more = false,
+            };
+            $body $($rest)*
+        )
+    };
+    (@range_split $kind: tt $label: lifetime $i: ident $step: tt $body: tt [$($start: tt)+] [.. $($end: tt)+]; $($rest: tt)*) => {
+        $crate::__cfor!(
+            @init $kind $label [let end = $($end)+, let step = $step, ::core::assert!(step > 0, "`cfor!` needs a positive step"),
+                                let mut $i = $($start)+]; ;
+            $i < end;
+            $i = match if $i < end { $crate::__private::step_up($i, end, step) } else { $crate::__private::None } {
+                $crate::__private::Some(next) => next,
+                $crate::__private::None => end,
+            };
+            $body $($rest)*
+        )
+    };
+    (@range_split $kind: tt $label: lifetime $i: ident $step: tt $body: tt [$($start: tt)*] [$next: tt $($range: tt)*]; $($rest: tt)*) => {
+        $crate::__cfor!(@range_split $kind $label $i $step $body [$($start)* $next] [$($range)*]; $($rest)*)
+    };
+    (@range_split $kind: tt $label: lifetime $i: ident $step: tt $body: tt $start: tt []; $($rest: tt)*) => {
+        ::core::compile_error!("expected a range with both ends, like `a..b`, `a..=b` or `b downto a`")
+    };
+
+    // the condition can contain `let`s (like a `let` chain), and so
+    // can't be parsed as an `expr`. Instead, it is split into its
+    // `&&`-separated parts one token at a time, up to the `;`. The
+    // state is: whether a `let` has been seen, the finished parts,
+    // the tokens of the current part, and all the tokens so far.
+
+    // ... let $pat = $e && ...
+    (@cond $kind: tt $label: lifetime $init: tt $lets: tt [$($parts: tt)*] [let $($cur: tt)*] [$($all: tt)*]; && $($rest: tt)*) => {
+        $crate::__cfor!(@cond $kind $label $init [let] [$($parts)* (let $($cur)*)] [] [$($all)* let $($cur)* &&]; $($rest)*)
+    };
+    // ... $cond && ...
+    (@cond $kind: tt $label: lifetime $init: tt $lets: tt [$($parts: tt)*] [$($cur: tt)+] [$($all: tt)*]; && $($rest: tt)*) => {
+        $crate::__cfor!(@cond $kind $label $init $lets [$($parts)* ($($cur)+)] [] [$($all)* $($cur)* &&]; $($rest)*)
+    };
+    // ... let $pat = $e; ...
+    (@cond $kind: tt $label: lifetime $init: tt $lets: tt [$($parts: tt)*] [let $($cur: tt)*] $all: tt; ; $($rest: tt)*) => {
+        $crate::__cfor!(@step $kind $label $init [$($parts)* (let $($cur)*)]; $($rest)*)
+    };
+    // ... let $pat = $e && $cond; ...
+    (@cond $kind: tt $label: lifetime $init: tt [let] [$($parts: tt)*] [$($cur: tt)+] $all: tt; ; $($rest: tt)*) => {
+        $crate::__cfor!(@step $kind $label $init [$($parts)* ($($cur)+)]; $($rest)*)
+    };
+    // for ($init; ; ...) { ... }
+    (@cond $kind: tt $label: lifetime $init: tt [] [] [] []; ; $($rest: tt)*) => {
+        $crate::__cfor!(@step $kind $label $init [(true)]; $($rest)*)
+    };
+    // for ($init; $cond; ...) { ... }
+    //
+    // no `let`s, so the condition is just a normal expression.
+    (@cond $kind: tt $label: lifetime $init: tt [] $parts: tt [$($cur: tt)*] [$($all: tt)*]; ; $($rest: tt)*) => {
+        $crate::__cfor!(@step $kind $label $init [($($all)* $($cur)*)]; $($rest)*)
+    };
+    (@cond $kind: tt $label: lifetime $init: tt $lets: tt $parts: tt [$($cur: tt)*] $all: tt; $next: tt $($rest: tt)*) => {
+        $crate::__cfor!(@cond $kind $label $init $lets $parts [$($cur)* $next] $all; $($rest)*)
+    };
+
+    // for (...; ...; ) { ... }
+    (@step $kind: tt $label: lifetime $init: tt $cond: tt; ; $($rest: tt)*) => {
+        $crate::__cfor!(@step $kind $label $init $cond; (); $($rest)*)
+    };
+    // for (...; ...; $step) { ... }
+    (@step $kind: tt $label: lifetime $init: tt $cond: tt; $($step: expr),+ $(,)?; $body: block $($rest: tt)*) => {
+        $crate::__cfor!(@expand $kind $label; $init $cond ($($step),+) $body $($rest)*)
+    };
+    // for (...; ...; $step) => $e, which becomes a `lazy` loop with
+    // the expression in place of the body. Only a plain `cfor!` can be
+    // an iterator.
+    (@step $kind: tt $label: lifetime $init: tt $cond: tt; => $($rest: tt)*) => {
+        $crate::__cfor!(@step $kind $label $init $cond; () => $($rest)*)
+    };
+    (@step plain $label: lifetime $init: tt $cond: tt; $($step: expr),+ $(,)? => $e: expr) => {
+        $crate::__cfor!(@expand lazy $label; $init $cond ($($step),+) (=> $e))
+    };
+    (@step (plain $($extra: tt)*) $label: lifetime $init: tt $cond: tt; $($step: expr),+ $(,)? => $e: expr) => {
+        $crate::__cfor!(@expand (lazy $($extra)*) $label; $init $cond ($($step),+) (=> $e))
+    };
+
+    // the kind can come with extra parts of the condition, to be
+    // checked before and after the loop's own, and extra statements
+    // for the initialiser, which lets other macros (with the same
+    // hygiene as those) add to the loop. They're kept separate until
+    // the end, so `__cfor_runaway!` can tell them apart from the
+    // loop's own.
+    (@expand (runaway $names: tt $kind: ident) $label: lifetime; $($rest: tt)*) => {
+        $crate::__cfor_runaway!(@check ($kind [] [] []) $names $label; $($rest)*)
+    };
+    (@expand $kind: ident $label: lifetime; $($rest: tt)*) => {
+        $crate::__cfor!(@expand ($kind [] [] []) $label; $($rest)*)
+    };
+    (@expand ($kind: ident $before: tt $after: tt) $label: lifetime; $($rest: tt)*) => {
+        $crate::__cfor!(@expand ($kind $before $after []) $label; $($rest)*)
+    };
+    (@expand ($kind: ident $before: tt $after: tt $init: tt) $label: lifetime; $($rest: tt)*) => {
+        $crate::__cfor!(@merge ($kind $before $after $init) $label; $($rest)*)
+    };
+    (@merge ($kind: ident [$($before: tt)*] [$($after: tt)*] [$(($($extra: tt)*))*]) $label: lifetime; ($($init: tt)*) [$($cond: tt)*] $($rest: tt)*) => {
+        $crate::__cfor_expand!($kind $label; ($($init)* $(, $($extra)*)*) [$($before)* $($cond)* $($after)*] $($rest)*)
+    };
+
+    // common mistakes, which would otherwise get a very unhelpful
+    // "no rules expected this token".
+    (@init $kind: tt $label: lifetime $init: tt; ) => {
+        ::core::compile_error!("expected `;` after the initialiser of the loop")
+    };
+    (@cond $kind: tt $label: lifetime $init: tt $lets: tt $parts: tt $cur: tt $all: tt; ) => {
+        ::core::compile_error!("expected `;` after the condition of the loop")
+    };
+    (@step $kind: tt $label: lifetime $init: tt $cond: tt; $body: block $($rest: tt)*) => {
+        ::core::compile_error!(
+            "expected three `;`-separated parts before the loop body, like \
+             `init; cond; step; { ... }` (the step can be empty: `init; cond; ; { ... }`)"
+        )
+    };
+    (@step $kind: tt $label: lifetime $init: tt $cond: tt; $($step: expr),+ $(,)?; $($extra: expr),* $(,)?; $($rest: tt)*) => {
+        ::core::compile_error!(
+            "too many `;`-separated parts before the loop body; expected exactly three, \
+             like `init; cond; step; { ... }`"
+        )
+    };
+    (@step $kind: tt $label: lifetime $init: tt $cond: tt; $($step: expr),+ $(,)?; $($rest: tt)*) => {
+        ::core::compile_error!("expected the loop body, in braces: `{ ... }`")
+    };
+}
+
+/// Checks each part of the condition of a loop, and runs
+/// `$otherwise` as soon as one isn't satisfied. A `let` part becomes
+/// a `let`-`else`, so its bindings are usable in the rest of the
+/// iteration.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfor_cond {
+    ([$($part: tt)*] $otherwise: tt) => {
+        $($crate::__cfor_cond!(@part $part $otherwise);)*
+    };
+    (@part (let $pat: pat = $e: expr) { $($otherwise: tt)* }) => {
+        let $pat = $e else { $($otherwise)* };
+    };
+    (@part ($cond: expr) { $($otherwise: tt)* }) => {
+        if !$cond { $($otherwise)* }
+    };
+}
+
+/// The check for `paranoid_cfor!`, that (in debug builds) the `mut`
+/// variables declared in its initialiser haven't all stayed the same
+/// for too many iterations.
+#[cfg(feature = "paranoid")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfor_paranoid {
+    ($kind: tt $label: lifetime [$($init: tt)*]; $($rest: tt)*) => {
+        $crate::__cfor_paranoid!(@scan $kind $label [$($init)*] [] [$($init)*]; $($rest)*)
+    };
+
+    // find the `let mut $name`s.
+    (@scan $kind: tt $label: lifetime $init: tt [$($name: ident)*] [let mut $new: ident $($todo: tt)*]; $($rest: tt)*) => {
+        $crate::__cfor_paranoid!(@scan $kind $label $init [$($name)* $new] [$($todo)*]; $($rest)*)
+    };
+    (@scan $kind: tt $label: lifetime $init: tt $names: tt [$skip: tt $($todo: tt)*]; $($rest: tt)*) => {
+        $crate::__cfor_paranoid!(@scan $kind $label $init $names [$($todo)*]; $($rest)*)
+    };
+    (@scan $kind: tt $label: lifetime [$($init: tt)*] [] []; $($rest: tt)*) => {
+        $crate::__cfor!(@stmts $kind $label; $($init)*; $($rest)*)
+    };
+    (@scan $kind: ident $label: lifetime $init: tt $names: tt []; $($rest: tt)*) => {
+        $crate::__cfor_paranoid!(@scan ($kind [] [] []) $label $init $names []; $($rest)*)
+    };
+    (@scan ($kind: ident $before: tt $after: tt) $label: lifetime $init: tt $names: tt []; $($rest: tt)*) => {
+        $crate::__cfor_paranoid!(@scan ($kind $before $after []) $label $init $names []; $($rest)*)
+    };
+    (@scan ($kind: ident $before: tt [$($after: tt)*] [$($extra: tt)*]) $label: lifetime [$($init: tt)*] [$($name: ident)+] []; $($rest: tt)*) => {
+        $crate::__cfor!(
+            @stmts
+            ($kind $before [$($after)* ({
+                #[cfg(debug_assertions)]
+                {
+                    use $crate::__private::paranoid::{Track as _, Untracked as _};
+                    if $crate::__cfor_paranoid!(@changed paranoid; $($name)+) {
+                        paranoid_unchanged = 0;
+                    } else {
+                        paranoid_unchanged += 1;
+                    }
+                    ::core::debug_assert!(
+                        paranoid_unchanged < $crate::PARANOID_WINDOW,
+                        "stagnant loop at {}:{}:{}: `{}` unchanged for {} iterations",
+                        ::core::file!(), ::core::line!(), ::core::column!(),
+                        ::core::stringify!($($name),+), $crate::PARANOID_WINDOW,
+                    );
+                }
+                true
+            })] [
+                $($extra)*
+                (#[cfg(debug_assertions)] let mut paranoid = {
+                    use $crate::__private::paranoid::{Track as _, Untracked as _};
+                    $crate::__cfor_paranoid!(@snapshot $($name)+)
+                })
+                (#[cfg(debug_assertions)] let mut paranoid_unchanged = 0u64)
+            ])
+            $label;
+            $($init)*;
+            $($rest)*
+        )
+    };
+
+    // the snapshots are a list, `(a, (b, (c, ())))`, so that each one
+    // can be reached without knowing its index.
+    (@snapshot $first: ident $($name: ident)*) => {
+        ((&$crate::__private::paranoid::Probe(&$first)).snapshot(), $crate::__cfor_paranoid!(@snapshot $($name)*))
+    };
+    (@snapshot) => { () };
+    (@changed $state: expr; $first: ident $($name: ident)*) => {
+        (&$crate::__private::paranoid::Probe(&$first)).changed(&mut $state.0)
+            | $crate::__cfor_paranoid!(@changed $state.1; $($name)*)
+    };
+    (@changed $state: expr;) => { false };
+}
+
+/// The check for `runaway_cfor!`: first, it finds the names declared
+/// with `let mut` in the initialiser, like `__cfor_paranoid!`, and
+/// then, once the rest of the header has been split up, it adds a
+/// count of the iterations (in debug builds), which panics when it
+/// passes the limit.
+#[cfg(feature = "runaway-detector")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfor_runaway {
+    (@check ($kind: ident $before: tt [$($after: tt)*] [$($extra: tt)*]) [$($name: ident)*] $label: lifetime; ($($init: stmt),+) [$($part: tt)*] ($($step: expr),+) $($rest: tt)*) => {
+        $crate::__cfor!(
+            @merge
+            ($kind $before [$($after)* ({
+                #[cfg(debug_assertions)]
+                {
+                    runaway += 1;
+                    if runaway > runaway_limit {
+                        #[allow(unused_imports)]
+                        use $crate::__private::runaway::{Hidden as _, Show as _};
+                        ::core::panic!(
+                            ::core::concat!(
+                                "runaway loop at {}:{}:{}: more than {} iterations of `{}; {}; {}`"
+                                $(, ", ", ::core::stringify!($name), " = {:?}")*
+                            ),
+                            ::core::file!(), ::core::line!(), ::core::column!(), runaway_limit,
+                            ::core::stringify!($($init),+),
+                            ::core::stringify!($($part)&&*),
+                            ::core::stringify!($($step),+),
+                            $((&$crate::__private::runaway::Probe(&$name)).show(),)*
+                        )
+                    }
+                }
+                true
+            })] [
+                $($extra)*
+                (#[cfg(debug_assertions)] let runaway_limit = $crate::__private::runaway_limit())
+                (#[cfg(debug_assertions)] let mut runaway = 0u64)
+            ])
+            $label;
+            ($($init),+) [$($part)*] ($($step),+)
+            $($rest)*
+        )
+    };
+
+    ($kind: tt $label: lifetime [$($init: tt)*]; $($rest: tt)*) => {
+        $crate::__cfor_runaway!(@scan $kind $label [$($init)*] [] [$($init)*]; $($rest)*)
+    };
+    (@scan $kind: tt $label: lifetime $init: tt [$($name: ident)*] [let mut $new: ident $($todo: tt)*]; $($rest: tt)*) => {
+        $crate::__cfor_runaway!(@scan $kind $label $init [$($name)* $new] [$($todo)*]; $($rest)*)
+    };
+    (@scan $kind: tt $label: lifetime $init: tt $names: tt [$skip: tt $($todo: tt)*]; $($rest: tt)*) => {
+        $crate::__cfor_runaway!(@scan $kind $label $init $names [$($todo)*]; $($rest)*)
+    };
+    (@scan $kind: tt $label: lifetime [$($init: tt)*] $names: tt []; $($rest: tt)*) => {
+        $crate::__cfor!(@stmts (runaway $names $kind) $label; $($init)*; $($rest)*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfor_expand {
+    (lazy $label: lifetime; ($($init: stmt),+) $cond: tt ($($step: expr),+) (=> $e: expr)) => {
+        {
+            $($init;)+
+            // the state of the loop lives in the closure, and each call
+            // is one iteration: the step (except the first time), then
+            // the condition, then the expression.
+            let mut first = true;
+            let mut done = false;
+            ::core::iter::from_fn(move || {
+                if done {
+                    return $crate::__private::None;
+                }
+                if !first {
+                    $(#[allow(clippy::no_effect)] $step;)+
+                }
+                first = false;
+                $crate::__cfor_cond!($cond { done = true; return $crate::__private::None });
+                $crate::__private::Some($e)
+            })
+        }
+    };
+    (plain $label: lifetime; ($($init: stmt),+) $cond: tt ($($step: expr),+) $body: block $(finally $fin: block)? $(;)?) => {
+        {
+            // NB. the variables and labels introduced here (`first`,
+            // `'outer`, etc.) are hygienic, so they can't be seen by,
+            // or conflict with, anything in the user's code.
+            $($init;)+
+            'outer: loop {
+                $crate::__cfor_cond!($cond { break 'outer });
+
+                let mut first = true;
+                // the loop below runs the body once, and a `break`,
+                // `continue` or fall-through in the body each end up
+                // in a different place: a `break` exits the loop
+                // normally and so propagates to the `break 'outer`,
+                // while `continue` and falling through both come back
+                // to the top, and jump out of `'step` to the step.
+                //
+                // `continue` necessarily passes through the same
+                // point as first entry into the loop, so one flag is
+                // needed to tell them apart. It's modified exactly
+                // once with very simple control flow, so the
+                // optimiser sees straight through it, and this ends
+                // up the same as a plain C for loop.
+                'step: {
+                    // the label goes here (not on `'outer`) so that
+                    // a `continue $label` from a loop nested in the
+                    // body lands on the `if` below, and still runs the
+                    // step.
+                    $label: loop {
+                        if !first { break 'step }
+                        first = false;
+
+                        $body
+                    }
+                    // (a body without a `break` makes this
+                    // unreachable.)
+                    #[allow(unreachable_code)]
+                    break 'outer
+                }
+
+                $(#[allow(clippy::no_effect)] $step;)+
+            }
+            $($fin)?
+        }
+    };
+
+    (plain $label: lifetime; ($($init: stmt),+) $cond: tt ($($step: expr),+) $body: block else $els: block $(finally $fin: block)? $(;)?) => {
+        {
+            $($init;)+
+            // the same as above, except the outer loop tells us
+            // whether it stopped because of the condition.
+            let completed = 'outer: loop {
+                $crate::__cfor_cond!($cond { break 'outer true });
+
+                let mut first = true;
+                'step: {
+                    $label: loop {
+                        if !first { break 'step }
+                        first = false;
+
+                        $body
+                    }
+                    #[allow(unreachable_code)]
+                    break 'outer false
+                }
+
+                $(#[allow(clippy::no_effect)] $step;)+
+            };
+            if completed $els
+            $($fin)?
+        }
+    };
+
+    (fallible $label: lifetime; $init: tt $cond: tt $step: tt $body: block $(else $els: block)? $(finally $fin: block)? $(;)? -> $err: ty) => {
+        {
+            let result = (|| -> $crate::__private::Result<(), $err> {
+                $crate::__cfor_expand!(plain $label; $init $cond $step $body $(else $els)?);
+                $crate::__private::Ok(())
+            })();
+            $($fin)?
+            result
+        }
+    };
+
+    (val $label: lifetime; ($($init: stmt),+) $cond: tt ($($step: expr),+) $body: block $(finally $fin: block)? $(;)?) => {
+        {
+            $($init;)+
+            let value = 'outer: loop {
+                $crate::__cfor_cond!($cond { break 'outer $crate::__private::None });
+
+                let mut first = true;
+                // as above, but the inner loop's value is whatever
+                // the body `break`s with.
+                'step: {
+                    let broke_with = $label: loop {
+                        if !first { break 'step }
+                        first = false;
+
+                        $body
+                    };
+                    #[allow(unreachable_code)]
+                    break 'outer $crate::__private::Some(broke_with)
+                }
+
+                $(#[allow(clippy::no_effect)] $step;)+
+            };
+            $($fin)?
+            value
+        }
+    };
+
+    ($kind: ident $label: lifetime; $init: tt $cond: tt $step: tt $body: block $($rest: tt)*) => {
+        ::core::compile_error!(
+            "unexpected tokens after the loop body; only `else { ... }` and then \
+             `finally { ... }` can follow it"
+        )
+    };
+}
+
+/// A C-style `do`-`while` loop in macro form.
+///
+/// This takes the form `cdo!({ body } while condition; step)`, and
+/// runs `body` at least once before `condition` is first checked. As
+/// with `cfor!`, `step` is evaluated at the end of every iteration,
+/// including those cut short by `continue`, and can be omitted (along
+/// with the preceding `;`). Like `cfor!`, it can be labelled, as in
+/// `cdo!('label: { body } while condition)`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let mut x = 10;
+///     let mut runs = 0;
+///
+///     // the condition is false from the start, but the body still
+///     // runs once.
+///     cdo!({
+///         runs += 1;
+///         if runs > 1 { continue }
+///     } while x < 10; x += 1);
+///
+///     assert_eq!(runs, 1);
+///     assert_eq!(x, 11);
+/// }
+/// ```
+#[macro_export]
+macro_rules! cdo {
+    // do { $body } while ($cond);
+    ($($label: lifetime:)? $body: block while $cond: expr $(;)?) => {
+        $crate::cdo!($($label:)? $body while $cond; ())
+    };
+
+    // do { $body; $step } while ($cond);
+    ($($label: lifetime:)? $body: block while $cond: expr; $($step: expr),+ $(,)?) => {
+        // the first check of the condition is skipped, so the body
+        // is entered unconditionally.
+        $crate::cfor!($($label:)? let mut first = true; first || $cond; first = false, $($step),+; $body)
+    };
+}
+
+/// A `while` loop with a step, in macro form.
+///
+/// This takes the form `cwhile!(condition; step; { body })`, and is
+/// identical to `cfor!` with an empty initialiser, that is,
+/// `cfor!(; condition; step; { body })`, including support for a
+/// label, as in `cwhile!('label: condition; step; { body })`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let v = [1, 2, 3, 4, 5, 6];
+///     let mut i = 0;
+///     let mut sum = 0;
+///
+///     cwhile!(i < v.len(); i += 1; {
+///         if v[i] % 2 == 0 { continue }
+///         sum += v[i];
+///     });
+///
+///     assert_eq!(sum, 1 + 3 + 5);
+/// }
+/// ```
+#[macro_export]
+macro_rules! cwhile {
+    ($label: lifetime: $($rest: tt)*) => {
+        $crate::cfor!($label: ; $($rest)*)
+    };
+    ($($rest: tt)*) => {
+        $crate::cfor!(; $($rest)*)
+    };
+}
+
+/// An infinite loop with a step, in macro form.
+///
+/// This takes the form `cloop!(step; { body })`, and is identical to
+/// `cfor!` with an empty initialiser and condition, that is,
+/// `cfor!(;; step; { body })`. The loop only exits via a `break` (or
+/// `return`, etc.) in `body`. A label can be given as in
+/// `cloop!('label: step; { body })`.
+///
+/// The `;` after the step is needed, as in `cfor!` and `cwhile!`,
+/// rather than writing `cloop!(step { body })`: `macro_rules!` doesn't
+/// allow an expression to be followed directly by a `{`, since the
+/// brace could be a continuation of it (as in `x = S { .. }`).
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let mut x = 1u32;
+///
+///     cloop!(x *= 3; {
+///         if x % 2 == 0 { continue }
+///         if x > 100 { break }
+///     });
+///
+///     assert_eq!(x, 243);
+/// }
+/// ```
+#[macro_export]
+macro_rules! cloop {
+    ($label: lifetime: $($rest: tt)*) => {
+        $crate::cfor!($label: ;; $($rest)*)
+    };
+    ($($rest: tt)*) => {
+        $crate::cfor!(;; $($rest)*)
+    };
+}
+
+/// Runs a body a fixed number of times, in macro form.
+///
+/// This takes the form `crepeat!(n { body })`, and runs `body` `n`
+/// times, like C's `for (i = 0; i < n; i++) { body }` when `i` isn't
+/// otherwise needed; `n` is evaluated once, before the loop. The
+/// index can be exposed with `crepeat!(i in n { body })`, where `i`
+/// counts from `0`, and belongs to the iteration: modifying it (if
+/// declared `mut`) doesn't change the number of iterations. Like
+/// `cfor!`, `continue` moves on to the next iteration, and a label
+/// can be given, as in `crepeat!('label: n { body })`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let mut s = String::new();
+///     crepeat!(3 {
+///         s.push('a');
+///     });
+///     assert_eq!(s, "aaa");
+///
+///     let mut odd = vec![];
+///     crepeat!(i in 10 {
+///         if i % 2 == 0 { continue }
+///         odd.push(i);
+///     });
+///     assert_eq!(odd, [1, 3, 5, 7, 9]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! crepeat {
+    // the count is found by collecting tokens up to the final
+    // block, since an `expr` can't be followed by one directly.
+    (@count $label: lifetime [$($i: tt)*] [$($n: tt)+]; { $($body: tt)* }) => {
+        $crate::cfor!($label: let n = $($n)+, let mut count = 0; count < n; count += 1; {
+            $crate::crepeat!(@bind [$($i)*] count);
+            $($body)*
+        })
+    };
+    (@count $label: lifetime $i: tt [$($n: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::crepeat!(@count $label $i [$($n)* $next]; $($rest)*)
+    };
+    (@bind [] $count: ident) => {};
+    (@bind [$($i: tt)+] $count: ident) => {
+        let $($i)+ = $count;
+    };
+
+    ($label: lifetime: mut $i: ident in $($rest: tt)*) => {
+        $crate::crepeat!(@count $label [mut $i] []; $($rest)*)
+    };
+    ($label: lifetime: $i: ident in $($rest: tt)*) => {
+        $crate::crepeat!(@count $label [$i] []; $($rest)*)
+    };
+    ($label: lifetime: $($rest: tt)*) => {
+        $crate::crepeat!(@count $label [] []; $($rest)*)
+    };
+    (mut $i: ident in $($rest: tt)*) => {
+        $crate::crepeat!(@count 'cfor [mut $i] []; $($rest)*)
+    };
+    ($i: ident in $($rest: tt)*) => {
+        $crate::crepeat!(@count 'cfor [$i] []; $($rest)*)
+    };
+    ($($rest: tt)*) => {
+        $crate::crepeat!(@count 'cfor [] []; $($rest)*)
+    };
+}
+
+/// A decrementing loop, in macro form.
+///
+/// This takes the form `countdown!(i from n { body })`, and runs
+/// `body` with `i` taking the values `n - 1`, `n - 2`, ..., `0`, like
+/// C's `for (i = n; i-- > 0;) { body }`. Unlike a direct translation
+/// of that trick, it never computes `0 - 1`, so it doesn't panic (or
+/// wrap around) for unsigned `i`. As with `crepeat!`, `n` is evaluated
+/// once, `i` can be declared `mut` without affecting the loop,
+/// `continue` moves on to the next value, and a label can be given,
+/// as in `countdown!('label: i from n { body })`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let v = [1, 2, 3, 4];
+///     let mut reversed = vec![];
+///
+///     countdown!(i from v.len() {
+///         reversed.push(v[i]);
+///     });
+///
+///     assert_eq!(reversed, [4, 3, 2, 1]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! countdown {
+    // the step only runs after a check that `next > 0`, so it never
+    // underflows.
+    (@count $label: lifetime [$($i: tt)+] [$($n: tt)+]; { $($body: tt)* }) => {
+        $crate::cfor!($label: let mut next = $($n)+; next > 0; next -= 1; {
+            let $($i)+ = next - 1;
+            $($body)*
+        })
+    };
+    (@count $label: lifetime $i: tt [$($n: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::countdown!(@count $label $i [$($n)* $next]; $($rest)*)
+    };
+
+    ($label: lifetime: mut $i: ident from $($rest: tt)*) => {
+        $crate::countdown!(@count $label [mut $i] []; $($rest)*)
+    };
+    ($label: lifetime: $i: ident from $($rest: tt)*) => {
+        $crate::countdown!(@count $label [$i] []; $($rest)*)
+    };
+    (mut $i: ident from $($rest: tt)*) => {
+        $crate::countdown!(@count 'cfor [mut $i] []; $($rest)*)
+    };
+    ($i: ident from $($rest: tt)*) => {
+        $crate::countdown!(@count 'cfor [$i] []; $($rest)*)
+    };
+}
+
+/// A loop counting down over a range, in macro form.
+///
+/// This takes the form `cfor_rev!(i in a..b step s { body })`, and
+/// runs `body` with `i` taking the values in `a..b` from the top down,
+/// `b - 1`, `b - 1 - s`, `b - 1 - 2 * s`, ..., for as long as they're
+/// at least `a`, like C's `for (i = b - 1; i >= a; i -= s) { body }`.
+/// With `a..=b`, `i` starts at `b` instead, and without `step s`, the
+/// step is `1`, so `cfor_rev!(i in a..b { body })` visits the same
+/// values as `for i in (a..b).rev() { body }`.
+///
+/// The direct translations of that C loop into Rust either start at
+/// `b - 1` when `b` is `0`, or check `i >= a` only after computing the
+/// `i - s` that goes below `0`, and so panic (in debug builds) for an
+/// unsigned `i`, but this never computes any value outside of the
+/// range. The bounds and step are evaluated once, before the loop, and
+/// the step must be positive. As with `countdown!`, `i` can be
+/// declared `mut` without affecting the loop, `continue` moves on to
+/// the next value, and a label can be given, as in
+/// `cfor_rev!('label: i in a..b { body })`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let mut seen = vec![];
+///     cfor_rev!(i in 0..10usize step 3 {
+///         seen.push(i);
+///     });
+///     assert_eq!(seen, [9, 6, 3, 0]);
+///
+///     let mut seen = vec![];
+///     cfor_rev!(i in 2..=8u8 step 4 {
+///         seen.push(i);
+///     });
+///     assert_eq!(seen, [8, 4]);
+///
+///     // nothing, rather than an underflow.
+///     cfor_rev!(_i in 0..0u32 { unreachable!() });
+/// }
+/// ```
+#[macro_export]
+macro_rules! cfor_rev {
+    // the range is collected up to the step or the body, and split
+    // at the `..`.
+    (@range $label: lifetime $i: tt [$($range: tt)+]; step $step: expr; { $($body: tt)* }) => {
+        $crate::cfor_rev!(@split $label $i ($step) { $($body)* } []; $($range)+)
+    };
+    (@range $label: lifetime $i: tt [$($range: tt)+]; step $($rest: tt)+) => {
+        $crate::cfor_rev!(@step $label $i [$($range)+] []; $($rest)+)
+    };
+    (@range $label: lifetime $i: tt [$($range: tt)+]; { $($body: tt)* } $(;)?) => {
+        $crate::cfor_rev!(@split $label $i (1) { $($body)* } []; $($range)+)
+    };
+    (@range $label: lifetime $i: tt [$($range: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::cfor_rev!(@range $label $i [$($range)* $next]; $($rest)*)
+    };
+    (@step $label: lifetime $i: tt $range: tt [$($step: tt)+]; { $($body: tt)* } $(;)?) => {
+        $crate::cfor_rev!(@range $label $i $range; step $($step)+; { $($body)* })
+    };
+    (@step $label: lifetime $i: tt $range: tt [$($step: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::cfor_rev!(@step $label $i $range [$($step)* $next]; $($rest)*)
+    };
+
+    (@split $label: lifetime $i: tt $step: tt $body: tt [$($lo: tt)+]; ..= $($hi: tt)+) => {
+        $crate::cfor_rev!(@expand $label $i $step $body ($($lo)+) inclusive ($($hi)+))
+    };
+    (@split $label: lifetime $i: tt $step: tt $body: tt [$($lo: tt)+]; .. $($hi: tt)+) => {
+        $crate::cfor_rev!(@expand $label $i $step $body ($($lo)+) exclusive ($($hi)+))
+    };
+    (@split $label: lifetime $i: tt $step: tt $body: tt [$($lo: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::cfor_rev!(@split $label $i $step $body [$($lo)* $next]; $($rest)*)
+    };
+    (@split $label: lifetime $i: tt $step: tt $body: tt [$($lo: tt)*];) => {
+        ::core::compile_error!("`cfor_rev!` expects a range with both ends, like `a..b` or `a..=b`")
+    };
+
+    // the step only happens when it stays in the range, and is worked
+    // out without overflowing, even when the range crosses zero.
+    (@expand $label: lifetime [$($i: tt)+] $step: tt { $($body: tt)* } $lo: tt $bound: ident $hi: tt) => {
+        {
+            let lo = $lo;
+            let hi = $hi;
+            let step = $step;
+            ::core::assert!(step > 0, "`cfor_rev!` needs a positive step");
+            let first = $crate::cfor_rev!(@first $bound lo hi);
+            $crate::cfor!($label: let mut next = first;
+                          let $crate::__private::Some(current) = next;
+                          next = $crate::__private::step_down(current, lo, step); {
+                let $($i)+ = current;
+                $($body)*
+            });
+        }
+    };
+
+    (@first inclusive $lo: ident $hi: ident) => {
+        if $hi >= $lo { $crate::__private::Some($hi) } else { $crate::__private::None }
+    };
+    (@first exclusive $lo: ident $hi: ident) => {
+        if $hi > $lo { $crate::__private::Some($hi - 1) } else { $crate::__private::None }
+    };
+
+    ($label: lifetime: mut $i: ident in $($rest: tt)*) => {
+        $crate::cfor_rev!(@range $label [mut $i] []; $($rest)*)
+    };
+    ($label: lifetime: $i: ident in $($rest: tt)*) => {
+        $crate::cfor_rev!(@range $label [$i] []; $($rest)*)
+    };
+    (mut $i: ident in $($rest: tt)*) => {
+        $crate::cfor_rev!(@range 'cfor [mut $i] []; $($rest)*)
+    };
+    ($i: ident in $($rest: tt)*) => {
+        $crate::cfor_rev!(@range 'cfor [$i] []; $($rest)*)
+    };
+}
+
+/// A loop with two indices converging from either end, in macro
+/// form.
+///
+/// This takes the form `two_ptr!(lo = a, hi = b; lo_step; hi_step; {
+/// body })`, and declares mutable `lo` and `hi`, then runs `body`,
+/// `lo_step` and `hi_step` in turn while `lo < hi`, like C's `for (lo
+/// = a, hi = b; lo < hi; lo++, hi--) { body }`. This is the shape of
+/// in-place reversal, palindrome checks and partitioning, where the
+/// steps are often conditional on the body, and are free to move just
+/// one of the ends, or both, or neither.
+///
+/// As with `cfor!`, both steps run after a `continue`, and a label can
+/// be given, as in `two_ptr!('label: lo = a, hi = b; ...)`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn is_palindrome(s: &[u8]) -> bool {
+///     let mut matched = true;
+///     two_ptr!(lo = 0, hi = s.len(); lo += 1; hi -= 1; {
+///         if s[lo] != s[hi - 1] {
+///             matched = false;
+///             break
+///         }
+///     });
+///     matched
+/// }
+///
+/// fn main() {
+///     assert!(is_palindrome(b"racecar"));
+///     assert!(!is_palindrome(b"rocket"));
+///
+///     // move the odd numbers to the front.
+///     let mut v = [1, 2, 4, 3, 6, 5, 7];
+///     let mut swap = false;
+///     two_ptr!(lo = 0, hi = v.len() - 1; lo += !swap as usize; hi -= swap as usize; {
+///         swap = v[lo] % 2 == 0;
+///         if swap { v.swap(lo, hi) }
+///     });
+///     assert!(v[..4].iter().all(|x| x % 2 == 1));
+/// }
+/// ```
+#[macro_export]
+macro_rules! two_ptr {
+    ($label: lifetime: $lo: ident = $a: expr, $hi: ident = $b: expr; $lo_step: expr; $hi_step: expr; { $($body: tt)* } $(;)?) => {
+        $crate::cfor!($label: let mut $lo = $a, let mut $hi = $b; $lo < $hi; $lo_step, $hi_step; {
+            $($body)*
+        })
+    };
+    ($lo: ident = $a: expr, $hi: ident = $b: expr; $lo_step: expr; $hi_step: expr; { $($body: tt)* } $(;)?) => {
+        $crate::two_ptr!('cfor: $lo = $a, $hi = $b; $lo_step; $hi_step; { $($body)* })
+    };
+}
+
+/// Two C-style `for` loops in lockstep.
+///
+/// This takes the form `cfor_zip!((init_a; cond_a; step_a), (init_b;
+/// cond_b; step_b) { body })`, and runs `body` while both `cond_a`
+/// and `cond_b` hold, then both steps, like `for (init_a, init_b;
+/// cond_a && cond_b; step_a, step_b)`. Writing the two progressions
+/// separately keeps each variable with its own condition and step,
+/// rather than mixing them up in one long header. Each header is the
+/// same as a `cfor!`'s, except that the conditions can't contain
+/// `let`. Everything after the headers is the same as `cfor!`, and a
+/// label can be given, as in `cfor_zip!('label: (...), (...) { ...
+/// })`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     // every other element of `a`, with every third element of `b`
+///     // from the end.
+///     let a = [1, 2, 3, 4, 5, 6, 7, 8];
+///     let b = [10, 20, 30, 40, 50, 60, 70, 80, 90];
+///
+///     let mut pairs = vec![];
+///     cfor_zip!((let mut i = 0; i < a.len(); i += 2), (let mut j = b.len(); j > 0; j -= 3) {
+///         pairs.push((a[i], b[j - 1]));
+///     });
+///     assert_eq!(pairs, [(1, 90), (3, 60), (5, 30)]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! cfor_zip {
+    // each header is split at its `;`s, into `[init] [cond] [step]`.
+    (@split $cont: tt init [$($init: tt)*] $cond: tt $step: tt; ; $($rest: tt)*) => {
+        $crate::cfor_zip!(@split $cont cond [$($init)*] $cond $step; $($rest)*)
+    };
+    (@split $cont: tt init [$($init: tt)*] $cond: tt $step: tt; $next: tt $($rest: tt)*) => {
+        $crate::cfor_zip!(@split $cont init [$($init)* $next] $cond $step; $($rest)*)
+    };
+    (@split $cont: tt cond $init: tt [$($cond: tt)*] $step: tt; ; $($rest: tt)*) => {
+        $crate::cfor_zip!(@split $cont step $init [$($cond)*] $step; $($rest)*)
+    };
+    (@split $cont: tt cond $init: tt [$($cond: tt)*] $step: tt; $next: tt $($rest: tt)*) => {
+        $crate::cfor_zip!(@split $cont cond $init [$($cond)* $next] $step; $($rest)*)
+    };
+    (@split $cont: tt step $init: tt $cond: tt [$($step: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::cfor_zip!(@split $cont step $init $cond [$($step)* $next]; $($rest)*)
+    };
+    (@split $cont: tt step $init: tt $cond: tt $step: tt;) => {
+        $crate::cfor_zip!(@fill $cont $init $cond $step)
+    };
+    (@split $cont: tt $part: ident $init: tt $cond: tt $step: tt;) => {
+        ::core::compile_error!("expected each header to have three `;`-separated parts, like `(init; cond; step)`")
+    };
+
+    // empty parts are filled in, as in `for (;;)`.
+    (@fill $cont: tt [$($init: tt)+] [$($cond: tt)+] [$($step: tt)+]) => {
+        $crate::cfor_zip!(@next $cont [$($init)+] [$($cond)+] [$($step)+])
+    };
+    (@fill $cont: tt [] $cond: tt $step: tt) => {
+        $crate::cfor_zip!(@fill $cont [()] $cond $step)
+    };
+    (@fill $cont: tt $init: tt [] $step: tt) => {
+        $crate::cfor_zip!(@fill $cont $init [true] $step)
+    };
+    (@fill $cont: tt $init: tt $cond: tt []) => {
+        $crate::cfor_zip!(@fill $cont $init $cond [()])
+    };
+
+    (@next [$label: tt [] ($($b: tt)*) $rest: tt] $init: tt $cond: tt $step: tt) => {
+        $crate::cfor_zip!(@split [$label [$init $cond $step] () $rest] init [] [] []; $($b)*)
+    };
+    (@next [[$($label: tt)*] [[$($ia: tt)*] [$($ca: tt)*] [$($sa: tt)*]] () [$($rest: tt)*]]
+     [$($ib: tt)*] [$($cb: tt)*] [$($sb: tt)*]) => {
+        $crate::cfor!($($label)* $($ia)*, $($ib)*; ($($ca)*) && ($($cb)*); $($sa)*, $($sb)*; $($rest)*)
+    };
+
+    ($label: lifetime: ($($a: tt)*), ($($b: tt)*) $($rest: tt)*) => {
+        $crate::cfor_zip!(@split [[$label:] [] ($($b)*) [$($rest)*]] init [] [] []; $($a)*)
+    };
+    (($($a: tt)*), ($($b: tt)*) $($rest: tt)*) => {
+        $crate::cfor_zip!(@split [[] [] ($($b)*) [$($rest)*]] init [] [] []; $($a)*)
+    };
+}
+
+/// A Pascal-style `repeat`-`until` loop, in macro form.
+///
+/// This takes the form `repeat_until!(condition; step { body })`, and
+/// runs `body` and then `step` until `condition` is true, checking it
+/// only after each iteration, so `body` always runs at least once.
+/// That is, it is `cdo!({ body } while !condition; step)`, and
+/// similarly `step` runs even after a `continue`, and can be empty,
+/// as in `repeat_until!(condition; { body })`. A label can be given
+/// as in `repeat_until!('label: condition; step { body })`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let mut x = 100;
+///     let mut steps = 0;
+///
+///     repeat_until!(x == 1; steps += 1 {
+///         x = if x % 2 == 0 { x / 2 } else { 3 * x + 1 };
+///     });
+///
+///     assert_eq!(steps, 25);
+/// }
+/// ```
+#[macro_export]
+macro_rules! repeat_until {
+    (@step [$($label: lifetime)?] $cond: expr; [$($step: tt)*]; { $($body: tt)* }) => {
+        // `cdo!` inlined, since a label can't be forwarded to it
+        // unambiguously.
+        $crate::cfor!($($label:)? let mut first = true; first || !$cond; first = false, $crate::repeat_until!(@or_unit $($step)*); {
+            $($body)*
+        })
+    };
+    (@step $label: tt $cond: expr; [$($step: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::repeat_until!(@step $label $cond; [$($step)* $next]; $($rest)*)
+    };
+    (@or_unit) => { () };
+    (@or_unit $($step: tt)+) => { $($step)+ };
+
+    ($label: lifetime: $cond: expr; $($rest: tt)*) => {
+        $crate::repeat_until!(@step [$label] $cond; []; $($rest)*)
+    };
+    ($cond: expr; $($rest: tt)*) => {
+        $crate::repeat_until!(@step [] $cond; []; $($rest)*)
+    };
+}
+
+/// A loop that works through a collection of work until it's empty,
+/// in macro form.
+///
+/// This takes the form `worklist!(item from queue { body })`, and
+/// takes the next item from `queue` (with [`Worklist::pop_next`])
+/// into the pattern `item`, then runs `body`, until `queue` is empty,
+/// like C's `while ((item = pop(queue)) != NULL) { body }`. The body is
+/// free to add more work to `queue`, so this is the core of graph
+/// searches and dataflow analyses: with a `VecDeque` it's
+/// breadth-first, and with a `Vec` depth-first. `queue` is a place,
+/// like `self.queue` or `*queue`.
+///
+/// A step can be given as in `worklist!(item from queue; step { body
+/// })`, which is run after each iteration, including one that ends
+/// with `continue`, as for `cfor!`, and a label can be given as in
+/// `worklist!('label: item from queue { ... })`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// use std::collections::VecDeque;
+///
+/// # #[cfg(feature = "std")]
+/// fn main() {
+///     // the distances from node 0, by breadth-first search.
+///     let edges = [vec![1, 2], vec![3], vec![3], vec![4], vec![]];
+///     let mut distance = [None; 5];
+///     distance[0] = Some(0);
+///
+///     let mut queue = VecDeque::from([0]);
+///     let mut visited = 0;
+///     worklist!(node from queue; visited += 1 {
+///         for &next in &edges[node] {
+///             if distance[next].is_some() { continue }
+///             distance[next] = Some(distance[node].unwrap() + 1);
+///             queue.push_back(next);
+///         }
+///     });
+///     assert_eq!(distance, [Some(0), Some(1), Some(1), Some(2), Some(3)]);
+///     assert_eq!(visited, 5);
+/// }
+/// # #[cfg(not(feature = "std"))] fn main() {}
+/// ```
+#[macro_export]
+macro_rules! worklist {
+    // the pattern is collected up to the `from`, and the queue up to
+    // the step or the body.
+    (@pat $label: tt [$($pat: tt)+]; from $($rest: tt)+) => {
+        $crate::worklist!(@queue $label [$($pat)+] []; $($rest)+)
+    };
+    (@pat $label: tt [$($pat: tt)*]; $next: tt $($rest: tt)+) => {
+        $crate::worklist!(@pat $label [$($pat)* $next]; $($rest)+)
+    };
+    (@queue $label: tt $pat: tt [$($queue: tt)+]; ; $($rest: tt)+) => {
+        $crate::worklist!(@step $label $pat [$($queue)+] []; $($rest)+)
+    };
+    (@queue $label: tt $pat: tt [$($queue: tt)+]; { $($body: tt)* } $(;)?) => {
+        $crate::worklist!(@expand $label $pat [$($queue)+] [] { $($body)* })
+    };
+    (@queue $label: tt $pat: tt [$($queue: tt)*]; $next: tt $($rest: tt)+) => {
+        $crate::worklist!(@queue $label $pat [$($queue)* $next]; $($rest)+)
+    };
+    (@step $label: tt $pat: tt $queue: tt [$($step: tt)*]; { $($body: tt)* } $(;)?) => {
+        $crate::worklist!(@expand $label $pat $queue [$($step)*] { $($body)* })
+    };
+    (@step $label: tt $pat: tt $queue: tt [$($step: tt)*]; $next: tt $($rest: tt)+) => {
+        $crate::worklist!(@step $label $pat $queue [$($step)* $next]; $($rest)+)
+    };
+    (@expand [$($label: tt)*] [$($pat: tt)+] [$($queue: tt)+] [$($step: tt)*] $body: tt) => {
+        $crate::cfor!(
+            $($label)* ;
+            let $crate::__private::Some($($pat)+) = $crate::Worklist::pop_next(&mut $($queue)+);
+            $($step)*;
+            $body
+        )
+    };
+
+    ($label: lifetime: $($rest: tt)+) => {
+        $crate::worklist!(@pat [$label:] []; $($rest)+)
+    };
+    ($($rest: tt)+) => {
+        $crate::worklist!(@pat [] []; $($rest)+)
+    };
+}
+
+/// A loop over the divisors of a number, in pairs, in macro form.
+///
+/// This takes the form `divisors!(d, q in n { body })`, and runs
+/// `body` for each divisor `d` of `n` with `d <= q`, with `q` bound to
+/// `n / d`, in increasing order of `d`, like C's `for (d = 1; d * d <=
+/// n; d++) { if (n % d == 0) { q = n / d; body } }`. So each pair of
+/// divisors is visited once, and a square number's root is visited
+/// once, as `d == q`, rather than twice. The condition is written as
+/// `d <= n / d`, so it can't overflow. `n` is evaluated once, before
+/// the loop, and should be positive (there are no divisors otherwise).
+///
+/// As with `crepeat!`, `d` and `q` belong to the iteration, `continue`
+/// moves on to the next divisor, and a label can be given, as in
+/// `divisors!('label: d, q in n { body })`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let mut all = vec![];
+///     divisors!(d, q in 36u32 {
+///         all.push(d);
+///         if d != q { all.push(q) }
+///     });
+///     all.sort();
+///     assert_eq!(all, [1, 2, 3, 4, 6, 9, 12, 18, 36]);
+///
+///     let mut pairs = 0;
+///     divisors!(_d, _q in 97 {
+///         pairs += 1;
+///     });
+///     assert_eq!(pairs, 1);
+/// }
+/// ```
+#[macro_export]
+macro_rules! divisors {
+    (@count $label: lifetime $d: ident $q: ident [$($n: tt)+]; { $($body: tt)* }) => {
+        $crate::cfor!($label: let n = $($n)+, let mut d = 1; d <= n / d; d += 1; {
+            if n % d != 0 {
+                continue;
+            }
+            let $d = d;
+            let $q = n / d;
+            $($body)*
+        })
+    };
+    (@count $label: lifetime $d: ident $q: ident [$($n: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::divisors!(@count $label $d $q [$($n)* $next]; $($rest)*)
+    };
+
+    ($label: lifetime: $d: ident, $q: ident in $($rest: tt)*) => {
+        $crate::divisors!(@count $label $d $q []; $($rest)*)
+    };
+    ($d: ident, $q: ident in $($rest: tt)*) => {
+        $crate::divisors!(@count 'cfor $d $q []; $($rest)*)
+    };
+}
+
+/// A loop over the digits of a number, in macro form.
+///
+/// This takes the form `digits!(d in n { body })`, and runs `body`
+/// with `d` bound to each decimal digit of `n`, from the least
+/// significant, like C's `while (n) { d = n % 10; body; n /= 10; }`.
+/// Another base can be given, as in `digits!(d in n; base = 2 { body
+/// })`, which must be at least `2`. As in C, zero has no digits. `n`
+/// is evaluated once, before the loop, and isn't changed by it.
+///
+/// As with `crepeat!`, `d` belongs to the iteration, `continue`
+/// moves on to the next digit, and a label can be given, as in
+/// `digits!('label: d in n { body })`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let n = 9045u32;
+///     let mut sum = 0;
+///     digits!(d in n {
+///         sum += d;
+///     });
+///     assert_eq!(sum, 18);
+///
+///     let mut hex = String::new();
+///     digits!(d in 0xbeefu16; base = 16 {
+///         hex.insert(0, char::from_digit(d.into(), 16).unwrap());
+///     });
+///     assert_eq!(hex, "beef");
+/// }
+/// ```
+#[macro_export]
+macro_rules! digits {
+    (@count $label: lifetime $d: ident $base: tt [$($n: tt)+]; ; base = $($rest: tt)+) => {
+        $crate::digits!(@base $label $d [$($n)+] []; $($rest)+)
+    };
+    (@count $label: lifetime $d: ident $base: tt [$($n: tt)+]; { $($body: tt)* }) => {
+        $crate::digits!(@expand $label $d $base [$($n)+] { $($body)* })
+    };
+    (@count $label: lifetime $d: ident $base: tt [$($n: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::digits!(@count $label $d $base [$($n)* $next]; $($rest)*)
+    };
+    (@base $label: lifetime $d: ident $n: tt [$($base: tt)+]; { $($body: tt)* }) => {
+        $crate::digits!(@expand $label $d ($($base)+) $n { $($body)* })
+    };
+    (@base $label: lifetime $d: ident $n: tt [$($base: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::digits!(@base $label $d $n [$($base)* $next]; $($rest)*)
+    };
+    (@expand $label: lifetime $d: ident $base: tt [$($n: tt)+] { $($body: tt)* }) => {
+        {
+            let n = $($n)+;
+            let base = $base;
+            ::core::assert!(base >= 2, "`digits!` needs a base of at least 2");
+            $crate::cfor!($label: let mut n = n; n != 0; n /= base; {
+                let $d = n % base;
+                $($body)*
+            })
+        }
+    };
+
+    ($label: lifetime: $d: ident in $($rest: tt)*) => {
+        $crate::digits!(@count $label $d (10) []; $($rest)*)
+    };
+    ($d: ident in $($rest: tt)*) => {
+        $crate::digits!(@count 'cfor $d (10) []; $($rest)*)
+    };
+}
+
+/// A loop over the set bits of an integer, in macro form.
+///
+/// This takes the form `bitloop!(b in x { body })`, and runs `body`
+/// with `b` bound to each set bit of `x` on its own (as a value of the
+/// same type, with just that bit set), from the lowest, like C's `for
+/// (; x; x &= x - 1) { b = x & -x; body }`. The index of the bit can
+/// be bound too, as in `bitloop!(b, i in x { body })`, where `i` is the
+/// `u32` from `trailing_zeros`. `x` is evaluated once, before the loop,
+/// and isn't changed by it; it needs a known integer type, so a bare
+/// literal needs a suffix. Negative numbers work, with the sign bit
+/// last.
+///
+/// As with `crepeat!`, `b` and `i` belong to the iteration, `continue`
+/// moves on to the next bit, and a label can be given, as in
+/// `bitloop!('label: b in x { body })`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let mut bits = vec![];
+///     bitloop!(b, i in 0b1010_0110u8 {
+///         bits.push((b, i));
+///     });
+///     assert_eq!(bits, [(0b10, 1), (0b100, 2), (0b10_0000, 5), (0b1000_0000, 7)]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! bitloop {
+    (@count $label: lifetime $b: ident [$($i: ident)?] [$($x: tt)+]; { $($body: tt)* }) => {
+        $crate::cfor!($label: let mut x = $($x)+; x != 0; x &= x.wrapping_sub(1); {
+            let $b = x & x.wrapping_neg();
+            $(let $i = x.trailing_zeros();)?
+            $($body)*
+        })
+    };
+    (@count $label: lifetime $b: ident $i: tt [$($x: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::bitloop!(@count $label $b $i [$($x)* $next]; $($rest)*)
+    };
+
+    ($label: lifetime: $b: ident $(, $i: ident)? in $($rest: tt)*) => {
+        $crate::bitloop!(@count $label $b [$($i)?] []; $($rest)*)
+    };
+    ($b: ident $(, $i: ident)? in $($rest: tt)*) => {
+        $crate::bitloop!(@count 'cfor $b [$($i)?] []; $($rest)*)
+    };
+}
+
+/// A loop over the submasks of a bit mask, in macro form.
+///
+/// This takes the form `submasks!(s in m { body })`, and runs `body`
+/// with `s` bound to each non-empty submask of `m` (each value whose
+/// set bits are all set in `m`), from `m` itself down, like C's `for
+/// (s = m; s; s = (s - 1) & m) { body }`. The empty mask can be
+/// included too, as the last one, as in `submasks!(s in m; with_empty
+/// { body })`, which is the loop that is easy to get wrong in C,
+/// since `s = (s - 1) & m` wraps back around to `m` after `0`. `m` is
+/// evaluated once, before the loop, and isn't changed by it; it needs a
+/// known integer type, so a bare literal needs a suffix.
+///
+/// As with `crepeat!`, `s` belongs to the iteration, `continue` moves
+/// on to the next submask, and a label can be given, as in
+/// `submasks!('label: s in m { body })`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let mut all = vec![];
+///     submasks!(s in 0b1010u8 {
+///         all.push(s);
+///     });
+///     assert_eq!(all, [0b1010, 0b1000, 0b0010]);
+///
+///     let mut count = 0;
+///     submasks!(_s in 0b111u32; with_empty {
+///         count += 1;
+///     });
+///     assert_eq!(count, 8);
+/// }
+/// ```
+#[macro_export]
+macro_rules! submasks {
+    (@count $label: lifetime $s: ident [$($m: tt)+]; ; with_empty { $($body: tt)* }) => {
+        $crate::cfor!($label: let m = $($m)+, let mut s = m, let mut more = true;
+                      more;
+                      more = s != 0, s = s.wrapping_sub(1) & m; {
+            let $s = s;
+            $($body)*
+        })
+    };
+    (@count $label: lifetime $s: ident [$($m: tt)+]; { $($body: tt)* }) => {
+        $crate::cfor!($label: let m = $($m)+, let mut s = m; s != 0; s = s.wrapping_sub(1) & m; {
+            let $s = s;
+            $($body)*
+        })
+    };
+    (@count $label: lifetime $s: ident [$($m: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::submasks!(@count $label $s [$($m)* $next]; $($rest)*)
+    };
+
+    ($label: lifetime: $s: ident in $($rest: tt)*) => {
+        $crate::submasks!(@count $label $s []; $($rest)*)
+    };
+    ($s: ident in $($rest: tt)*) => {
+        $crate::submasks!(@count 'cfor $s []; $($rest)*)
+    };
+}
+
+/// A loop over the powers of a number, in macro form.
+///
+/// This takes the form `powers!(p in base { body })`, and runs `body`
+/// with `p` bound to `1`, `base`, `base * base`, and so on, like C's
+/// `for (p = 1; ; p *= base) { body }`, except that it stops before
+/// the power that would overflow the type, rather than panicking (in a
+/// debug build) or wrapping around (in a release one). A condition on
+/// `p` can be given too, as in `powers!(p in base; p < end { body })`,
+/// which is safe even if `end` is larger than every power in the type,
+/// unlike `cfor!(let mut p = 1; p < end; p *= base; { body })`. `base`
+/// is evaluated once, before the loop, and must be at least `2`; it
+/// needs a known integer type, so a bare literal needs a suffix.
+///
+/// As with `crepeat!`, `p` belongs to the iteration, `continue` moves
+/// on to the next power, and a label can be given, as in
+/// `powers!('label: p in base { body })`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let mut all = vec![];
+///     powers!(p in 3u8 {
+///         all.push(p);
+///     });
+///     assert_eq!(all, [1, 3, 9, 27, 81, 243]);
+///
+///     let mut count = 0;
+///     powers!(p in 2u16; p <= u16::MAX {
+///         count += 1;
+///     });
+///     assert_eq!(count, 16);
+/// }
+/// ```
+#[macro_export]
+macro_rules! powers {
+    (@cond $label: lifetime $p: ident [$($base: tt)+] [$($cond: tt)*]; { $($body: tt)* }) => {
+        {
+            let base = $($base)+;
+            ::core::assert!(base >= 2, "`powers!` needs a base of at least 2");
+            $crate::cfor_checked!($label: let mut $p = $crate::__private::one(&base);
+                                  $($cond)*;
+                                  $p *= base; {
+                let $p = $p;
+                $($body)*
+            })
+        }
+    };
+    (@cond $label: lifetime $p: ident $base: tt [$($cond: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::powers!(@cond $label $p $base [$($cond)* $next]; $($rest)*)
+    };
+    (@count $label: lifetime $p: ident [$($base: tt)+]; ; $($rest: tt)+) => {
+        $crate::powers!(@cond $label $p [$($base)+] []; $($rest)+)
+    };
+    (@count $label: lifetime $p: ident [$($base: tt)+]; { $($body: tt)* }) => {
+        $crate::powers!(@cond $label $p [$($base)+] []; { $($body)* })
+    };
+    (@count $label: lifetime $p: ident [$($base: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::powers!(@count $label $p [$($base)* $next]; $($rest)*)
+    };
+
+    ($label: lifetime: $p: ident in $($rest: tt)*) => {
+        $crate::powers!(@count $label $p []; $($rest)*)
+    };
+    ($p: ident in $($rest: tt)*) => {
+        $crate::powers!(@count 'cfor $p []; $($rest)*)
+    };
+}
+
+/// Retries a fallible operation, in macro form.
+///
+/// This takes the form `retry!(attempts = n; { body })`, where `body`
+/// evaluates to a `Result`. It is evaluated up to `n` times, stopping
+/// at the first `Ok`, and the whole macro evaluates to that `Ok`, or
+/// the `Err` from the last attempt, if they all failed. This is C's
+/// `for (tries = 0; tries < MAX; tries++) { ... }`, except that at
+/// least one attempt is always made, even if `n` is `0`, so that
+/// there's always a result.
+///
+/// A [`Backoff`] policy can be given to wait between attempts, as in
+/// `retry!(attempts = n, backoff = policy; { body })`. Nothing waits
+/// before the first attempt, or after the last one.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let mut calls = 0;
+///     let mut flaky = || {
+///         calls += 1;
+///         if calls < 3 { Err(calls) } else { Ok("done") }
+///     };
+///
+///     assert_eq!(retry!(attempts = 5; { flaky() }), Ok("done"));
+///     assert_eq!(retry!(attempts = 2; { Err::<(), _>("nope") }), Err("nope"));
+/// }
+/// ```
+#[macro_export]
+macro_rules! retry {
+    (attempts = $n: expr, backoff = $backoff: expr; $body: block) => {
+        {
+            let mut backoff: $crate::Backoff = $backoff;
+            let mut first = true;
+            $crate::retry!(attempts = $n; {
+                if !first { ::std::thread::sleep(backoff.next_delay()) }
+                first = false;
+                $body
+            })
+        }
+    };
+    (attempts = $n: expr; $body: block) => {
+        {
+            let attempts = $n;
+            let mut last = $crate::__private::None;
+            let ok = $crate::cfor_val!{let mut attempt = 0; attempt == 0 || attempt < attempts; attempt += 1; {
+                match $body {
+                    $crate::__private::Ok(value) => break value,
+                    $crate::__private::Err(e) => last = $crate::__private::Some(e),
+                }
+            }};
+            match (ok, last) {
+                ($crate::__private::Some(value), _) => $crate::__private::Ok(value),
+                (_, $crate::__private::Some(e)) => $crate::__private::Err(e),
+                // the body ran at least once, and didn't succeed.
+                (_, $crate::__private::None) => ::core::unreachable!(),
+            }
+        }
+    };
+}
+
+/// Retries a system call that was interrupted by a signal.
+///
+/// This takes the form `retry_eintr!(call)`, where `call` is an
+/// expression returning `-1` on failure and setting `errno`, like the
+/// functions in `libc`. It is evaluated until it either succeeds, or
+/// fails with an error other than `EINTR`, and the whole macro
+/// evaluates to the result of the last evaluation. That is, it is C's
+///
+/// ```c
+/// while ((r = call) == -1 && errno == EINTR);
+/// ```
+///
+/// This requires the `libc` feature.
+///
+/// ```rust
+/// # #[cfg(feature = "libc")] {
+/// use cfor::retry_eintr;
+///
+/// let msg = b"hello\n";
+/// let written = retry_eintr!(unsafe {
+///     libc::write(1, msg.as_ptr() as *const libc::c_void, msg.len())
+/// });
+/// assert_eq!(written, msg.len() as isize);
+/// # }
+/// ```
+#[cfg(feature = "libc")]
+#[macro_export]
+macro_rules! retry_eintr {
+    ($call: expr) => {
+        {
+            let mut result = $call;
+            $crate::cwhile!(result == -1 && $crate::__private::is_eintr(); result = $call; {});
+            result
+        }
+    };
+}
+
+/// Spins until a condition holds, in macro form.
+///
+/// This takes the form `spin_wait!(condition)`, and evaluates
+/// `condition` repeatedly until it is `true`, calling
+/// [`core::hint::spin_loop`] between checks to tell the CPU that
+/// it's busy-waiting. This is suitable for waits that are expected to
+/// be very short, like for another thread to release a lock-free
+/// structure.
+///
+/// With `spin_wait!(condition; spins)`, only the first `spins` waits
+/// spin, and after that it escalates to
+/// [`std::thread::yield_now`], letting other threads run when the
+/// wait turns out to be longer.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use std::sync::Arc;
+///
+/// fn main() {
+///     let ready = Arc::new(AtomicBool::new(false));
+///     let setter = {
+///         let ready = ready.clone();
+///         std::thread::spawn(move || ready.store(true, Ordering::Release))
+///     };
+///
+///     spin_wait!(ready.load(Ordering::Acquire); 100);
+///     setter.join().unwrap();
+/// }
+/// ```
+#[macro_export]
+macro_rules! spin_wait {
+    ($cond: expr) => {
+        $crate::cwhile!(!$cond; (); {
+            ::core::hint::spin_loop();
+        })
+    };
+    ($cond: expr; $spins: expr) => {
+        $crate::cfor!(let spins = $spins, let mut spun = 0; !$cond; (); {
+            if spun < spins {
+                spun += 1;
+                ::core::hint::spin_loop();
+            } else {
+                ::std::thread::yield_now();
+            }
+        })
+    };
+}
+
+/// Polls a condition a limited number of times, in macro form.
+///
+/// This takes the form `busy_wait_for!(condition; n)`, and evaluates
+/// `condition` up to `n` times, calling [`core::hint::spin_loop`]
+/// between checks, until it is `true`. The whole macro evaluates to
+/// `Ok(())` if it became `true`, or `Err(Timeout)` if it never did.
+/// This is the C loop for polling hardware,
+///
+/// ```c
+/// for (i = 0; i < TIMEOUT; i++) if (READY) break;
+/// ```
+///
+/// but with the check of whether it timed out done for you. It only
+/// needs `core`, so it can be used in `no_std` drivers.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// use cfor::Timeout;
+///
+/// fn main() {
+///     let mut status = 0u32;
+///     let mut read_status = || { status += 1; status };
+///
+///     assert_eq!(busy_wait_for!(read_status() & 0b100 != 0; 10), Ok(()));
+///     assert_eq!(busy_wait_for!(read_status() == 0; 10), Err(Timeout));
+/// }
+/// ```
+#[macro_export]
+macro_rules! busy_wait_for {
+    ($cond: expr; $n: expr) => {
+        match $crate::cfor_val!{let n = $n, let mut i = 0; i < n; i += 1; {
+            if $cond { break }
+            ::core::hint::spin_loop();
+        }} {
+            $crate::__private::Some(()) => $crate::__private::Ok(()),
+            $crate::__private::None => $crate::__private::Err($crate::Timeout),
+        }
+    };
+}
+
+/// A `cfor!` that feeds a watchdog periodically, in macro form.
+///
+/// This takes the form `watchdog_cfor!(every n => feed; ...)`, where
+/// `...` is anything accepted by `cfor!`, and evaluates `feed` at the
+/// start of the first iteration, and then of every `n`th one after
+/// it, however the previous ones ended. The count `n` is evaluated
+/// once, and must be at least `1`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let mut feeds = 0;
+///     let mut sum = 0;
+///
+///     watchdog_cfor!(every 100 => feeds += 1; let mut i = 0; i < 1000; i += 1; {
+///         if i % 2 == 0 { continue }
+///         sum += i;
+///     });
+///
+///     assert_eq!(sum, 250_000);
+///     assert_eq!(feeds, 10);
+/// }
+/// ```
+#[macro_export]
+macro_rules! watchdog_cfor {
+    // the body is the block that ends the invocation (other than an
+    // `else` or `finally`), so going forward one token at a time
+    // finds it.
+    (@body $since_feed: ident $every: ident $feed: tt [$($header: tt)*]; { $($body: tt)* } $(else $els: block)? $(finally $fin: block)? $(;)?) => {
+        $crate::cfor!($($header)* {
+            if $since_feed == 0 {
+                $feed;
+            }
+            $since_feed += 1;
+            if $since_feed == $every {
+                $since_feed = 0;
+            }
+            $($body)*
+        } $(else $els)? $(finally $fin)?)
+    };
+    (@body $since_feed: ident $every: ident $feed: tt [$($header: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::watchdog_cfor!(@body $since_feed $every $feed [$($header)* $next]; $($rest)*)
+    };
+
+    (every $n: expr => $feed: expr; $($rest: tt)*) => {
+        {
+            let every = $n;
+            ::core::assert!(every >= 1, "`watchdog_cfor!` needs an `every` of at least 1");
+            let mut since_feed = 0;
+            $crate::watchdog_cfor!(@body since_feed every ($feed) []; $($rest)*)
+        }
+    };
+}
+
+/// Polls a non-blocking operation with a `cfor!` header, in macro
+/// form.
+///
+/// This takes the form `block_with_step!(init; condition; step; {
+/// operation })`, where `operation` evaluates to an `nb::Result`. It
+/// is like `nb::block!(operation)`, in that it evaluates `operation`
+/// until it no longer returns `Err(nb::Error::WouldBlock)`, but it
+/// does so in the body of a `cfor!` with the given header. So, `step`
+/// runs between each poll (to count for a timeout, blink an LED,
+/// etc.), and the polling stops early if `condition` becomes false.
+/// The whole macro evaluates to the final result of `operation`, or
+/// `Err(nb::Error::WouldBlock)` if it stopped early.
+///
+/// This requires the `nb` feature.
+///
+/// ```rust
+/// # #[cfg(feature = "nb")] {
+/// use cfor::block_with_step;
+///
+/// let mut ready_in = 3;
+/// let mut poll = || {
+///     if ready_in == 0 { return Ok('x') }
+///     ready_in -= 1;
+///     Err(nb::Error::WouldBlock::<()>)
+/// };
+///
+/// let result = block_with_step!(let mut tries = 0; tries < 10; tries += 1; { poll() });
+/// assert_eq!(result, Ok('x'));
+///
+/// let result = block_with_step!(let mut tries = 0; tries < 10; tries += 1; {
+///     Err(nb::Error::WouldBlock::<()>)
+/// });
+/// assert_eq!(result, Err::<(), _>(nb::Error::WouldBlock));
+/// # }
+/// ```
+#[cfg(feature = "nb")]
+#[macro_export]
+macro_rules! block_with_step {
+    // as in `watchdog_cfor!`, the operation is in the block that ends
+    // the invocation.
+    (@body [$($header: tt)*]; { $($body: tt)* } $(;)?) => {
+        match $crate::cfor_val!($($header)* {
+            match { $($body)* } {
+                $crate::__private::Err($crate::__private::nb::Error::WouldBlock) => {}
+                result => break result,
+            }
+        }) {
+            $crate::__private::Some(result) => result,
+            $crate::__private::None => $crate::__private::Err($crate::__private::nb::Error::WouldBlock),
+        }
+    };
+    (@body [$($header: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::block_with_step!(@body [$($header)* $next]; $($rest)*)
+    };
+
+    ($($rest: tt)*) => {
+        $crate::block_with_step!(@body []; $($rest)*)
+    };
+}
+
+/// A game loop's fixed-timestep update, in macro form.
+///
+/// This takes the form `fixed_timestep!(accumulator, frame_time, dt;
+/// { body })`, where `accumulator` is a place holding the time not
+/// yet simulated. It adds `frame_time` to `accumulator`, and then runs
+/// `body` (the update) once per whole `dt` it holds, subtracting `dt`
+/// for each, so that the simulation advances in fixed steps however
+/// long the frames are. That is, it is the classic
+///
+/// ```c
+/// accumulator += frame_time;
+/// for (; accumulator >= dt; accumulator -= dt) { update(); }
+/// ```
+///
+/// and, as with `cfor!`, the subtraction happens even if the body
+/// `continue`s, so a skipped update still uses up its time. The times
+/// can be anything supporting `+=`, `>=` and `-=`, like `f64` or
+/// `Duration`, and the whole macro evaluates to the number of updates
+/// that were run.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// use std::time::Duration;
+///
+/// fn main() {
+///     let dt = Duration::from_millis(10);
+///     let mut accumulator = Duration::ZERO;
+///     let mut position = 0;
+///
+///     for frame in [25, 3, 12] {
+///         fixed_timestep!(accumulator, Duration::from_millis(frame), dt; {
+///             position += 1;
+///         });
+///     }
+///
+///     assert_eq!(position, 4);
+///     assert_eq!(accumulator, Duration::from_millis(0));
+/// }
+/// ```
+#[macro_export]
+macro_rules! fixed_timestep {
+    ($accumulator: expr, $frame_time: expr, $dt: expr; $body: block) => {
+        {
+            let dt = $dt;
+            let mut updates = 0usize;
+            $accumulator += $frame_time;
+            $crate::cwhile!($accumulator >= dt; { $accumulator -= dt; updates += 1 }; $body);
+            updates
+        }
+    };
+}
+
+/// A `cfor!` that stops when it runs out of time, in macro form.
+///
+/// This takes the form `budgeted_cfor!(budget = duration; ...)`, where
+/// `...` is anything accepted by `cfor!`, and runs the loop until its
+/// condition becomes false or, checked before the condition at the
+/// start of each iteration, `duration` has passed since the loop
+/// started. The whole macro evaluates to the number of iterations
+/// that started, and so, if the loop's state is outside it, the
+/// remaining work can be resumed later, like in the next frame.
+///
+/// Running out of time ends the loop in the same way as the condition
+/// becoming false, so, for example, it runs an `else` block.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// use std::time::Duration;
+///
+/// fn main() {
+///     let work: Vec<u64> = (0..1000).collect();
+///     let mut next = 0;
+///     let mut total = 0;
+///
+///     while next < work.len() {
+///         let done = budgeted_cfor!(budget = Duration::from_millis(1); ; next < work.len(); next += 1; {
+///             total += work[next];
+///         });
+///         println!("processed {} items this frame", done);
+///     }
+///
+///     assert_eq!(total, 999 * 1000 / 2);
+/// }
+/// ```
+#[macro_export]
+macro_rules! budgeted_cfor {
+    (budget = $budget: expr; $label: lifetime: $($rest: tt)*) => {
+        $crate::budgeted_cfor!(@run $budget; $label; $($rest)*)
+    };
+    (budget = $budget: expr; $($rest: tt)*) => {
+        $crate::budgeted_cfor!(@run $budget; 'cfor; $($rest)*)
+    };
+    (@run $budget: expr; $label: lifetime; $($rest: tt)*) => {
+        {
+            let budget: ::core::time::Duration = $budget;
+            let start = ::std::time::Instant::now();
+            let mut iterations = 0usize;
+            $crate::__cfor!((plain [(start.elapsed() < budget)] [({ iterations += 1; true })]) $label; $($rest)*);
+            iterations
+        }
+    };
+}
+
+/// A `cfor!` that gives up after a while, in macro form.
+///
+/// This takes the form `cfor_timeout!(timeout = duration; ...)`, where
+/// `...` is anything accepted by `cfor!`, and runs the loop until it
+/// ends normally, or, checked before the condition at the start of
+/// each iteration, `duration` has passed since the loop started. The
+/// whole macro evaluates to `Ok(())` in the first case, and
+/// `Err(Timeout)` in the second. Like `budgeted_cfor!`, timing out
+/// otherwise ends the loop like the condition becoming false.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// use std::time::Duration;
+/// use cfor::Timeout;
+///
+/// fn main() {
+///     let mut best = 0u64;
+///     let searched = cfor_timeout!(timeout = Duration::from_secs(10); let mut x = 0; x < 1000; x += 1; {
+///         best = best.max((x * 7919) % 1000);
+///     });
+///     assert_eq!((searched, best), (Ok(()), 999));
+///
+///     let searched = cfor_timeout!(timeout = Duration::from_millis(1);;; (); {});
+///     assert_eq!(searched, Err(Timeout));
+/// }
+/// ```
+#[macro_export]
+macro_rules! cfor_timeout {
+    (timeout = $timeout: expr; $label: lifetime: $($rest: tt)*) => {
+        $crate::cfor_timeout!(@run $timeout; $label; $($rest)*)
+    };
+    (timeout = $timeout: expr; $($rest: tt)*) => {
+        $crate::cfor_timeout!(@run $timeout; 'cfor; $($rest)*)
+    };
+    (@run $timeout: expr; $label: lifetime; $($rest: tt)*) => {
+        {
+            let timeout: ::core::time::Duration = $timeout;
+            let start = ::std::time::Instant::now();
+            let mut timed_out = false;
+            $crate::__cfor!((plain [({ timed_out = start.elapsed() >= timeout; !timed_out })] []) $label; $($rest)*);
+            if timed_out {
+                $crate::__private::Err($crate::Timeout)
+            } else {
+                $crate::__private::Ok(())
+            }
+        }
+    };
+}
+
+/// A loop over a range in fixed-size chunks, with the remainder
+/// done separately, in macro form.
+///
+/// This takes the form `simd_cfor!(lanes = N; i in a..b { body }
+/// remainder { tail })`, where `N` is a constant and `a..b` is a
+/// `Range<usize>`, and runs `body` with `i` taking the values `a`,
+/// `a + N`, `a + 2 * N`, ..., for as long as there are a whole `N`
+/// indices from `i` left in the range. Then, it runs `tail` with `i`
+/// taking each of the indices that are left over, one by one, like
+/// the start and end of a hand-vectorised C loop:
+///
+/// ```c
+/// for (i = a; i + N <= b; i += N) { body }
+/// for (; i < b; i++) { tail }
+/// ```
+///
+/// Each is a `cfor!`, so `continue` moves on to the next chunk or
+/// index, and `break` ends only the loop it is in (so the remainder
+/// still runs after a `break` in the `body`). Changing `i` doesn't
+/// change which indices are visited.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn dot(a: &[f32], b: &[f32]) -> f32 {
+///     let mut lanes = [0.0f32; 4];
+///     let mut total = 0.0;
+///     simd_cfor!(lanes = 4; i in 0..a.len() {
+///         for j in 0..4 {
+///             lanes[j] += a[i + j] * b[i + j];
+///         }
+///     } remainder {
+///         total += a[i] * b[i];
+///     });
+///     total + lanes.iter().sum::<f32>()
+/// }
+///
+/// fn main() {
+///     let a = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0];
+///     assert_eq!(dot(&a, &a), 140.0);
+/// }
+/// ```
+#[macro_export]
+macro_rules! simd_cfor {
+    (@range $lanes: expr; $i: ident [$($range: tt)+]; { $($body: tt)* } remainder { $($tail: tt)* } $(;)?) => {
+        {
+            const LANES: usize = $lanes;
+            const _: () = ::core::assert!(LANES > 0, "`simd_cfor!` needs at least one lane");
+            let range: ::core::ops::Range<usize> = $($range)+;
+            let split = range.start + range.end.saturating_sub(range.start) / LANES * LANES;
+            $crate::cfor!(let mut next = range.start; next < split; next += LANES; {
+                let $i = next;
+                $($body)*
+            });
+            $crate::cfor!(let mut next = split; next < range.end; next += 1; {
+                let $i = next;
+                $($tail)*
+            });
+        }
+    };
+    (@range $lanes: expr; $i: ident [$($range: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::simd_cfor!(@range $lanes; $i [$($range)* $next]; $($rest)*)
+    };
+    (lanes = $lanes: expr; $i: ident in $($rest: tt)*) => {
+        $crate::simd_cfor!(@range $lanes; $i []; $($rest)*)
+    };
+}
+
+/// A `cfor!` whose body can assume an invariant, in macro form.
+///
+/// This takes the form `cfor_assume!(unsafe { assume(invariant) };
+/// ...)`, where `...` is anything accepted by `cfor!`, and runs the
+/// `unsafe` block at the start of each iteration, after the condition.
+/// [`assume`] tells the optimiser that each `invariant` (a `bool`
+/// expression, usually about the loop's variables) is true there, so
+/// that it can remove checks that it implies, like the bounds checks
+/// on `data[i]` from `i < data.len()` when the condition doesn't
+/// already show that. This puts the single `unsafe` block in the
+/// header, rather than the body, but it is still the caller's own, so
+/// lints like `unsafe_code` see it.
+///
+/// An invariant that is false is undefined behaviour, which is why
+/// `assume` is an `unsafe fn`. In debug builds, they are checked
+/// instead, and a false one panics.
+///
+/// This requires the `unchecked` feature.
+///
+/// ```rust
+/// # #[cfg(feature = "unchecked")] {
+/// use cfor::{assume, cfor_assume};
+///
+/// fn sum_every_other(data: &[u64]) -> u64 {
+///     let mut total = 0;
+///     let end = data.len() - data.len() % 2;
+///     // SAFETY: `i < end`, and `end` is even and at most the length.
+///     cfor_assume!(unsafe { assume(i + 1 < data.len()) }; let mut i = 0; i < end; i += 2; {
+///         total += data[i] + data[i + 1];
+///     });
+///     total
+/// }
+///
+/// assert_eq!(sum_every_other(&[1, 2, 3, 4, 5]), 10);
+/// # }
+/// ```
+#[cfg(feature = "unchecked")]
+#[macro_export]
+macro_rules! cfor_assume {
+    ($assume: expr; $label: lifetime: $($rest: tt)*) => {
+        $crate::cfor_assume!(@run $assume; $label; $($rest)*)
+    };
+    ($assume: expr; $($rest: tt)*) => {
+        $crate::cfor_assume!(@run $assume; 'cfor; $($rest)*)
+    };
+    (@run $assume: expr; $label: lifetime; $($rest: tt)*) => {
+        $crate::__cfor!((plain [] [({
+            $assume;
+            true
+        })]) $label; $($rest)*)
+    };
+}
+
+/// A `cfor!` with a software prefetch in each iteration, in macro
+/// form.
+///
+/// This takes the form `prefetch_cfor!(prefetch = p; ...)` or
+/// `prefetch_cfor!(prefetch = p, ahead = n; ...)`, where `...` is
+/// anything accepted by `cfor!`, and `p` is a pointer (or reference)
+/// usually computed from the loop's variables, and, at the start of
+/// each iteration, hints to the CPU that the memory at `p` (or `n`
+/// elements after it) will be read soon, so that it can be fetched
+/// into the cache while the body runs. This helps loops whose
+/// accesses the CPU can't predict itself, like following indices or
+/// pointers.
+///
+/// `p` is only evaluated, never dereferenced, and prefetching an
+/// invalid address is harmless, so it can go past the end of the
+/// data: `ahead` uses `pointer::wrapping_add`. The hint does nothing
+/// on targets without a prefetch instruction that this knows (which
+/// are x86 with SSE, and AArch64).
+///
+/// This requires the `prefetch` feature.
+///
+/// ```rust
+/// # #[cfg(feature = "prefetch")] {
+/// use cfor::prefetch_cfor;
+///
+/// let values: Vec<u64> = (0..10_000).collect();
+/// let order: Vec<usize> = (0..10_000).map(|i| (i * 7919) % 10_000).collect();
+/// let mut total = 0;
+/// prefetch_cfor!(prefetch = values.as_ptr().wrapping_add(order[(i + 8) % order.len()]);
+///                let mut i = 0; i < order.len(); i += 1; {
+///     total += values[order[i]];
+/// });
+/// assert_eq!(total, 9_999 * 10_000 / 2);
+/// # }
+/// ```
+#[cfg(feature = "prefetch")]
+#[macro_export]
+macro_rules! prefetch_cfor {
+    (prefetch = $p: expr, ahead = $ahead: expr; $label: lifetime: $($rest: tt)*) => {
+        $crate::prefetch_cfor!(@run ($p, $ahead) $label; $($rest)*)
+    };
+    (prefetch = $p: expr, ahead = $ahead: expr; $($rest: tt)*) => {
+        $crate::prefetch_cfor!(@run ($p, $ahead) 'cfor; $($rest)*)
+    };
+    (prefetch = $p: expr; $label: lifetime: $($rest: tt)*) => {
+        $crate::prefetch_cfor!(@run ($p, 0) $label; $($rest)*)
+    };
+    (prefetch = $p: expr; $($rest: tt)*) => {
+        $crate::prefetch_cfor!(@run ($p, 0) 'cfor; $($rest)*)
+    };
+    (@run ($p: expr, $ahead: expr) $label: lifetime; $($rest: tt)*) => {
+        $crate::__cfor!((plain [] [({ $crate::__private::prefetch($p, $ahead); true })]) $label; $($rest)*)
+    };
+}
+
+/// A loop over the elements of a slice, in macro form.
+///
+/// This takes the form `slice_cfor!(x in &data[a..b] { body })`, and
+/// runs `body` with `x` bound to a reference to each element of
+/// `data[a..b]` in turn, like C's `for (i = a; i < b; i++) { x =
+/// &data[i]; body }`. Adding `with index i` before the body also binds
+/// `i` to the index of `x` in `data`, and `&mut data[a..b]` gives
+/// mutable references. The range can be any of the forms that
+/// indexing accepts, like `a..`, `..=b`, or none at all, for the whole
+/// of `data`.
+///
+/// The range is checked against `data` once, before the loop, and, as
+/// each element is then in the subslice by construction, the
+/// optimiser can remove the bounds checks from the loop itself, like
+/// an iterator, but keeping `cfor!`'s `continue` and `break`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let data = [3, 1, 4, 1, 5, 9, 2, 6];
+///     let mut best = (0, 0);
+///     slice_cfor!(x in &data[2..6] with index i {
+///         if *x > best.1 { best = (i, *x) }
+///     });
+///     assert_eq!(best, (5, 9));
+///
+///     let mut data = data;
+///     slice_cfor!(x in &mut data[..3] { *x *= 10 });
+///     assert_eq!(data[..4], [30, 10, 40, 1]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! slice_cfor {
+    // the slice is collected up to the body, to find the range.
+    (@slice $x: ident $borrow: tt [$($data: tt)+]; [$($range: tt)*] $(with index $i: ident)? { $($body: tt)* } $(;)?) => {
+        $crate::slice_cfor!(@start $x $borrow [$($data)+] [$($i)?] { $($body)* } []; $($range)*)
+    };
+    (@slice $x: ident $borrow: tt [$($data: tt)+]; $(with index $i: ident)? { $($body: tt)* } $(;)?) => {
+        $crate::slice_cfor!(@start $x $borrow [$($data)+] [$($i)?] { $($body)* } []; ..)
+    };
+    (@slice $x: ident $borrow: tt [$($data: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::slice_cfor!(@slice $x $borrow [$($data)* $next]; $($rest)*)
+    };
+
+    // the start of the range is needed for the index, and is split
+    // off so that it's only evaluated once.
+    (@start $x: ident $borrow: tt $data: tt $i: tt $body: tt [$($start: tt)*]; .. $($end: tt)*) => {
+        $crate::slice_cfor!(@expand $x $borrow $data $i $body [$($start)*] [.. $($end)*])
+    };
+    (@start $x: ident $borrow: tt $data: tt $i: tt $body: tt [$($start: tt)*]; ..= $($end: tt)*) => {
+        $crate::slice_cfor!(@expand $x $borrow $data $i $body [$($start)*] [..= $($end)*])
+    };
+    (@start $x: ident $borrow: tt $data: tt $i: tt $body: tt [$($start: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::slice_cfor!(@start $x $borrow $data $i $body [$($start)* $next]; $($rest)*)
+    };
+    (@start $x: ident $borrow: tt $data: tt $i: tt $body: tt [$($start: tt)*];) => {
+        ::core::compile_error!("expected a range to index the slice with, like `&data[a..b]`")
+    };
+
+    (@expand $x: ident [$($borrow: tt)*] [$($data: tt)+] [$($i: ident)?] $body: tt [$($start: tt)*] [$($range: tt)*]) => {
+        {
+            let start: usize = $crate::slice_cfor!(@or_zero $($start)*);
+            let slice = $($borrow)* $($data)+[start $($range)*];
+            $crate::cfor!(let mut next = 0; next < slice.len(); next += 1; {
+                let $x = $($borrow)* slice[next];
+                $(let $i: usize = start + next;)?
+                $body
+            });
+        }
+    };
+    (@or_zero) => { 0 };
+    (@or_zero $($start: tt)+) => { $($start)+ };
+
+    ($x: ident in &mut $($rest: tt)*) => {
+        $crate::slice_cfor!(@slice $x [&mut] []; $($rest)*)
+    };
+    ($x: ident in & $($rest: tt)*) => {
+        $crate::slice_cfor!(@slice $x [&] []; $($rest)*)
+    };
+}
+
+/// A loop over every so many elements of a slice, in macro form.
+///
+/// This takes the form `stride_cfor!(start = a, step = s; x in slice
+/// { body })`, and runs `body` with `x` bound to a reference to
+/// `slice[a]`, then `slice[a + s]`, `slice[a + 2 * s]`, and so on,
+/// while the index is in `slice`, like `for (i = a; 0 <= i && i < n;
+/// i += s) { x = &slice[i]; body }`. The step is an `isize`, and so
+/// can be negative, to go backwards, unlike `Iterator::step_by`. With
+/// `x in &mut slice`, the elements are mutable, and adding `with index
+/// i` before the body also binds `i` to the index of `x` in `slice`.
+///
+/// `continue` moves on to the next element, and `break` ends the loop.
+/// A step of zero panics.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let data = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+///     let mut seen = vec![];
+///     stride_cfor!(start = 1, step = 4; x in &data { seen.push(*x) });
+///     assert_eq!(seen, [1, 5, 9]);
+///
+///     let mut seen = vec![];
+///     stride_cfor!(start = 8, step = -3; x in &data with index i {
+///         seen.push((i, *x));
+///     });
+///     assert_eq!(seen, [(8, 8), (5, 5), (2, 2)]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! stride_cfor {
+    // as usual, the slice is collected up to the body.
+    (@slice $start: tt $step: tt $x: ident [$($slice: tt)+]; $(with index $index: ident)? { $($body: tt)* } $(;)?) => {
+        $crate::stride_cfor!(@expand $start $step $x [$($slice)+] [$($index)?] { $($body)* })
+    };
+    (@slice $start: tt $step: tt $x: ident [$($slice: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::stride_cfor!(@slice $start $step $x [$($slice)* $next]; $($rest)*)
+    };
+
+    (@expand $start: tt $step: tt $x: ident [& mut $($slice: tt)+] $index: tt $body: tt) => {
+        $crate::stride_cfor!(@run $start $step $x [&mut [_]] (&mut $($slice)+) [&mut] $index $body)
+    };
+    (@expand $start: tt $step: tt $x: ident [$($slice: tt)+] $index: tt $body: tt) => {
+        $crate::stride_cfor!(@run $start $step $x [&[_]] ($($slice)+) [&] $index $body)
+    };
+    (@run $start: tt $step: tt $x: ident [$($ty: tt)*] $slice: tt [$($borrow: tt)*] [$($index: ident)?] $body: tt) => {
+        {
+            let start: usize = $start;
+            let step: isize = $step;
+            ::core::assert!(step != 0, "`stride_cfor!` needs a non-zero step");
+            let slice: $($ty)* = $slice;
+            let len = slice.len();
+            // (the checked arithmetic stops the loop at either end,
+            // rather than overflowing.)
+            $crate::cfor!(let mut next = $crate::__private::Some(start);
+                          let $crate::__private::Some(current) = next && current < len;
+                          next = if step < 0 {
+                              current.checked_sub(step.unsigned_abs())
+                          } else {
+                              current.checked_add(step as usize)
+                          }; {
+                let $x = $($borrow)* slice[current];
+                $(let $index: usize = current;)?
+                $body
+            });
+        }
+    };
+
+    (start = $start: expr, step = $step: expr; $x: ident in $($rest: tt)*) => {
+        $crate::stride_cfor!(@slice ($start) ($step) $x []; $($rest)*)
+    };
+}
+
+/// A loop over the frames of an interleaved buffer, in macro form.
+///
+/// This takes the form `interleaved_cfor!((a, b, ...) in &mut buffer
+/// { body })`, where `buffer` holds samples for each of the channels
+/// `a`, `b`, ... in turn, like stereo audio `[l0, r0, l1, r1, ...]`,
+/// and runs `body` once for each frame, with each channel's name
+/// bound to a mutable reference to its sample in that frame. It's
+/// like C's `for (i = 0; i + 2 <= n; i += 2) { l = &buf[i]; r =
+/// &buf[i + 1]; body }`, with the number of channels taken from the
+/// number of names. With `&buffer`, the references are immutable, and
+/// adding `with index i` before the body also binds `i` to the number
+/// of the frame (not of the sample). A name can also be `_`, for a
+/// channel that isn't needed.
+///
+/// `continue` moves on to the next frame, and `break` ends the loop.
+/// Any samples at the end that don't make up a whole frame are
+/// skipped, like `slice::chunks_exact`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let mut samples = [0.5, 0.25, 1.0, -1.0, 0.0, 0.5];
+///     // swap the channels, and halve the left.
+///     interleaved_cfor!((l, r) in &mut samples {
+///         ::std::mem::swap(l, r);
+///         *l /= 2.0;
+///     });
+///     assert_eq!(samples, [0.125, 0.5, -0.5, 1.0, 0.25, 0.0]);
+///
+///     let mut peak = (0, 0.0);
+///     interleaved_cfor!((_, r) in &samples with index frame {
+///         if *r > peak.1 { peak = (frame, *r) }
+///     });
+///     assert_eq!(peak, (1, 1.0));
+/// }
+/// ```
+#[macro_export]
+macro_rules! interleaved_cfor {
+    // as usual, the buffer is collected up to the body.
+    (@slice $channels: tt [$($slice: tt)+]; $(with index $index: ident)? { $($body: tt)* } $(;)?) => {
+        $crate::interleaved_cfor!(@expand $channels [$($slice)+] [$($index)?] { $($body)* })
+    };
+    (@slice $channels: tt [$($slice: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::interleaved_cfor!(@slice $channels [$($slice)* $next]; $($rest)*)
+    };
+
+    (@expand $channels: tt [& mut $($slice: tt)+] $index: tt $body: tt) => {
+        $crate::interleaved_cfor!(@run $channels [&mut [_]] (&mut $($slice)+) [&mut] $index $body)
+    };
+    (@expand $channels: tt [$($slice: tt)+] $index: tt $body: tt) => {
+        $crate::interleaved_cfor!(@run $channels [&[_]] ($($slice)+) [&] $index $body)
+    };
+    (@run [$($channel: tt)+] [$($ty: tt)*] $slice: tt [$($borrow: tt)*] [$($index: ident)?] $body: tt) => {
+        {
+            const CHANNELS: usize = 0 $(+ $crate::interleaved_cfor!(@one $channel))+;
+            let slice: $($ty)* = $slice;
+            let end = slice.len() / CHANNELS * CHANNELS;
+            $crate::cfor!(let mut start = 0; start < end; start += CHANNELS; {
+                let [$($channel),+] = $($borrow)* slice[start..start + CHANNELS] else {
+                    ::core::unreachable!()
+                };
+                $(let $index: usize = start / CHANNELS;)?
+                $body
+            });
+        }
+    };
+    (@one $channel: tt) => { 1 };
+
+    (($($channel: pat),+ $(,)?) in $($rest: tt)*) => {
+        $crate::interleaved_cfor!(@slice [$($channel)+] []; $($rest)*)
+    };
+}
+
+/// A loop over windows of a slice, in macro form.
+///
+/// This takes the form `window_cfor!(size = n, hop = h; window in
+/// slice { body })`, and runs `body` with `window` bound to
+/// `&slice[0..n]`, then `&slice[h..h + n]`, `&slice[2 * h..2 * h +
+/// n]`, and so on, for as long as a whole window fits, like C's `for
+/// (i = 0; i + n <= len; i += h) { body }`. With a hop of `1`, this is
+/// `slice::windows`, and with a hop of `n`, it's
+/// `slice::chunks_exact`, but any hop works, like the overlapping
+/// frames of a short-time Fourier transform. With `window in &mut
+/// slice`, the windows are mutable, and adding `with index i` before
+/// the body also binds `i` to the index in `slice` of the start of the
+/// window.
+///
+/// `continue` moves on to the next window, and `break` ends the loop.
+/// A size or hop of zero panics.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let text = b"abracadabra";
+///     let mut found = vec![];
+///     window_cfor!(size = 4, hop = 1; w in &text[..] with index i {
+///         if w == b"abra" { found.push(i) }
+///     });
+///     assert_eq!(found, [0, 7]);
+///
+///     let samples = [1, 2, 3, 4, 5, 6, 7];
+///     let mut sums = vec![];
+///     window_cfor!(size = 3, hop = 2; frame in &samples {
+///         sums.push(frame.iter().sum::<i32>());
+///     });
+///     assert_eq!(sums, [6, 12, 18]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! window_cfor {
+    // as usual, the slice is collected up to the body.
+    (@slice $size: tt $hop: tt $window: ident [$($slice: tt)+]; $(with index $index: ident)? { $($body: tt)* } $(;)?) => {
+        $crate::window_cfor!(@expand $size $hop $window [$($slice)+] [$($index)?] { $($body)* })
+    };
+    (@slice $size: tt $hop: tt $window: ident [$($slice: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::window_cfor!(@slice $size $hop $window [$($slice)* $next]; $($rest)*)
+    };
+
+    (@expand $size: tt $hop: tt $window: ident [& mut $($slice: tt)+] $index: tt $body: tt) => {
+        $crate::window_cfor!(@run $size $hop $window [&mut [_]] (&mut $($slice)+) [&mut] $index $body)
+    };
+    (@expand $size: tt $hop: tt $window: ident [$($slice: tt)+] $index: tt $body: tt) => {
+        $crate::window_cfor!(@run $size $hop $window [&[_]] ($($slice)+) [&] $index $body)
+    };
+    (@run $size: tt $hop: tt $window: ident [$($ty: tt)*] $slice: tt [$($borrow: tt)*] [$($index: ident)?] $body: tt) => {
+        {
+            let size: usize = $size;
+            let hop: usize = $hop;
+            ::core::assert!(size > 0, "`window_cfor!` needs a window size of at least 1");
+            ::core::assert!(hop > 0, "`window_cfor!` needs a hop of at least 1");
+            let slice: $($ty)* = $slice;
+            // (written as a subtraction, so that a huge hop can't
+            // overflow `start`.)
+            $crate::cfor!(let mut start = 0; size <= slice.len() - start;
+                          start = if slice.len() - start >= hop { start + hop } else { slice.len() }; {
+                let $window = $($borrow)* slice[start..start + size];
+                $(let $index: usize = start;)?
+                $body
+            });
+        }
+    };
+
+    (size = $size: expr, hop = $hop: expr; $window: ident in $($rest: tt)*) => {
+        $crate::window_cfor!(@slice ($size) ($hop) $window []; $($rest)*)
+    };
+}
+
+/// A loop over the rows and columns of a matrix stored in a flat
+/// slice, in macro form.
+///
+/// This takes the form `matrix_cfor!(width = w; (row, col, x) in
+/// slice { body })`, and runs `body` for each element of `slice` in
+/// order, with `x` bound to a reference to it, and `row` and `col`
+/// to its position in a row-major matrix `w` elements wide, like C's
+/// `for (r = 0; r < h; r++) { for (c = 0; c < w; c++) { x =
+/// &slice[r * w + c]; body } }`. The flat index is kept up to date as it goes,
+/// rather than being recomputed from `row` and `col`, and adding
+/// `with index i` before the body binds it to `i`. With `in &mut
+/// slice`, `x` is a mutable reference.
+///
+/// It's a single loop, so `continue` moves on to the next element,
+/// and `break` ends the whole loop. If the length of `slice` isn't a
+/// multiple of `w`, the last row is cut short. A width of zero
+/// panics.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let mut image = vec![0u8; 12];
+///     let width = 4;
+///
+///     // a diagonal gradient.
+///     matrix_cfor!(width = width; (y, x, pixel) in &mut image {
+///         *pixel = (10 * y + x) as u8;
+///     });
+///     assert_eq!(image[..8], [0, 1, 2, 3, 10, 11, 12, 13]);
+///
+///     let mut brightest = (0, 0);
+///     matrix_cfor!(width = width; (_, _, &pixel) in &image with index i {
+///         if pixel > brightest.1 { brightest = (i, pixel) }
+///     });
+///     assert_eq!(brightest, (11, 23));
+/// }
+/// ```
+#[macro_export]
+macro_rules! matrix_cfor {
+    (@slice $width: tt $bind: tt [$($slice: tt)+]; $(with index $index: ident)? { $($body: tt)* } $(;)?) => {
+        $crate::matrix_cfor!(@expand $width $bind [$($slice)+] [$($index)?] { $($body)* })
+    };
+    (@slice $width: tt $bind: tt [$($slice: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::matrix_cfor!(@slice $width $bind [$($slice)* $next]; $($rest)*)
+    };
+
+    (@expand $width: tt $bind: tt [& mut $($slice: tt)+] $index: tt $body: tt) => {
+        $crate::matrix_cfor!(@run $width $bind [&mut [_]] (&mut $($slice)+) [&mut] $index $body)
+    };
+    (@expand $width: tt $bind: tt [$($slice: tt)+] $index: tt $body: tt) => {
+        $crate::matrix_cfor!(@run $width $bind [&[_]] ($($slice)+) [&] $index $body)
+    };
+    (@run $width: tt ($row: pat, $col: pat, $x: pat) [$($ty: tt)*] $slice: tt [$($borrow: tt)*] [$($index: ident)?] $body: tt) => {
+        {
+            let width: usize = $width;
+            ::core::assert!(width > 0, "`matrix_cfor!` needs a width of at least 1");
+            let slice: $($ty)* = $slice;
+            $crate::cfor!(let mut index = 0, let mut row = 0, let mut col = 0; index < slice.len(); {
+                index += 1;
+                col += 1;
+                if col == width {
+                    col = 0;
+                    row += 1;
+                }
+            }; {
+                let $row: usize = row;
+                let $col: usize = col;
+                $(let $index: usize = index;)?
+                let $x = $($borrow)* slice[index];
+                $body
+            });
+        }
+    };
+
+    (width = $width: expr; ($row: pat, $col: pat, $x: pat) in $($rest: tt)*) => {
+        $crate::matrix_cfor!(@slice ($width) ($row, $col, $x) []; $($rest)*)
+    };
+}
+
+/// A loop over a slice in fixed-size chunks, with the remainder done
+/// separately, in macro form.
+///
+/// This takes the form `chunked_cfor!(size = n; chunk in slice { body }
+/// remainder { tail })`, and runs `body` with `chunk` bound to each
+/// whole `n`-element chunk of `slice` in turn, and then `tail` with
+/// `chunk` bound to the elements left over, if there are any, like
+/// the main loop and epilogue of a `memcpy` or a DSP kernel. With
+/// `chunk in &mut slice`, the chunks are mutable. Adding `with index
+/// i` before the body also binds `i` to the index in `slice` of the
+/// start of the chunk, in both bodies.
+///
+/// Both bodies run as `cfor!` bodies (the `tail` as if it were a loop
+/// with one iteration), so `continue` moves on to the next chunk, and
+/// `break` ends the loop it is in, like `simd_cfor!`, which is the
+/// same for a range of indices.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn checksum(data: &[u8]) -> u32 {
+///     let mut sum = 0u32;
+///     chunked_cfor!(size = 4; chunk in data {
+///         sum = sum.rotate_left(5) ^ u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+///     } remainder {
+///         for &byte in chunk {
+///             sum = sum.rotate_left(5) ^ byte as u32;
+///         }
+///     });
+///     sum
+/// }
+///
+/// fn main() {
+///     let mut data = *b"hello, world";
+///     chunked_cfor!(size = 5; chunk in &mut data[..] with index i {
+///         chunk[0] = b'0' + i as u8;
+///     } remainder {
+///         chunk.reverse();
+///     });
+///     assert_eq!(&data, b"0ello5 wordl");
+///     assert_ne!(checksum(&data), checksum(b"hello, world"));
+/// }
+/// ```
+#[macro_export]
+macro_rules! chunked_cfor {
+    // as usual, the slice is collected up to the bodies.
+    (@slice $size: tt $chunk: ident [$($slice: tt)+] [$($index: ident)?]; { $($body: tt)* } remainder { $($tail: tt)* } $(;)?) => {
+        $crate::chunked_cfor!(@expand $size $chunk [$($slice)+] [$($index)?] { $($body)* } { $($tail)* })
+    };
+    (@slice $size: tt $chunk: ident [$($slice: tt)+] []; with index $index: ident { $($body: tt)* } remainder { $($tail: tt)* } $(;)?) => {
+        $crate::chunked_cfor!(@expand $size $chunk [$($slice)+] [$index] { $($body)* } { $($tail)* })
+    };
+    (@slice $size: tt $chunk: ident [$($slice: tt)*] []; $next: tt $($rest: tt)*) => {
+        $crate::chunked_cfor!(@slice $size $chunk [$($slice)* $next] []; $($rest)*)
+    };
+
+    (@expand $size: tt $chunk: ident [& mut $($slice: tt)+] $index: tt $body: tt $tail: tt) => {
+        $crate::chunked_cfor!(@run $size $chunk [&mut [_]] (&mut $($slice)+) [&mut] $index $body $tail)
+    };
+    (@expand $size: tt $chunk: ident [$($slice: tt)+] $index: tt $body: tt $tail: tt) => {
+        $crate::chunked_cfor!(@run $size $chunk [&[_]] ($($slice)+) [&] $index $body $tail)
+    };
+    (@run $size: tt $chunk: ident [$($ty: tt)*] $slice: tt [$($borrow: tt)*] [$($index: ident)?] $body: tt $tail: tt) => {
+        {
+            let size: usize = $size;
+            ::core::assert!(size > 0, "`chunked_cfor!` needs a chunk size of at least 1");
+            let slice: $($ty)* = $slice;
+            let split = slice.len() / size * size;
+            $crate::cfor!(let mut start = 0; start < split; start += size; {
+                let $chunk = $($borrow)* slice[start..start + size];
+                $(let $index: usize = start;)?
+                $body
+            });
+            $crate::cfor!(let mut once = split < slice.len(); once; once = false; {
+                let $chunk = $($borrow)* slice[split..];
+                $(let $index: usize = split;)?
+                $tail
+            });
+        }
+    };
+
+    (size = $size: expr; $chunk: ident in $($rest: tt)*) => {
+        $crate::chunked_cfor!(@slice ($size) $chunk [] []; $($rest)*)
+    };
+}
+
+/// A loop over a range with its body unrolled, in macro form.
+///
+/// This takes the form `unroll!(N; i in a..b { body })`, where `N` is
+/// a literal `1` to `8`, or `16`, and `a..b` is a `Range<usize>`, and
+/// runs `body` for each `i` in the range, in order, like `for (i = a;
+/// i < b; i++) { body }`. However, the loop steps by `N` at a time,
+/// with `N` copies of `body` in each iteration (for `i`, `i + 1`, ...,
+/// `i + N - 1`), followed by a loop for the remainder that doesn't
+/// fit. This gives deterministic control of unrolling, rather than
+/// leaving it to the optimiser.
+///
+/// `continue` moves on to the next index, and `break` ends the whole
+/// loop, as if it weren't unrolled. Changing `i` doesn't change which
+/// indices are visited.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let data: Vec<u32> = (0..10).collect();
+///     let mut sum = 0;
+///     // runs `sum += data[i]` for i = 0, 1, 2, 3, then 4, 5, 6, 7, then
+///     // 8 and 9 in the remainder.
+///     unroll!(4; i in 0..data.len() {
+///         sum += data[i];
+///     });
+///     assert_eq!(sum, 45);
+/// }
+/// ```
+#[macro_export]
+macro_rules! unroll {
+    (@range $offsets: tt $n: tt; $i: ident [$($range: tt)+]; { $($body: tt)* } $(;)?) => {
+        $crate::unroll!(@expand $offsets $n; $i [$($range)+]; { $($body)* })
+    };
+    (@range $offsets: tt $n: tt; $i: ident [$($range: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::unroll!(@range $offsets $n; $i [$($range)* $next]; $($rest)*)
+    };
+    (@expand [$($k: tt)+] $n: tt; $i: ident [$($range: tt)+]; $body: tt) => {
+        {
+            let range: ::core::ops::Range<usize> = $($range)+;
+            let split = range.start + range.end.saturating_sub(range.start) / $n * $n;
+            $crate::cfor!('unroll: let mut base = range.start; base < split; base += $n; {
+                $(
+                    // each copy is its own one-iteration loop, run
+                    // like the body of a `cfor!`, so that a
+                    // `continue` moves on to the next copy, and a
+                    // `break` (which skips setting `completed`) ends
+                    // the whole loop.
+                    let mut completed = false;
+                    let mut first = true;
+                    loop {
+                        if !first {
+                            completed = true;
+                            break;
+                        }
+                        first = false;
+                        let $i = base + $k;
+                        $body
+                    }
+                    if !completed { break 'unroll }
+                )+
+            } else {
+                $crate::cfor!(let mut next = split; next < range.end; next += 1; {
+                    let $i = next;
+                    $body
+                });
+            });
+        }
+    };
+    (1; $i: ident in $($rest: tt)*) => {
+        $crate::unroll!(@range [0] 1; $i []; $($rest)*)
+    };
+    (2; $i: ident in $($rest: tt)*) => {
+        $crate::unroll!(@range [0 1] 2; $i []; $($rest)*)
+    };
+    (3; $i: ident in $($rest: tt)*) => {
+        $crate::unroll!(@range [0 1 2] 3; $i []; $($rest)*)
+    };
+    (4; $i: ident in $($rest: tt)*) => {
+        $crate::unroll!(@range [0 1 2 3] 4; $i []; $($rest)*)
+    };
+    (5; $i: ident in $($rest: tt)*) => {
+        $crate::unroll!(@range [0 1 2 3 4] 5; $i []; $($rest)*)
+    };
+    (6; $i: ident in $($rest: tt)*) => {
+        $crate::unroll!(@range [0 1 2 3 4 5] 6; $i []; $($rest)*)
+    };
+    (7; $i: ident in $($rest: tt)*) => {
+        $crate::unroll!(@range [0 1 2 3 4 5 6] 7; $i []; $($rest)*)
+    };
+    (8; $i: ident in $($rest: tt)*) => {
+        $crate::unroll!(@range [0 1 2 3 4 5 6 7] 8; $i []; $($rest)*)
+    };
+    (16; $i: ident in $($rest: tt)*) => {
+        $crate::unroll!(@range [0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15] 16; $i []; $($rest)*)
+    };
+}
+
+/// Nested C-style `for` loops, in macro form.
+///
+/// This takes the form `nested_cfor!((header) (header) ... { body
+/// })`, and expands to a `cfor!` for each header, nested inside each
+/// other in order, with `body` in the innermost. A header is either a
+/// range, `(i: a..b)`, which is `cfor!(i in a..b ...)` (with `..=`,
+/// `downto` and `step` too), or a full C-style header, `(init; cond;
+/// step)`. Later headers can use the variables of earlier ones. Each
+/// level uses up some of the compiler's limit on macro recursion, so a
+/// deep nest may need a higher `#![recursion_limit]`.
+///
+/// `break` and `continue` act on the innermost loop, and `break_all!()`
+/// exits every level at once, even from a loop nested in the body. A
+/// label can also be given to the whole nest, as in
+/// `nested_cfor!('label: (...) (...) { ... })`, which is the outermost
+/// loop's, so that `break 'label` does the same as `break_all!()`, and
+/// `continue 'label` moves on to the outermost loop's next iteration.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let (h, w) = (3, 4);
+///     let grid: Vec<usize> = (0..h * w).collect();
+///
+///     let mut total = 0;
+///     nested_cfor!((i: 0..h) (j: 0..w) {
+///         total += grid[i * w + j];
+///     });
+///     assert_eq!(total, 66);
+///
+///     // the first cell over 6, skipping odd columns.
+///     let mut found = None;
+///     nested_cfor!((i: 0..h) (let mut j = 0; j < w; j += 2) {
+///         if grid[i * w + j] > 6 {
+///             found = Some((i, j));
+///             break_all!();
+///         }
+///     });
+///     assert_eq!(found, Some((2, 0)));
+///
+///     // the first row with a multiple of 5, with a label.
+///     let mut row = None;
+///     nested_cfor!('rows: (i: 0..h) (j: 0..w) {
+///         if grid[i * w + j] % 5 == 0 && i > 0 {
+///             row = Some(i);
+///             break 'rows;
+///         }
+///     });
+///     assert_eq!(row, Some(1));
+/// }
+/// ```
+#[macro_export]
+macro_rules! nested_cfor {
+    ([$($label: tt)*] ($i: ident : $($range: tt)+) { $($body: tt)* }) => {
+        $crate::cfor!($($label)* $i in $($range)+ { $($body)* })
+    };
+    ([$($label: tt)*] ($($header: tt)*) { $($body: tt)* }) => {
+        $crate::cfor!($($label)* ($($header)*) { $($body)* })
+    };
+    ([$($label: tt)*] ($i: ident : $($range: tt)+) $($rest: tt)+) => {
+        $crate::cfor!($($label)* $i in $($range)+ { $crate::nested_cfor!([] $($rest)+) })
+    };
+    ([$($label: tt)*] ($($header: tt)*) $($rest: tt)+) => {
+        $crate::cfor!($($label)* ($($header)*) { $crate::nested_cfor!([] $($rest)+) })
+    };
+    ($label: lifetime: $($rest: tt)+) => {
+        $crate::__cfor_break_all!($crate::nested_cfor!([$label:] $($rest)+))
+    };
+    ($($rest: tt)+) => {
+        $crate::__cfor_break_all!($crate::nested_cfor!([] $($rest)+))
+    };
+}
+
+/// Defines `break_all!()` for the loops in `$loops`, which breaks out
+/// of all of them.
+///
+/// The `break_all` macro is local to the expansion, and so shadows any
+/// from an enclosing nest, with the same hygiene as the label it
+/// breaks to.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfor_break_all {
+    ($($loops: tt)*) => {
+        {
+            #[allow(unused_labels)]
+            'all: {
+                #[allow(unused_macros)]
+                macro_rules! break_all {
+                    () => { break 'all };
+                }
+                $($loops)*
+            }
+        }
+    };
+}
+
+/// A loop over the pairs of distinct indices in a range, in macro
+/// form.
+///
+/// This takes the form `triangular_cfor!(i, j in a..b { body })`, and
+/// runs `body` for each `i < j` in the range, like `for (i = a; i <
+/// b; i++) { for (j = i + 1; j < b; j++) { body } }`, which visits
+/// every unordered pair once, as in pairwise comparisons. `b` is
+/// evaluated once, before the loop.
+///
+/// As in nested loops, `break` and `continue` act on the inner loop,
+/// over `j`, and a label can be given to the outer loop, over `i`, as
+/// in `triangular_cfor!('label: i, j in a..b { ... })`. `break_all!()`
+/// exits both loops at once, as in `nested_cfor!`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let points = [(0, 0), (3, 4), (1, 1), (6, 8)];
+///
+///     let mut closest = None;
+///     triangular_cfor!(i, j in 0..points.len() {
+///         let (dx, dy) = (points[i].0 - points[j].0, points[i].1 - points[j].1);
+///         let d = dx * dx + dy * dy;
+///         if closest.map_or(true, |(best, _)| d < best) {
+///             closest = Some((d, (i, j)));
+///         }
+///     });
+///     assert_eq!(closest, Some((2, (0, 2))));
+/// }
+/// ```
+#[macro_export]
+macro_rules! triangular_cfor {
+    (@range $label: tt $i: ident $j: ident [$($range: tt)*]; { $($body: tt)* }) => {
+        $crate::triangular_cfor!(@split $label $i $j { $($body)* } [] [$($range)*])
+    };
+    (@range $label: tt $i: ident $j: ident [$($range: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::triangular_cfor!(@range $label $i $j [$($range)* $next]; $($rest)*)
+    };
+    (@split [$($label: tt)*] $i: ident $j: ident $body: tt [$($start: tt)+] [.. $($end: tt)+]) => {
+        {
+            let end = $($end)+;
+            $crate::cfor!($($label)* $i in $($start)+..end {
+                $crate::cfor!($j in $i + 1..end $body)
+            })
+        }
+    };
+    (@split $label: tt $i: ident $j: ident $body: tt [$($start: tt)*] [$next: tt $($range: tt)*]) => {
+        $crate::triangular_cfor!(@split $label $i $j $body [$($start)* $next] [$($range)*])
+    };
+    (@split $label: tt $i: ident $j: ident $body: tt $start: tt []) => {
+        ::core::compile_error!("expected a half-open range, like `a..b`")
+    };
+    ($label: lifetime: $i: ident, $j: ident in $($rest: tt)+) => {
+        $crate::__cfor_break_all!($crate::triangular_cfor!(@range [$label:] $i $j []; $($rest)+))
+    };
+    ($i: ident, $j: ident in $($rest: tt)+) => {
+        $crate::__cfor_break_all!($crate::triangular_cfor!(@range [] $i $j []; $($rest)+))
+    };
+}
+
+/// Nested loops over ranges, split into tiles, in macro form.
+///
+/// This takes the form `tile_cfor!(tile = t; i in a..b, j in c..d {
+/// body })`, with two or three `Range<usize>`s, and runs `body` once
+/// for each `(i, j)` (or `(i, j, k)`), like nested loops over the
+/// ranges, but blocked for the cache: the index space is split into
+/// tiles of `t` by `t` (by `t`) indices, which are visited one at a
+/// time, with the indices inside each visited in order, so that the
+/// body works on a small region of memory at once. The tiles at the
+/// ends of the ranges are cut short, rather than going past them.
+///
+/// `continue` moves on to the next indices, and `break` ends the whole
+/// loop. Changing the indices doesn't change which are visited.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn transpose(src: &[f64], dst: &mut [f64], rows: usize, cols: usize) {
+///     tile_cfor!(tile = 16; r in 0..rows, c in 0..cols {
+///         dst[c * rows + r] = src[r * cols + c];
+///     });
+/// }
+///
+/// fn main() {
+///     let src: Vec<f64> = (0..6).map(|x| x as f64).collect();
+///     let mut dst = vec![0.0; 6];
+///     transpose(&src, &mut dst, 2, 3);
+///     assert_eq!(dst, [0.0, 3.0, 1.0, 4.0, 2.0, 5.0]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! tile_cfor {
+    // as in `crepeat!`, the ranges are collected up to the final
+    // block, since an `expr` can't be followed by one.
+    (@header $tile: tt [$($header: tt)*]; { $($body: tt)* } $(;)?) => {
+        $crate::tile_cfor!(@dims $tile [$($header)*] { $($body)* })
+    };
+    (@header $tile: tt [$($header: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::tile_cfor!(@header $tile [$($header)* $next]; $($rest)*)
+    };
+
+    // only the loop containing the body needs to be a `cfor!`, since
+    // the others just count (and nesting lots of `cfor!`s hits the
+    // recursion limit).
+    (@dims $tile: tt [$i: ident in $ri: expr, $j: ident in $rj: expr $(,)?] $body: tt) => {
+        {
+            let tile: usize = $tile;
+            ::core::assert!(tile > 0, "`tile_cfor!` needs a tile size of at least 1");
+            let ri: ::core::ops::Range<usize> = $ri;
+            let rj: ::core::ops::Range<usize> = $rj;
+            let mut ti = ri.start;
+            'tile: while ti < ri.end {
+                let ei = $crate::tile_cfor!(@end ti tile ri);
+                let mut tj = rj.start;
+                while tj < rj.end {
+                    let ej = $crate::tile_cfor!(@end tj tile rj);
+                    let mut pi = ti;
+                    while pi < ei {
+                        $crate::tile_cfor!(@innermost 'tile; pj tj ej; { let $i = pi; let $j = pj; } $body);
+                        pi += 1;
+                    }
+                    tj = ej;
+                }
+                ti = ei;
+            }
+        }
+    };
+    (@dims $tile: tt [$i: ident in $ri: expr, $j: ident in $rj: expr, $k: ident in $rk: expr $(,)?] $body: tt) => {
+        {
+            let tile: usize = $tile;
+            ::core::assert!(tile > 0, "`tile_cfor!` needs a tile size of at least 1");
+            let ri: ::core::ops::Range<usize> = $ri;
+            let rj: ::core::ops::Range<usize> = $rj;
+            let rk: ::core::ops::Range<usize> = $rk;
+            let mut ti = ri.start;
+            'tile: while ti < ri.end {
+                let ei = $crate::tile_cfor!(@end ti tile ri);
+                let mut tj = rj.start;
+                while tj < rj.end {
+                    let ej = $crate::tile_cfor!(@end tj tile rj);
+                    let mut tk = rk.start;
+                    while tk < rk.end {
+                        let ek = $crate::tile_cfor!(@end tk tile rk);
+                        let mut pi = ti;
+                        while pi < ei {
+                            let mut pj = tj;
+                            while pj < ej {
+                                $crate::tile_cfor!(@innermost 'tile; pk tk ek; { let $i = pi; let $j = pj; let $k = pk; } $body);
+                                pj += 1;
+                            }
+                            pi += 1;
+                        }
+                        tk = ek;
+                    }
+                    tj = ej;
+                }
+                ti = ei;
+            }
+        }
+    };
+
+    // the end of the tile starting at `$start`.
+    (@end $start: ident $tile: ident $range: ident) => {
+        if $range.end - $start < $tile { $range.end } else { $start + $tile }
+    };
+    // the loop containing the body, which is the only one that a
+    // `break` in the body can stop directly, so it passes the `break`
+    // on to the outermost loop.
+    (@innermost $outer: lifetime; $p: ident $start: ident $end: ident; { $($bind: tt)* } $body: tt) => {
+        let mut broke = true;
+        $crate::cfor!(let mut $p = $start; $p < $end; $p += 1; {
+            $($bind)*
+            $body
+        } else {
+            broke = false;
+        });
+        if broke { break $outer }
+    };
+
+    (tile = $tile: expr; $($rest: tt)*) => {
+        $crate::tile_cfor!(@header ($tile) []; $($rest)*)
+    };
+}
+
+/// A body repeated for each number in a range, at compile time, in
+/// macro form.
+///
+/// This takes the form `static_for!(i in a..b { body })` (or `a..=b`),
+/// where `a` and `b` are integer literals up to 64, and expands to a
+/// copy of `body` for each `i` in the range, in order, with `i` a
+/// `const` of type `usize`. There is no loop at all, so `i` can be
+/// used where a constant is needed, like an array length or a const
+/// generic argument, and `break` and `continue` refer to any loop
+/// around the `static_for!`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn zeroes<const N: usize>() -> [u8; N] {
+///     [0; N]
+/// }
+///
+/// fn main() {
+///     let mut lengths = vec![];
+///     static_for!(i in 0..4 {
+///         let array: [u8; i] = zeroes::<i>();
+///         lengths.push(array.len());
+///     });
+///     assert_eq!(lengths, [0, 1, 2, 3]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! static_for {
+    (@expand $i: ident $body: tt [$($k: tt)*]) => {
+        $(
+            {
+                #[allow(non_upper_case_globals)]
+                const $i: usize = $k;
+                $body
+            }
+        )*
+    };
+    ($i: ident in $a: tt .. $b: tt $body: block $(;)?) => {
+        $crate::__cfor_seq!(static_for [@expand $i $body] $a .. $b)
+    };
+    ($i: ident in $a: tt ..= $b: tt $body: block $(;)?) => {
+        $crate::__cfor_seq!(static_for [@expand $i $body] $a ..= $b)
+    };
+}
+
+/// Generates code for each number in a range, at compile time, in
+/// macro form.
+///
+/// This takes the form `seq_cfor!(a..b => path::to::callback!(args))`
+/// (or `a..=b`), where `a` and `b` are integer literals up to 64, and
+/// expands to `path::to::callback!(args [a a+1 ... b-1])`, so that the
+/// callback, usually a local `macro_rules!`, can repeat anything it
+/// likes with the numbers: match arms, struct fields, trait impls,
+/// etc. (Unlike `static_for!`, this works where a block can't go, but
+/// `macro_rules!` can't substitute numbers into arbitrary code itself,
+/// so it needs the callback to do the repetition.)
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// macro_rules! impl_nth {
+///     ($t: ty, [$($k: tt)*]) => {
+///         fn nth(x: &$t, n: usize) -> Option<u8> {
+///             match n {
+///                 $($k => Some(x.$k),)*
+///                 _ => None,
+///             }
+///         }
+///     };
+/// }
+///
+/// seq_cfor!(0..3 => impl_nth!((u8, u8, u8),));
+///
+/// fn main() {
+///     let x = (10, 20, 30);
+///     assert_eq!((nth(&x, 1), nth(&x, 3)), (Some(20), None));
+/// }
+/// ```
+#[macro_export]
+macro_rules! seq_cfor {
+    (@call [$($callback: tt)*] [$($args: tt)*] [$($k: tt)*]) => {
+        $($callback)*!{$($args)* [$($k)*]}
+    };
+    ($a: tt .. $b: tt => $($callback: ident)::+ ! ($($args: tt)*) $(;)?) => {
+        $crate::__cfor_seq!{seq_cfor [@call [$($callback)::+] [$($args)*]] $a .. $b}
+    };
+    ($a: tt ..= $b: tt => $($callback: ident)::+ ! ($($args: tt)*) $(;)?) => {
+        $crate::__cfor_seq!{seq_cfor [@call [$($callback)::+] [$($args)*]] $a ..= $b}
+    };
+}
+
+/// Builds an array from each element's index, in macro form.
+///
+/// This takes the form `array_init_cfor![i => value; N]`, and
+/// evaluates to a `[T; N]` whose `i`th element is `value` (which can
+/// use `i`, a `usize`), computed with `i` counting up from `0`. This
+/// is like `core::array::from_fn(|i| value)`, except it also works in
+/// `const` items and `const fn`s, as long as `value` does, and doesn't
+/// need `T` to be `Copy`, unlike `[value; N]`.
+///
+/// If computing an element panics, the ones before it are leaked
+/// rather than dropped.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// const SQUARES: [u32; 8] = array_init_cfor![i => (i * i) as u32; 8];
+///
+/// const fn crc_table() -> [u32; 256] {
+///     array_init_cfor![i => {
+///         let mut crc = i as u32;
+///         cfor!{let mut bit = 0; bit < 8; bit += 1; {
+///             crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+///         }}
+///         crc
+///     }; 256]
+/// }
+///
+/// fn main() {
+///     assert_eq!(SQUARES[7], 49);
+///     assert_eq!(crc_table()[1], 0x7707_3096);
+///
+///     let names = array_init_cfor![i => format!("item {}", i); 3];
+///     assert_eq!(names[2], "item 2");
+/// }
+/// ```
+#[macro_export]
+macro_rules! array_init_cfor {
+    ($i: ident => $value: expr; $n: expr) => {
+        {
+            let mut builder = $crate::__private::ArrayBuilder::<_, { $n }>::new();
+            $crate::cfor!(let mut next = 0; next < $n; next += 1; {
+                let $i: usize = next;
+                builder = builder.push($value);
+            });
+            builder.finish()
+        }
+    };
+}
+
+/// A parallel loop over a range, in macro form.
+///
+/// This takes the form `par_cfor!(i in range { body })`, where `range`
+/// is an integer `a..b` or `a..=b`, and runs `body` once for each `i`
+/// in it, split across the `rayon` thread pool, in no particular
+/// order. This is for the common C loop `for (i = a; i < b; i++)`
+/// whose iterations are independent, like most numeric kernels.
+///
+/// The body is a closure called from several threads at once, so it
+/// can only share what is `Sync`, it can't mutate the variables around
+/// it (other than through atomics, locks, etc.), and `break` and
+/// `continue` aren't allowed; `return` ends the current iteration.
+///
+/// This requires the `rayon` feature.
+///
+/// ```rust
+/// # #[cfg(feature = "rayon")] {
+/// use std::sync::atomic::{AtomicU64, Ordering};
+/// use cfor::par_cfor;
+///
+/// let data: Vec<u64> = (0..1000).collect();
+/// let total = AtomicU64::new(0);
+/// par_cfor!(i in 0..data.len() {
+///     if data[i] % 2 == 1 { return }
+///     total.fetch_add(data[i], Ordering::Relaxed);
+/// });
+/// assert_eq!(total.into_inner(), 998 * 500 / 2);
+/// # }
+/// ```
+#[cfg(feature = "rayon")]
+#[macro_export]
+macro_rules! par_cfor {
+    // as in `crepeat!`, the range is collected up to the final block.
+    (@range $i: ident [$($range: tt)+]; { $($body: tt)* }) => {
+        $crate::__private::rayon::iter::ParallelIterator::for_each(
+            $crate::__private::rayon::iter::IntoParallelIterator::into_par_iter($($range)+),
+            |$i| { $($body)* },
+        )
+    };
+    (@range $i: ident [$($range: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::par_cfor!(@range $i [$($range)* $next]; $($rest)*)
+    };
+    ($i: ident in $($rest: tt)*) => {
+        $crate::par_cfor!(@range $i []; $($rest)*)
+    };
+}
+
+/// A parallel loop over a range that combines a value from each
+/// iteration, in macro form.
+///
+/// This takes the form `par_cfor_reduce!(reduce(identity, combine); i
+/// in range { body })`, and is like `par_cfor!`, except `body`
+/// evaluates to a value, and the whole macro evaluates to all of them
+/// combined with `combine`, a closure like `|a, b| a + b`, starting
+/// from `identity`. Since the iterations are split across threads,
+/// `combine` should be associative (like addition, or `min`), and
+/// `identity` is evaluated once for each piece of work, so should be
+/// a value that `combine` leaves the other argument unchanged with
+/// (like `0` for addition). Each thread combines its own results
+/// before they're combined with each other, so this doesn't need any
+/// locks or channels.
+///
+/// This requires the `rayon` feature.
+///
+/// ```rust
+/// # #[cfg(feature = "rayon")] {
+/// use cfor::par_cfor_reduce;
+///
+/// let data: Vec<i64> = (0..1000).map(|x| (x * 7919) % 1000 - 500).collect();
+/// let sum_of_squares = par_cfor_reduce!(reduce(0, |a, b| a + b); i in 0..data.len() {
+///     data[i] * data[i]
+/// });
+/// let min = par_cfor_reduce!(reduce(i64::MAX, i64::min); i in 0..data.len() { data[i] });
+/// assert_eq!(sum_of_squares, data.iter().map(|x| x * x).sum::<i64>());
+/// assert_eq!(min, -500);
+/// # }
+/// ```
+#[cfg(feature = "rayon")]
+#[macro_export]
+macro_rules! par_cfor_reduce {
+    (@range $identity: tt $combine: tt; $i: ident [$($range: tt)+]; { $($body: tt)* }) => {
+        $crate::__private::rayon::iter::ParallelIterator::reduce(
+            $crate::__private::rayon::iter::ParallelIterator::map(
+                $crate::__private::rayon::iter::IntoParallelIterator::into_par_iter($($range)+),
+                |$i| { $($body)* },
+            ),
+            || $identity,
+            $combine,
+        )
+    };
+    (@range $identity: tt $combine: tt; $i: ident [$($range: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::par_cfor_reduce!(@range $identity $combine; $i [$($range)* $next]; $($rest)*)
+    };
+    (reduce($identity: expr, $combine: expr $(,)?); $i: ident in $($rest: tt)*) => {
+        $crate::par_cfor_reduce!(@range ($identity) ($combine); $i []; $($rest)*)
+    };
+}
+
+/// A parallel loop over a range on scoped threads, in macro form.
+///
+/// This takes the form `scoped_par_cfor!(threads = n; i in a..b {
+/// body })`, where `a..b` is a `Range<usize>`, and runs `body` once for
+/// each `i` in it, with the range split into `n` (or fewer, if
+/// there aren't enough iterations) contiguous chunks, each run in
+/// order on its own thread (from `std::thread::scope`).
+/// The whole loop finishes before the macro does, and so, unlike with
+/// `std::thread::spawn`, the body can borrow from the enclosing
+/// function. A panic in the body is propagated once all the threads
+/// have finished.
+///
+/// As with `par_cfor!`, the body is called from several threads at
+/// once, so it can only share what is `Sync`, and it can't mutate the
+/// variables around it, use `break` or `continue`; `return` ends the
+/// current iteration.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// use std::sync::atomic::{AtomicU64, Ordering};
+///
+/// fn main() {
+///     let data: Vec<u64> = (0..1000).collect();
+///     let total = AtomicU64::new(0);
+///     scoped_par_cfor!(threads = 4; i in 0..data.len() {
+///         total.fetch_add(data[i], Ordering::Relaxed);
+///     });
+///     assert_eq!(total.into_inner(), 999 * 1000 / 2);
+/// }
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! scoped_par_cfor {
+    (@range $threads: expr; $i: ident [$($range: tt)+]; { $($body: tt)* }) => {
+        $crate::__private::par::scoped($threads, $($range)+, &|$i: usize| { $($body)* })
+    };
+    (@range $threads: expr; $i: ident [$($range: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::scoped_par_cfor!(@range $threads; $i [$($range)* $next]; $($rest)*)
+    };
+    (threads = $threads: expr; $i: ident in $($rest: tt)*) => {
+        $crate::scoped_par_cfor!(@range $threads; $i []; $($rest)*)
+    };
+}
+
+/// A parallel loop over a range, with the work shared out as it
+/// goes, in macro form.
+///
+/// This takes the form `dynamic_par_cfor!(threads = n, chunk = c; i in
+/// a..b { body })` (or just `threads = n;`, for a `chunk` of `1`), and
+/// is like `scoped_par_cfor!`, except, rather than each thread being
+/// given a fixed part of the range up front, whenever one finishes
+/// its current chunk of `c` indices, it takes the next one that no
+/// thread has started. So, when some iterations take much longer
+/// than others, the threads that get the quick ones don't finish
+/// early and sit idle. A larger `chunk` means less contention
+/// between the threads, at the cost of a less even split.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// use std::sync::atomic::{AtomicU64, Ordering};
+///
+/// fn collatz_steps(mut x: u64) -> u64 {
+///     let mut steps = 0;
+///     cwhile!(x != 1; steps += 1; {
+///         x = if x % 2 == 0 { x / 2 } else { 3 * x + 1 };
+///     });
+///     steps
+/// }
+///
+/// fn main() {
+///     let longest = AtomicU64::new(0);
+///     dynamic_par_cfor!(threads = 4, chunk = 64; i in 1..10_000 {
+///         longest.fetch_max(collatz_steps(i as u64), Ordering::Relaxed);
+///     });
+///     assert_eq!(longest.into_inner(), 261);
+/// }
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! dynamic_par_cfor {
+    (@range $threads: expr, $chunk: expr; $i: ident [$($range: tt)+]; { $($body: tt)* }) => {
+        $crate::__private::par::dynamic($threads, $chunk, $($range)+, &|$i: usize| { $($body)* })
+    };
+    (@range $threads: expr, $chunk: expr; $i: ident [$($range: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::dynamic_par_cfor!(@range $threads, $chunk; $i [$($range)* $next]; $($rest)*)
+    };
+    (threads = $threads: expr, chunk = $chunk: expr; $i: ident in $($rest: tt)*) => {
+        $crate::dynamic_par_cfor!(@range $threads, $chunk; $i []; $($rest)*)
+    };
+    (threads = $threads: expr; $i: ident in $($rest: tt)*) => {
+        $crate::dynamic_par_cfor!(@range $threads, 1; $i []; $($rest)*)
+    };
+}
+
+/// A `cfor!` that counts its iterations, in macro form.
+///
+/// This takes exactly the same input as `cfor!`, and evaluates to the
+/// number of iterations of the body that started, as a `u64`. This
+/// includes an iteration that ends with a `continue` or a `break`, so a
+/// loop that breaks in its first iteration counts one.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let words = ["apple", "banana", "", "cherry"];
+///     let n = counted_cfor!(let mut i = 0; i < words.len(); i += 1; {
+///         if words[i].is_empty() { break }
+///     });
+///     assert_eq!(n, 3);
+/// }
+/// ```
+#[macro_export]
+macro_rules! counted_cfor {
+    (@run $label: lifetime; $($rest: tt)*) => {
+        {
+            let mut iterations = 0u64;
+            $crate::__cfor!((plain [] [({ iterations += 1; true })]) $label; $($rest)*);
+            iterations
+        }
+    };
+    ($label: lifetime: $($rest: tt)*) => {
+        $crate::counted_cfor!(@run $label; $($rest)*)
+    };
+    ($($rest: tt)*) => {
+        $crate::counted_cfor!(@run 'cfor; $($rest)*)
+    };
+}
+
+/// A `cfor!` that times itself, in macro form.
+///
+/// This takes exactly the same input as `cfor!`, and evaluates to the
+/// `Duration` that the whole loop took to run, measured with
+/// `std::time::Instant`, from just before the initialiser until after
+/// the `else` and `finally` blocks (if any).
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let mut total = 0u64;
+///     let elapsed = timed_cfor!(let mut i = 0; i < 1000; i += 1; {
+///         total += i;
+///     });
+///     println!("summed in {:?}", elapsed);
+///     assert_eq!(total, 999 * 1000 / 2);
+/// }
+/// ```
+#[macro_export]
+macro_rules! timed_cfor {
+    ($($rest: tt)*) => {
+        {
+            let start = ::std::time::Instant::now();
+            $crate::cfor!($($rest)*);
+            start.elapsed()
+        }
+    };
+}
+
+/// A `cfor!` that summarises how it ran, in macro form.
+///
+/// This takes exactly the same input as `cfor!`, and evaluates to a
+/// `LoopStats`, with the number of iterations that started (like
+/// `counted_cfor!`), whether the body ended the loop with a `break`,
+/// and how long the loop took (like `timed_cfor!`).
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let haystack = [3, 1, 4, 1, 5, 9, 2, 6];
+///     let stats = stats_cfor!(let mut i = 0; i < haystack.len(); i += 1; {
+///         if haystack[i] == 5 { break }
+///     });
+///     assert_eq!((stats.iterations, stats.broke_early), (5, true));
+///     println!("searched in {:?}", stats.elapsed);
+/// }
+/// ```
+#[macro_export]
+macro_rules! stats_cfor {
+    (@run $label: lifetime; $($rest: tt)*) => {
+        {
+            let start = ::std::time::Instant::now();
+            let mut iterations = 0u64;
+            // as in `observed_cfor!`.
+            let mut completed;
+            $crate::__cfor!((plain [({ completed = true; true })] [({
+                completed = false;
+                iterations += 1;
+                true
+            })]) $label; $($rest)*);
+            $crate::LoopStats {
+                iterations,
+                broke_early: !completed,
+                elapsed: start.elapsed(),
+            }
+        }
+    };
+    ($label: lifetime: $($rest: tt)*) => {
+        $crate::stats_cfor!(@run $label; $($rest)*)
+    };
+    ($($rest: tt)*) => {
+        $crate::stats_cfor!(@run 'cfor; $($rest)*)
+    };
+}
+
+/// A `cfor!` that reports its progress to a `LoopObserver`, in macro
+/// form.
+///
+/// This takes the form `observed_cfor!(observer = o; ...)`, where `...`
+/// is anything accepted by `cfor!`, and calls the hooks of `o` as the
+/// loop runs. `o` is taken by value, so, to use the observer again
+/// afterwards, pass `&mut o`.
+///
+/// `on_start` is called after the initialiser and `on_iteration`
+/// before each iteration of the body, but `on_break` and `on_finish`
+/// are called after the whole loop, including any `else` or `finally`
+/// block.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// use cfor::LoopObserver;
+///
+/// #[derive(Default)]
+/// struct Stats {
+///     iterations: u64,
+///     broke: bool,
+/// }
+///
+/// impl LoopObserver for Stats {
+///     fn on_iteration(&mut self, iteration: u64) {
+///         self.iterations = iteration + 1;
+///     }
+///     fn on_break(&mut self) {
+///         self.broke = true;
+///     }
+/// }
+///
+/// fn main() {
+///     let mut stats = Stats::default();
+///     observed_cfor!(observer = &mut stats; let mut i = 0; i < 10; i += 1; {
+///         if i == 3 { break }
+///     });
+///     assert_eq!((stats.iterations, stats.broke), (4, true));
+/// }
+/// ```
+#[macro_export]
+macro_rules! observed_cfor {
+    (observer = $observer: expr; $label: lifetime: $($rest: tt)*) => {
+        $crate::observed_cfor!(@run $observer; $label; $($rest)*)
+    };
+    (observer = $observer: expr; $($rest: tt)*) => {
+        $crate::observed_cfor!(@run $observer; 'cfor; $($rest)*)
+    };
+    (@run $observer: expr; $label: lifetime; $($rest: tt)*) => {
+        {
+            let mut observer = $observer;
+            let mut iterations = 0u64;
+            // the condition was false the last time it was checked,
+            // rather than the body breaking.
+            let mut completed;
+            $crate::__cfor!((plain [({ completed = true; true })] [({
+                completed = false;
+                $crate::LoopObserver::on_iteration(&mut observer, iterations);
+                iterations += 1;
+                true
+            })] [($crate::LoopObserver::on_start(&mut observer))]) $label; $($rest)*);
+            if !completed {
+                $crate::LoopObserver::on_break(&mut observer);
+            }
+            $crate::LoopObserver::on_finish(&mut observer);
+        }
+    };
+}
+
+/// A `cfor!` that reports its iterations to `tracing`, in macro
+/// form.
+///
+/// This takes exactly the same input as `cfor!`, optionally preceded
+/// by `vars = (a, b, ...);`, and runs the loop inside a `cfor` span
+/// (at the `TRACE` level) recording the location of the loop. Each
+/// iteration emits a `TRACE` event with the index of the iteration, as
+/// the field `iteration`, and the `Debug` representation of each of
+/// the listed variables, which are usually the ones declared in the
+/// initialiser.
+///
+/// This requires the `tracing` feature.
+///
+/// ```rust
+/// # #[cfg(feature = "tracing")] {
+/// use cfor::traced_cfor;
+///
+/// let mut total = 0;
+/// traced_cfor!(vars = (i, total); let mut i = 0; i < 10; i += 1; {
+///     total += i;
+/// });
+/// assert_eq!(total, 45);
+/// # }
+/// ```
+#[cfg(feature = "tracing")]
+#[macro_export]
+macro_rules! traced_cfor {
+    (@run [$($var: ident),*]; $label: lifetime; $($rest: tt)*) => {
+        {
+            let span = $crate::__private::tracing::trace_span!(
+                "cfor",
+                location = ::core::concat!(::core::file!(), ":", ::core::line!()),
+            );
+            let _entered = span.enter();
+            let mut iterations = 0u64;
+            $crate::__cfor!((plain [] [({
+                $crate::__private::tracing::trace!(iteration = iterations $(, $var = ?$var)*);
+                iterations += 1;
+                true
+            })]) $label; $($rest)*)
+        }
+    };
+    (vars = ($($var: ident),* $(,)?); $label: lifetime: $($rest: tt)*) => {
+        $crate::traced_cfor!(@run [$($var),*]; $label; $($rest)*)
+    };
+    (vars = ($($var: ident),* $(,)?); $($rest: tt)*) => {
+        $crate::traced_cfor!(@run [$($var),*]; 'cfor; $($rest)*)
+    };
+    ($label: lifetime: $($rest: tt)*) => {
+        $crate::traced_cfor!(@run []; $label; $($rest)*)
+    };
+    ($($rest: tt)*) => {
+        $crate::traced_cfor!(@run []; 'cfor; $($rest)*)
+    };
+}
+
+/// A `cfor!` that logs its variables, in macro form.
+///
+/// This takes the form `logged_cfor!(vars = (a, b, ...); ...)`, where
+/// `...` is anything accepted by `cfor!`, and logs the `Debug`
+/// representation of each of the listed variables with `log::debug!`,
+/// along with the location of the loop and the index of the
+/// iteration. To avoid flooding the log, this only happens for the
+/// first iteration, and then those whose index is a power of two (1, 2,
+/// 4, 8, ...): cheap enough to leave in, and still enough to see where
+/// a long loop is going.
+///
+/// This requires the `log` feature.
+///
+/// ```rust
+/// # #[cfg(feature = "log")] {
+/// use cfor::logged_cfor;
+///
+/// let mut total = 0;
+/// // logs "cfor at src/main.rs:7: iteration 0: i = 0, total = 0", etc.
+/// logged_cfor!(vars = (i, total); let mut i = 0; i < 10; i += 1; {
+///     total += i;
+/// });
+/// assert_eq!(total, 45);
+/// # }
+/// ```
+#[cfg(feature = "log")]
+#[macro_export]
+macro_rules! logged_cfor {
+    (vars = ($($var: ident),+ $(,)?); $label: lifetime: $($rest: tt)*) => {
+        $crate::logged_cfor!(@run [$($var),+]; $label; $($rest)*)
+    };
+    (vars = ($($var: ident),+ $(,)?); $($rest: tt)*) => {
+        $crate::logged_cfor!(@run [$($var),+]; 'cfor; $($rest)*)
+    };
+    (@run [$($var: ident),+]; $label: lifetime; $($rest: tt)*) => {
+        {
+            let mut iterations = 0u64;
+            $crate::__cfor!((plain [] [({
+                if iterations == 0 || iterations.is_power_of_two() {
+                    $crate::__private::log::debug!(
+                        ::core::concat!(
+                            "cfor at {}:{}: iteration {}: ",
+                            $crate::logged_cfor!(@format $($var),+),
+                        ),
+                        ::core::file!(), ::core::line!(), iterations, $($var),+
+                    );
+                }
+                iterations += 1;
+                true
+            })]) $label; $($rest)*)
+        }
+    };
+    (@format $first: ident $(, $var: ident)*) => {
+        ::core::concat!(::core::stringify!($first), " = {:?}" $(, ", ", ::core::stringify!($var), " = {:?}")*)
+    };
+}
+
+/// A `cfor!` with a progress bar, in macro form.
+///
+/// This takes exactly the same input as `cfor!`, optionally preceded
+/// by `bar = b;`, where `b` is an `indicatif::ProgressBar`, and
+/// advances the bar by one for each iteration. Without `bar`, a new
+/// one is shown, and finished when the loop ends; a given bar is left
+/// as it is, so that it can be used for more.
+///
+/// When the header has the form `let mut i = start; i < end; i +=
+/// step` (or `i <= end`), with the same `i` in each part and nothing
+/// else in the condition, and these are integers, the length of the
+/// bar is set to the number of iterations, so that it can show an
+/// ETA. This evaluates `end` and `step` an extra time, before the
+/// first iteration.
+///
+/// This requires the `indicatif` feature.
+///
+/// ```rust
+/// # #[cfg(feature = "indicatif")] {
+/// use cfor::progress_cfor;
+/// use indicatif::ProgressBar;
+///
+/// let bar = ProgressBar::hidden();
+/// let mut total = 0;
+/// progress_cfor!(bar = bar.clone(); let mut i = 0; i < 100; i += 3; {
+///     total += i;
+/// });
+/// assert_eq!((bar.position(), bar.length()), (34, Some(34)));
+/// # }
+/// ```
+#[cfg(feature = "indicatif")]
+#[macro_export]
+macro_rules! progress_cfor {
+    (@run $bar: ident $finish: tt; $label: lifetime; $($rest: tt)*) => {
+        $crate::progress_cfor!(@range $bar $finish; $label; [$($rest)*] $($rest)*)
+    };
+
+    // work out the length, if the header is a simple range. The end
+    // and step are collected up to the `;`s, since they could be
+    // anything (like a `let` chain).
+    (@range $bar: ident $finish: tt; $label: lifetime; $all: tt ($($header: tt)*) $body: block $($rest: tt)*) => {
+        $crate::progress_cfor!(@range $bar $finish; $label; $all $($header)*; $body $($rest)*)
+    };
+    (@range $bar: ident $finish: tt; $label: lifetime; $all: tt let mut $i: ident = $start: expr; $j: ident < $($rest: tt)*) => {
+        $crate::progress_cfor!(@end $bar $finish false; $label; $all $i $j [] $($rest)*)
+    };
+    (@range $bar: ident $finish: tt; $label: lifetime; $all: tt let mut $i: ident = $start: expr; $j: ident <= $($rest: tt)*) => {
+        $crate::progress_cfor!(@end $bar $finish true; $label; $all $i $j [] $($rest)*)
+    };
+    (@range $bar: ident $finish: tt; $label: lifetime; $all: tt $($rest: tt)*) => {
+        $crate::progress_cfor!(@expand $bar $finish; []; $label; $all)
+    };
+    (@end $bar: ident $finish: tt $incl: tt; $label: lifetime; $all: tt $i: ident $j: ident [$($end: tt)+] ; $k: ident += $($rest: tt)*) => {
+        $crate::progress_cfor!(@step $bar $finish $incl; $label; $all $i $j $k [$($end)+] [] $($rest)*)
+    };
+    // the end is only the rest of the condition if that doesn't go on
+    // with a `&&` or `||` (which bind less tightly than the `<`).
+    (@end $bar: ident $finish: tt $incl: tt; $label: lifetime; $all: tt $i: ident $j: ident $end: tt && $($rest: tt)*) => {
+        $crate::progress_cfor!(@expand $bar $finish; []; $label; $all)
+    };
+    (@end $bar: ident $finish: tt $incl: tt; $label: lifetime; $all: tt $i: ident $j: ident $end: tt || $($rest: tt)*) => {
+        $crate::progress_cfor!(@expand $bar $finish; []; $label; $all)
+    };
+    (@end $bar: ident $finish: tt $incl: tt; $label: lifetime; $all: tt $i: ident $j: ident [$($end: tt)*] ; $($rest: tt)*) => {
+        $crate::progress_cfor!(@expand $bar $finish; []; $label; $all)
+    };
+    (@end $bar: ident $finish: tt $incl: tt; $label: lifetime; $all: tt $i: ident $j: ident [$($end: tt)*] $next: tt $($rest: tt)*) => {
+        $crate::progress_cfor!(@end $bar $finish $incl; $label; $all $i $j [$($end)* $next] $($rest)*)
+    };
+    // `$i`, `$j` and `$k` have to be the same variable, which is only
+    // known by matching them against each other, by a macro with `$i`
+    // in its pattern.
+    (@step $bar: ident $finish: tt $incl: tt; $label: lifetime; $all: tt $i: ident $j: ident $k: ident [$($end: tt)+] [$($step: tt)+] ; $($rest: tt)*) => {
+        $crate::progress_cfor!(@expand $bar $finish; [({
+            macro_rules! progress_len {
+                ($i $i $i) => {
+                    use $crate::__private::progress::{Len as _, Unknown as _};
+                    let range = $crate::__private::progress::Range(&$i, &($($end)+), &($($step)+));
+                    if let $crate::__private::Some(len) = (&range).len($incl) {
+                        $bar.set_length(len);
+                    }
+                };
+                ($other: tt $other2: tt $other3: tt) => {};
+            }
+            progress_len!($i $j $k);
+        })]; $label; $all)
+    };
+    // several steps
+    (@step $bar: ident $finish: tt $incl: tt; $label: lifetime; $all: tt $i: ident $j: ident $k: ident $end: tt $step: tt , $($rest: tt)*) => {
+        $crate::progress_cfor!(@expand $bar $finish; []; $label; $all)
+    };
+    (@step $bar: ident $finish: tt $incl: tt; $label: lifetime; $all: tt $i: ident $j: ident $k: ident $end: tt [$($step: tt)*] $next: tt $($rest: tt)*) => {
+        $crate::progress_cfor!(@step $bar $finish $incl; $label; $all $i $j $k $end [$($step)* $next] $($rest)*)
+    };
+    (@step $bar: ident $finish: tt $incl: tt; $label: lifetime; $all: tt $($rest: tt)*) => {
+        $crate::progress_cfor!(@expand $bar $finish; []; $label; $all)
+    };
+
+    (@expand $bar: ident [$($finish: tt)?]; [$($init: tt)*]; $label: lifetime; [$($rest: tt)*]) => {
+        {
+            $crate::__cfor!((plain [] [({ $bar.inc(1); true })] [$($init)*]) $label; $($rest)*);
+            $($bar.$finish();)?
+        }
+    };
+
+    (bar = $bar: expr; $label: lifetime: $($rest: tt)*) => {
+        {
+            let bar: &$crate::__private::indicatif::ProgressBar = &$bar;
+            $crate::progress_cfor!(@run bar []; $label; $($rest)*)
+        }
+    };
+    (bar = $bar: expr; $($rest: tt)*) => {
+        {
+            let bar: &$crate::__private::indicatif::ProgressBar = &$bar;
+            $crate::progress_cfor!(@run bar []; 'cfor; $($rest)*)
+        }
+    };
+    ($label: lifetime: $($rest: tt)*) => {
+        {
+            let bar = $crate::__private::indicatif::ProgressBar::no_length();
+            $crate::progress_cfor!(@run bar [finish]; $label; $($rest)*)
+        }
+    };
+    ($($rest: tt)*) => {
+        {
+            let bar = $crate::__private::indicatif::ProgressBar::no_length();
+            $crate::progress_cfor!(@run bar [finish]; 'cfor; $($rest)*)
+        }
+    };
+}
+
+/// A `cfor!` with a limit on its iterations, in macro form.
+///
+/// This takes the form `cfor_bounded!(max = n; ...)`, where `...` is
+/// anything accepted by `cfor!`, and runs the loop until it ends
+/// normally, or it would start more than `n` iterations. The whole
+/// macro evaluates to `Ok(())` in the first case, and
+/// `Err(Exhausted)` in the second, so that a loop over untrusted input
+/// can't run forever (or just for too long).
+///
+/// The limit is checked after the condition, so that a loop that
+/// would have stopped after exactly `n` iterations anyway isn't
+/// reported as exhausted. Otherwise, reaching the limit ends the loop
+/// like the condition becoming false.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// use cfor::Exhausted;
+///
+/// fn collatz_steps(mut x: u64) -> Result<u32, Exhausted> {
+///     let mut steps = 0;
+///     cfor_bounded!(max = 100; ; x != 1; steps += 1; {
+///         x = if x % 2 == 0 { x / 2 } else { 3 * x + 1 };
+///     })?;
+///     Ok(steps)
+/// }
+///
+/// fn main() {
+///     assert_eq!(collatz_steps(6), Ok(8));
+///     assert_eq!(collatz_steps(27), Err(Exhausted));
+/// }
+/// ```
+#[macro_export]
+macro_rules! cfor_bounded {
+    (max = $max: expr; $label: lifetime: $($rest: tt)*) => {
+        $crate::cfor_bounded!(@run $max; $label; $($rest)*)
+    };
+    (max = $max: expr; $($rest: tt)*) => {
+        $crate::cfor_bounded!(@run $max; 'cfor; $($rest)*)
+    };
+    (@run $max: expr; $label: lifetime; $($rest: tt)*) => {
+        {
+            let max = $max;
+            let mut iterations = 0;
+            let mut exhausted = false;
+            $crate::__cfor!((plain [] [({
+                exhausted = iterations >= max;
+                if !exhausted { iterations += 1 }
+                !exhausted
+            })]) $label; $($rest)*);
+            if exhausted {
+                $crate::__private::Err($crate::Exhausted)
+            } else {
+                $crate::__private::Ok(())
+            }
+        }
+    };
+}
+
+/// Applies a transformation until its result stops changing, in macro
+/// form.
+///
+/// This takes the form `until_stable!(x = start => next)`, and
+/// declares `x` as `start`, then repeatedly evaluates `next`, until it
+/// is equal to `x`, and otherwise replaces `x` with it, evaluating to
+/// the final `x`, the fixed point. This is the outer loop of dataflow
+/// analyses and constraint solvers, which refine an answer until it
+/// settles. `x` can be given a type, as in `x: T = start`, which must
+/// implement `PartialEq`. `next` can use `x`, but not move out of it,
+/// since it is compared to the result.
+///
+/// With `until_stable!(max = n; x = start => next)`, at most `n`
+/// `next`s are evaluated, and the macro evaluates to `Ok` of the fixed
+/// point, or `Err(Exhausted)` if it hasn't settled by then, as with
+/// `cfor_bounded!`.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// use cfor::Exhausted;
+///
+/// fn main() {
+///     // the integer square root of 1000, by Newton's method.
+///     let n = 1000u32;
+///     let root = until_stable!(x = n => (x + n / x) / 2);
+///     assert_eq!(root, 31);
+///
+///     // the nodes reachable from node 0, as a bit set.
+///     let edges: [u8; 4] = [0b0010, 0b0100, 0b0010, 0b0001];
+///     let reachable = until_stable!(seen: u8 = 0b0001 => {
+///         let mut next = seen;
+///         for node in 0..4 {
+///             if seen & (1 << node) != 0 { next |= edges[node] }
+///         }
+///         next
+///     });
+///     assert_eq!(reachable, 0b0111);
+///
+///     assert_eq!(until_stable!(max = 5; x = 1u64 => x * 2), Err(Exhausted));
+/// }
+/// ```
+#[macro_export]
+macro_rules! until_stable {
+    (max = $max: expr; $x: ident $(: $ty: ty)? = $start: expr => $next: expr) => {
+        {
+            let mut $x $(: $ty)? = $start;
+            let result = $crate::cfor_bounded!(max = $max; ; ; ; {
+                let next = $next;
+                if next == $x {
+                    break;
+                }
+                $x = next;
+            });
+            match result {
+                $crate::__private::Ok(()) => $crate::__private::Ok($x),
+                $crate::__private::Err(e) => $crate::__private::Err(e),
+            }
+        }
+    };
+    ($x: ident $(: $ty: ty)? = $start: expr => $next: expr) => {
+        {
+            let mut $x $(: $ty)? = $start;
+            $crate::cfor!(; ; ; {
+                let next = $next;
+                if next == $x {
+                    break;
+                }
+                $x = next;
+            });
+            $x
+        }
+    };
+}
+
+/// An iterative numeric method, stopped by a tolerance or a limit on
+/// iterations, in macro form.
+///
+/// This takes the form `converge!(tol = t, max = n; x = start; { body
+/// } error: err)`, and declares `x` as `start`, then runs `body`
+/// (which updates `x`) and evaluates `err`, until `err < t`, or `body`
+/// has run `n` times. The macro evaluates to `Ok(x)` in the first
+/// case, and `Err(DidNotConverge)` in the second, so that a method
+/// that doesn't converge (or gives a NaN error) always stops, and is
+/// reported as such. `x` can be given a type, as in `x: T = start`,
+/// and can be a tuple for several values at once. A `break` in `body`
+/// stops it early, as if it had converged.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// use cfor::DidNotConverge;
+///
+/// fn sqrt(a: f64) -> Result<f64, DidNotConverge> {
+///     converge!(tol = 1e-12, max = 50; x = a.max(1.0); {
+///         x = (x + a / x) / 2.0;
+///     } error: (x * x - a).abs())
+/// }
+///
+/// fn main() {
+///     assert!((sqrt(2.0).unwrap() - 2f64.sqrt()).abs() < 1e-12);
+///
+///     // the fixed point of `cos`, which converges slowly.
+///     let slow = converge!(tol = 1e-12, max = 10; x = 1.0f64; {
+///         x = x.cos();
+///     } error: (x.cos() - x).abs());
+///     assert_eq!(slow, Err(DidNotConverge));
+/// }
+/// ```
+#[macro_export]
+macro_rules! converge {
+    (tol = $tol: expr, max = $max: expr; $x: ident $(: $ty: ty)? = $start: expr; $body: block error: $error: expr) => {
+        {
+            let mut $x $(: $ty)? = $start;
+            let tol = $tol;
+            let max = $max;
+            let converged = $crate::cfor_val!(let mut iterations = 0; iterations < max; iterations += 1; {
+                $body
+                if $error < tol {
+                    break;
+                }
+            });
+            match converged {
+                $crate::__private::Some(()) => $crate::__private::Ok($x),
+                $crate::__private::None => $crate::__private::Err($crate::DidNotConverge),
+            }
+        }
+    };
+}
+
+/// A `cfor!` whose step stops the loop when it would overflow, in
+/// macro form.
+///
+/// This takes the same form as `cfor!`, but each part of the step
+/// that is an `x += e`, `x -= e`, `x *= e` or `x /= e` (for a variable
+/// `x`) uses `checked_add`, `checked_sub`, `checked_mul` or
+/// `checked_div` instead. If that overflows, `x` is left unchanged,
+/// and the loop ends as if its condition were false (so any `else`
+/// block runs), rather than panicking in a debug build, or wrapping
+/// around in a release one. Other parts of the step are unchanged.
+/// (Since these are method calls, the type of `x` must be known,
+/// rather than being an integer literal with no suffix.)
+///
+/// This is for loops like `for (x = 1; x != 0; x *= 2)` on an
+/// unsigned type, which rely on C's wrapping to stop, and for `for (x
+/// = a; x <= b; x += s)` where `b` might be the largest value of the
+/// type (although `cfor!(x in a..=b step s { ... })` handles that
+/// case too).
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let mut powers = vec![];
+///     cfor_checked!(let mut x = 1u8; ; x *= 3; {
+///         powers.push(x);
+///     });
+///     assert_eq!(powers, [1, 3, 9, 27, 81, 243]);
+///
+///     // `x` never equals 255, but stops at the top anyway.
+///     let mut seen = vec![];
+///     cfor_checked!(let mut x = 250u8; x != 255; x += 2; {
+///         seen.push(x);
+///     } else {
+///         seen.push(0);
+///     });
+///     assert_eq!(seen, [250, 252, 254, 0]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! cfor_checked {
+    ($label: lifetime: $($rest: tt)*) => {
+        $crate::__cfor_arith!(checked $label; $($rest)*)
+    };
+    ($($rest: tt)*) => {
+        $crate::__cfor_arith!(checked 'cfor; $($rest)*)
+    };
+}
+
+/// A `cfor!` whose step wraps around on overflow, in macro form.
+///
+/// This takes the same form as `cfor!`, but each part of the step
+/// that is an `x += e`, `x -= e` or `x *= e` (for a variable `x`) uses
+/// `wrapping_add`, `wrapping_sub` or `wrapping_mul` instead, in both
+/// debug and release builds, like arithmetic on C's unsigned types.
+/// Other parts of the step are unchanged. This is for loops where the
+/// wrapping is the point, like probing a hash table, or walking a
+/// ring buffer indexed by the low bits of a counter. As with
+/// `cfor_checked!`, the type of `x` must be known.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let ring = [10, 11, 12, 13];
+///     let mut seen = vec![];
+///     // a sequence number that wraps, as `u8`s on the wire do.
+///     cfor_wrapping!(let mut seq = 254u8, let mut n = 0u32; n < 5; seq += 1, n += 1; {
+///         seen.push((seq, ring[seq as usize % ring.len()]));
+///     });
+///     assert_eq!(seen, [(254, 12), (255, 13), (0, 10), (1, 11), (2, 12)]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! cfor_wrapping {
+    ($label: lifetime: $($rest: tt)*) => {
+        $crate::__cfor_arith!(wrapping $label; $($rest)*)
+    };
+    ($($rest: tt)*) => {
+        $crate::__cfor_arith!(wrapping 'cfor; $($rest)*)
+    };
+}
+
+/// A `cfor!` whose step saturates at the bounds of its type, in macro
+/// form.
+///
+/// This takes the same form as `cfor!`, but each part of the step
+/// that is an `x += e`, `x -= e` or `x *= e` (for a variable `x`) uses
+/// `saturating_add`, `saturating_sub` or `saturating_mul` instead, so
+/// `x` stops at the largest (or smallest) value of its type rather
+/// than overflowing. The iteration with `x` at that bound still runs,
+/// but once a step leaves `x` unchanged, the loop ends as if its
+/// condition were false (so any `else` block runs), rather than
+/// repeating forever. Other parts of the step are unchanged. As with
+/// `cfor_checked!`, the type of `x` must be known.
+///
+/// This mirrors defensive C code like `i = (i > MAX - s) ? MAX : i +
+/// s`, and goes with `cfor_checked!`, which doesn't run that last
+/// iteration, and `cfor_wrapping!`, which runs past it.
+///
+/// ```rust
+/// #[macro_use] extern crate cfor;
+///
+/// fn main() {
+///     let mut delays = vec![];
+///     cfor_saturating!(let mut delay = 10u8; ; delay *= 3; {
+///         delays.push(delay);
+///     });
+///     assert_eq!(delays, [10, 30, 90, 255]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! cfor_saturating {
+    ($label: lifetime: $($rest: tt)*) => {
+        $crate::__cfor_arith!(saturating $label; $($rest)*)
+    };
+    ($($rest: tt)*) => {
+        $crate::__cfor_arith!(saturating 'cfor; $($rest)*)
+    };
+}
+
+/// The `cfor!`s with steps using a different kind of arithmetic:
+/// `checked`, `wrapping` or `saturating`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfor_arith {
+    // the header is split at its top-level `;`s, so that the step
+    // can be rewritten.
+    (@init $mode: ident $label: lifetime [$($init: tt)*]; ; $($rest: tt)*) => {
+        $crate::__cfor_arith!(@cond $mode $label [$($init)*] []; $($rest)*)
+    };
+    (@init $mode: ident $label: lifetime [$($init: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::__cfor_arith!(@init $mode $label [$($init)* $next]; $($rest)*)
+    };
+    (@cond $mode: ident $label: lifetime $init: tt [$($cond: tt)*]; ; $($rest: tt)*) => {
+        $crate::__cfor_arith!(@step $mode $label $init [$($cond)*] [] []; $($rest)*)
+    };
+    (@cond $mode: ident $label: lifetime $init: tt [$($cond: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::__cfor_arith!(@cond $mode $label $init [$($cond)* $next]; $($rest)*)
+    };
+    // ..., $cur, ...
+    (@step $mode: ident $label: lifetime $init: tt $cond: tt [$($done: tt)*] [$($cur: tt)+]; , $($rest: tt)*) => {
+        $crate::__cfor_arith!(@step $mode $label $init $cond [$($done)* ($($cur)+)] []; $($rest)*)
+    };
+    // ..., $cur; { ... }
+    (@step $mode: ident $label: lifetime $init: tt $cond: tt [$($done: tt)*] [$($cur: tt)+]; ; $($rest: tt)*) => {
+        $crate::__cfor_arith!(@expand $mode $label $init $cond [$($done)* ($($cur)+)]; $($rest)*)
+    };
+    (@step $mode: ident $label: lifetime $init: tt $cond: tt $done: tt []; ; $($rest: tt)*) => {
+        $crate::__cfor_arith!(@expand $mode $label $init $cond $done; $($rest)*)
+    };
+    (@step $mode: ident $label: lifetime $init: tt $cond: tt $done: tt [$($cur: tt)*]; $next: tt $($rest: tt)*) => {
+        $crate::__cfor_arith!(@step $mode $label $init $cond $done [$($cur)* $next]; $($rest)*)
+    };
+
+    (@expand checked $label: lifetime [$($init: tt)*] [$($cond: tt)*] [$($step: tt)*]; $($rest: tt)*) => {
+        $crate::__cfor!((plain [(!overflowed)] [] [(let mut overflowed = false)]) $label;
+                        $($init)*; $($cond)*; $($crate::__cfor_arith!(@rewrite checked overflowed $step)),*; $($rest)*)
+    };
+    (@expand saturating $label: lifetime [$($init: tt)*] [$($cond: tt)*] [$($step: tt)*]; $($rest: tt)*) => {
+        $crate::__cfor!((plain [(!stuck)] [] [(let mut stuck = false)]) $label;
+                        $($init)*; $($cond)*; $($crate::__cfor_arith!(@rewrite saturating stuck $step)),*; $($rest)*)
+    };
+    (@expand wrapping $label: lifetime [$($init: tt)*] [$($cond: tt)*] [$($step: tt)*]; $($rest: tt)*) => {
+        $crate::__cfor!(plain $label; $($init)*; $($cond)*; $($crate::__cfor_arith!(@rewrite wrapping () $step)),*; $($rest)*)
+    };
+
+    (@rewrite checked $flag: tt ($x: ident += $($e: tt)+)) => {
+        $crate::__cfor_arith!(@checked $flag $x checked_add ($($e)+))
+    };
+    (@rewrite checked $flag: tt ($x: ident -= $($e: tt)+)) => {
+        $crate::__cfor_arith!(@checked $flag $x checked_sub ($($e)+))
+    };
+    (@rewrite checked $flag: tt ($x: ident *= $($e: tt)+)) => {
+        $crate::__cfor_arith!(@checked $flag $x checked_mul ($($e)+))
+    };
+    (@rewrite checked $flag: tt ($x: ident /= $($e: tt)+)) => {
+        $crate::__cfor_arith!(@checked $flag $x checked_div ($($e)+))
+    };
+    (@rewrite wrapping $flag: tt ($x: ident += $($e: tt)+)) => {
+        $x = $x.wrapping_add($($e)+)
+    };
+    (@rewrite wrapping $flag: tt ($x: ident -= $($e: tt)+)) => {
+        $x = $x.wrapping_sub($($e)+)
+    };
+    (@rewrite wrapping $flag: tt ($x: ident *= $($e: tt)+)) => {
+        $x = $x.wrapping_mul($($e)+)
+    };
+    (@rewrite saturating $flag: tt ($x: ident += $($e: tt)+)) => {
+        $crate::__cfor_arith!(@saturating $flag $x saturating_add ($($e)+))
+    };
+    (@rewrite saturating $flag: tt ($x: ident -= $($e: tt)+)) => {
+        $crate::__cfor_arith!(@saturating $flag $x saturating_sub ($($e)+))
+    };
+    (@rewrite saturating $flag: tt ($x: ident *= $($e: tt)+)) => {
+        $crate::__cfor_arith!(@saturating $flag $x saturating_mul ($($e)+))
+    };
+    (@rewrite $mode: ident $flag: tt ($($step: tt)*)) => {
+        $($step)*
+    };
+    (@checked $flag: ident $x: ident $method: ident $e: tt) => {
+        match $x.$method($e) {
+            $crate::__private::Some(next) => $x = next,
+            $crate::__private::None => $flag = true,
+        }
+    };
+
+    (@saturating $flag: ident $x: ident $method: ident $e: tt) => {
+        {
+            let next = $x.$method($e);
+            if next == $x {
+                $flag = true
+            } else {
+                $x = next
+            }
+        }
+    };
+
+    ($mode: ident $label: lifetime; ($($header: tt)*) $body: block $($rest: tt)*) => {
+        $crate::__cfor_arith!(@init $mode $label []; $($header)*; $body $($rest)*)
+    };
+    ($mode: ident $label: lifetime; $($rest: tt)*) => {
+        $crate::__cfor_arith!(@init $mode $label []; $($rest)*)
+    };
+}
+
+/// Calls `$crate::$callback!($($args)* [a a+1 ... b-1])`, for bounds
+/// `a..b` or `a..=b` that are integer literals up to 64, so that
+/// macros can repeat things for each of the numbers in the range.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfor_seq {
+    ($callback: ident [$($args: tt)*] $a: tt .. $b: tt) => {
+        $crate::__cfor_seq!{@below $b @drop $a $callback [$($args)*]}
+    };
+    ($callback: ident [$($args: tt)*] $a: tt ..= $b: tt) => {
+        $crate::__cfor_seq!{@below $b @append $b @drop $a $callback [$($args)*]}
+    };
+
+    (@got [$($list: tt)*] @append $b: tt $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [$($list)* $b] $($cont)*}
+    };
+    // `a..b` is `0..b` without the numbers in `0..a`.
+    (@got [$($list: tt)*] @drop $a: tt $callback: ident $args: tt) => {
+        $crate::__cfor_seq!{@below $a @skip [$($list)*] $callback $args}
+    };
+    (@got [$($skip: tt)*] @skip [$($list: tt)*] $callback: ident $args: tt) => {
+        $crate::__cfor_seq!{@pop [$($skip)*] [$($list)*] $callback $args}
+    };
+    (@pop [] [$($list: tt)*] $callback: ident [$($args: tt)*]) => {
+        $crate::$callback!{$($args)* [$($list)*]}
+    };
+    (@pop [$skip: tt $($skips: tt)*] [$first: tt $($list: tt)*] $callback: ident $args: tt) => {
+        $crate::__cfor_seq!{@pop [$($skips)*] [$($list)*] $callback $args}
+    };
+    // an empty range, like `4..2`.
+    (@pop [$($skip: tt)+] [] $callback: ident [$($args: tt)*]) => {
+        $crate::$callback!{$($args)* []}
+    };
+
+    (@below 0 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [] $($cont)*}
+    };
+    (@below 1 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [0] $($cont)*}
+    };
+    (@below 2 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [0 1] $($cont)*}
+    };
+    (@below 3 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [0 1 2] $($cont)*}
+    };
+    (@below 4 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [0 1 2 3] $($cont)*}
+    };
+    (@below 5 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [0 1 2 3 4] $($cont)*}
+    };
+    (@below 6 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [0 1 2 3 4 5] $($cont)*}
+    };
+    (@below 7 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [0 1 2 3 4 5 6] $($cont)*}
+    };
+    (@below 8 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [0 1 2 3 4 5 6 7] $($cont)*}
+    };
+    (@below 9 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [0 1 2 3 4 5 6 7 8] $($cont)*}
+    };
+    (@below 10 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [0 1 2 3 4 5 6 7 8 9] $($cont)*}
+    };
+    (@below 11 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [0 1 2 3 4 5 6 7 8 9 10] $($cont)*}
+    };
+    (@below 12 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [0 1 2 3 4 5 6 7 8 9 10 11] $($cont)*}
+    };
+    (@below 13 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [0 1 2 3 4 5 6 7 8 9 10 11 12] $($cont)*}
+    };
+    (@below 14 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [0 1 2 3 4 5 6 7 8 9 10 11 12 13] $($cont)*}
+    };
+    (@below 15 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [0 1 2 3 4 5 6 7 8 9 10 11 12 13 14] $($cont)*}
+    };
+    (@below 16 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15] $($cont)*}
+    };
+    (@below 17 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16
+        ] $($cont)*}
+    };
+    (@below 18 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17
+        ] $($cont)*}
+    };
+    (@below 19 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18
+        ] $($cont)*}
+    };
+    (@below 20 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19
+        ] $($cont)*}
+    };
+    (@below 21 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20
+        ] $($cont)*}
+    };
+    (@below 22 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21
+        ] $($cont)*}
+    };
+    (@below 23 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22
+        ] $($cont)*}
+    };
+    (@below 24 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23
+        ] $($cont)*}
+    };
+    (@below 25 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24
+        ] $($cont)*}
+    };
+    (@below 26 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25
+        ] $($cont)*}
+    };
+    (@below 27 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26
+        ] $($cont)*}
+    };
+    (@below 28 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27
+        ] $($cont)*}
+    };
+    (@below 29 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28
+        ] $($cont)*}
+    };
+    (@below 30 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29
+        ] $($cont)*}
+    };
+    (@below 31 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30
+        ] $($cont)*}
+    };
+    (@below 32 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+        ] $($cont)*}
+    };
+    (@below 33 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32
+        ] $($cont)*}
+    };
+    (@below 34 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33
+        ] $($cont)*}
+    };
+    (@below 35 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34
+        ] $($cont)*}
+    };
+    (@below 36 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35
+        ] $($cont)*}
+    };
+    (@below 37 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36
+        ] $($cont)*}
+    };
+    (@below 38 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37
+        ] $($cont)*}
+    };
+    (@below 39 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38
+        ] $($cont)*}
+    };
+    (@below 40 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39
+        ] $($cont)*}
+    };
+    (@below 41 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40
+        ] $($cont)*}
+    };
+    (@below 42 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41
+        ] $($cont)*}
+    };
+    (@below 43 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42
+        ] $($cont)*}
+    };
+    (@below 44 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42 43
+        ] $($cont)*}
+    };
+    (@below 45 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42 43 44
+        ] $($cont)*}
+    };
+    (@below 46 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42 43 44 45
+        ] $($cont)*}
+    };
+    (@below 47 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42 43 44 45 46
+        ] $($cont)*}
+    };
+    (@below 48 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47
+        ] $($cont)*}
+    };
+    (@below 49 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47
+            48
+        ] $($cont)*}
+    };
+    (@below 50 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47
+            48 49
+        ] $($cont)*}
+    };
+    (@below 51 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47
+            48 49 50
+        ] $($cont)*}
+    };
+    (@below 52 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47
+            48 49 50 51
+        ] $($cont)*}
+    };
+    (@below 53 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47
+            48 49 50 51 52
+        ] $($cont)*}
+    };
+    (@below 54 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47
+            48 49 50 51 52 53
+        ] $($cont)*}
+    };
+    (@below 55 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47
+            48 49 50 51 52 53 54
+        ] $($cont)*}
+    };
+    (@below 56 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47
+            48 49 50 51 52 53 54 55
+        ] $($cont)*}
+    };
+    (@below 57 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47
+            48 49 50 51 52 53 54 55 56
+        ] $($cont)*}
+    };
+    (@below 58 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47
+            48 49 50 51 52 53 54 55 56 57
+        ] $($cont)*}
+    };
+    (@below 59 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47
+            48 49 50 51 52 53 54 55 56 57 58
+        ] $($cont)*}
+    };
+    (@below 60 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47
+            48 49 50 51 52 53 54 55 56 57 58 59
+        ] $($cont)*}
+    };
+    (@below 61 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47
+            48 49 50 51 52 53 54 55 56 57 58 59 60
+        ] $($cont)*}
+    };
+    (@below 62 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47
+            48 49 50 51 52 53 54 55 56 57 58 59 60 61
+        ] $($cont)*}
+    };
+    (@below 63 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47
+            48 49 50 51 52 53 54 55 56 57 58 59 60 61 62
+        ] $($cont)*}
+    };
+    (@below 64 $($cont: tt)*) => {
+        $crate::__cfor_seq!{@got [
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+            16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+            32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47
+            48 49 50 51 52 53 54 55 56 57 58 59 60 61 62 63
+        ] $($cont)*}
+    };
+    (@below $n: tt $($cont: tt)*) => {
+        ::core::compile_error!(::core::concat!(
+            "expected an integer literal from 0 to 64 as a bound, found `",
+            ::core::stringify!($n),
+            "`",
+        ))
     };
 }
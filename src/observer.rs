@@ -0,0 +1,61 @@
+/// Hooks into the progress of an `observed_cfor!`, for things like
+/// metrics, progress reporting and debugging.
+///
+/// Every method does nothing by default, so an observer only needs to
+/// implement the ones it is interested in. A loop calls `on_start`
+/// once before it begins, then `on_iteration` at the start of each
+/// iteration of its body, then `on_break` if the body ended the loop
+/// with a `break`, and finally `on_finish` however the loop ended
+/// (other than by a panic, or a `return` from the body). The last two
+/// are called after any `else` or `finally` block.
+///
+/// ```rust
+/// use cfor::LoopObserver;
+///
+/// #[derive(Default)]
+/// struct Longest {
+///     iterations: u64,
+///     broke: bool,
+/// }
+///
+/// impl LoopObserver for Longest {
+///     fn on_iteration(&mut self, iteration: u64) {
+///         self.iterations = iteration + 1;
+///     }
+///     fn on_break(&mut self) {
+///         self.broke = true;
+///     }
+/// }
+/// # let _ = Longest::default();
+/// ```
+pub trait LoopObserver {
+    /// Called before the loop begins, after its initialiser.
+    fn on_start(&mut self) {}
+
+    /// Called at the start of each iteration of the body, with the
+    /// number of iterations before this one.
+    fn on_iteration(&mut self, iteration: u64) {
+        let _ = iteration;
+    }
+
+    /// Called when the body ends the loop with a `break`.
+    fn on_break(&mut self) {}
+
+    /// Called once the loop has ended, after `on_break` (if any).
+    fn on_finish(&mut self) {}
+}
+
+impl<T: LoopObserver + ?Sized> LoopObserver for &mut T {
+    fn on_start(&mut self) {
+        (**self).on_start()
+    }
+    fn on_iteration(&mut self, iteration: u64) {
+        (**self).on_iteration(iteration)
+    }
+    fn on_break(&mut self) {
+        (**self).on_break()
+    }
+    fn on_finish(&mut self) {
+        (**self).on_finish()
+    }
+}
@@ -0,0 +1,50 @@
+//! The runtime parts of the parallel loops.
+
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Runs `body` for each index in `range`, split into `threads`
+/// contiguous chunks (at most), each on its own scoped thread.
+pub fn scoped(threads: usize, range: Range<usize>, body: &(dyn Fn(usize) + Sync)) {
+    let len = range.len();
+    if len == 0 {
+        return;
+    }
+    let threads = threads.max(1);
+    let chunk = (len + threads - 1) / threads;
+    thread::scope(|s| {
+        let mut start = range.start;
+        while start < range.end {
+            let end = range.end.min(start + chunk);
+            s.spawn(move || {
+                for i in start..end {
+                    body(i)
+                }
+            });
+            start = end;
+        }
+    });
+}
+
+/// Runs `body` for each index in `range` on `threads` scoped threads,
+/// which repeatedly take the next `chunk` indices that haven't been
+/// taken yet, until there are none left.
+pub fn dynamic(threads: usize, chunk: usize, range: Range<usize>, body: &(dyn Fn(usize) + Sync)) {
+    let chunk = chunk.max(1);
+    let threads = threads.max(1).min((range.len() + chunk - 1) / chunk);
+    let next = AtomicUsize::new(range.start);
+    thread::scope(|s| {
+        for _ in 0..threads {
+            s.spawn(|| loop {
+                let start = next.fetch_add(chunk, Ordering::Relaxed);
+                if start >= range.end {
+                    break;
+                }
+                for i in start..range.end.min(start.saturating_add(chunk)) {
+                    body(i)
+                }
+            });
+        }
+    });
+}
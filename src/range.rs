@@ -0,0 +1,608 @@
+use core::fmt;
+use core::iter::FusedIterator;
+use core::ops::{Add, Div, Mul, Sub};
+
+/// Stops `Integer` and `Float` from being implemented outside of this
+/// crate, so that the `num` feature can add its blanket impls without
+/// conflicting with anyone else's.
+mod sealed {
+    pub trait SealedInteger {}
+    pub trait SealedFloat {}
+}
+
+/// An integer type that a [`CRange`] can count with.
+///
+/// This is implemented for all of the primitive integer types, and,
+/// with the `num` feature, for every type implementing
+/// [`num_traits::PrimInt`](https://docs.rs/num-traits/0.2/num_traits/int/trait.PrimInt.html)
+/// (and `Debug`) with at most 128 bits, like a newtype wrapping a
+/// primitive. It can't be implemented for any other types.
+pub trait Integer: sealed::SealedInteger + Copy + PartialOrd + fmt::Debug {
+    /// `self` as two's complement, extended to 128 bits.
+    #[doc(hidden)]
+    fn to_bits(self) -> u128;
+    /// The inverse of `to_bits`, truncating.
+    #[doc(hidden)]
+    fn from_bits(bits: u128) -> Self;
+    /// Whether `self` is below zero.
+    #[doc(hidden)]
+    fn is_negative(self) -> bool;
+    /// The smallest and largest values of the type.
+    #[doc(hidden)]
+    fn bounds() -> (Self, Self);
+}
+
+#[cfg(not(feature = "num"))]
+macro_rules! impl_integer {
+    ($($signed: ty, $unsigned: ty;)*) => {
+        $(
+            impl sealed::SealedInteger for $signed {}
+            impl sealed::SealedInteger for $unsigned {}
+            impl Integer for $signed {
+                fn to_bits(self) -> u128 {
+                    self as i128 as u128
+                }
+                fn from_bits(bits: u128) -> $signed {
+                    bits as $signed
+                }
+                fn is_negative(self) -> bool {
+                    self < 0
+                }
+                fn bounds() -> ($signed, $signed) {
+                    (<$signed>::MIN, <$signed>::MAX)
+                }
+            }
+            impl Integer for $unsigned {
+                fn to_bits(self) -> u128 {
+                    self as u128
+                }
+                fn from_bits(bits: u128) -> $unsigned {
+                    bits as $unsigned
+                }
+                fn is_negative(self) -> bool {
+                    false
+                }
+                fn bounds() -> ($unsigned, $unsigned) {
+                    (<$unsigned>::MIN, <$unsigned>::MAX)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(not(feature = "num"))]
+impl_integer! {
+    i8, u8;
+    i16, u16;
+    i32, u32;
+    i64, u64;
+    i128, u128;
+    isize, usize;
+}
+
+#[cfg(feature = "num")]
+impl<T: num_traits::PrimInt + fmt::Debug> sealed::SealedInteger for T {}
+
+#[cfg(feature = "num")]
+impl<T: num_traits::PrimInt + fmt::Debug> Integer for T {
+    fn to_bits(self) -> u128 {
+        let bits = if T::min_value() < T::zero() {
+            self.to_i128().map(|n| n as u128)
+        } else {
+            self.to_u128()
+        };
+        bits.expect("a `CRange` needs an integer type of at most 128 bits")
+    }
+    fn from_bits(bits: u128) -> T {
+        // truncating to the width of `T` first, so the conversion
+        // can't fail.
+        let shift = 128 - T::zero().count_zeros().min(128);
+        let n = if T::min_value() < T::zero() {
+            T::from(((bits << shift) as i128) >> shift)
+        } else {
+            T::from((bits << shift) >> shift)
+        };
+        n.expect("a `CRange` needs an integer type of at most 128 bits")
+    }
+    fn is_negative(self) -> bool {
+        self < T::zero()
+    }
+    fn bounds() -> (T, T) {
+        (T::min_value(), T::max_value())
+    }
+}
+
+/// An iterator over an arithmetic progression, like the variable of a
+/// C `for` loop.
+///
+/// `CRange::new(start, end, step)` yields the same values as `i` in
+/// `for (i = start; i < end; i += step)`, or, with a negative step,
+/// `for (i = start; i > end; i += step)`. `CRange::inclusive` is the
+/// same, with `<=` and `>=` instead, and `CRange::down` and
+/// `CRange::down_inclusive` count down by a positive step, for
+/// unsigned types. Unlike those C loops, these never overflow, so
+/// each of them works for any bounds and step the type can represent,
+/// including reaching its largest or smallest value exactly. There's
+/// also `CRange::checked`, which has no end, stopping only before it
+/// would overflow, `CRange::saturating`, which is the same but stops
+/// at the bound of the type itself, and `CRange::wrapping`, which has
+/// a number of values, wrapping around on overflow.
+///
+/// It can be iterated from both ends, skips ahead in constant time
+/// with `nth`, and knows its exact length, so implements
+/// `ExactSizeIterator` for the integer types up to 16 bits on every
+/// target, the same as a `RangeInclusive`.
+///
+/// ```rust
+/// use cfor::CRange;
+///
+/// let odds: Vec<u32> = CRange::new(1, 10, 2).collect();
+/// assert_eq!(odds, [1, 3, 5, 7, 9]);
+///
+/// let down: Vec<i8> = CRange::inclusive(100, -128, -76).collect();
+/// assert_eq!(down, [100, 24, -52, -128]);
+///
+/// let top: Vec<u8> = CRange::down_inclusive(255, 0, 100).rev().collect();
+/// assert_eq!(top, [55, 155, 255]);
+/// ```
+#[derive(Clone)]
+pub struct CRange<T> {
+    start: T,
+    step: u128,
+    descending: bool,
+    // the indices of the first and last remaining values, with
+    // `None` once they're all used up.
+    remaining: Option<(u128, u128)>,
+    // for `saturating`, the index and value of the bound it stops at,
+    // which is off the progression.
+    clamp: Option<(u128, T)>,
+}
+
+impl<T: Integer> CRange<T> {
+    /// The values from `start` by `step` until just before `end`, like
+    /// `for (i = start; i < end; i += step)` for a positive step, or
+    /// `i > end` for a negative one.
+    ///
+    /// # Panics
+    ///
+    /// If `step` is zero.
+    pub fn new(start: T, end: T, step: T) -> CRange<T> {
+        CRange::signed(start, end, step, false)
+    }
+
+    /// The values from `start` by `step` up to and including `end`,
+    /// like `for (i = start; i <= end; i += step)` for a positive
+    /// step, or `i >= end` for a negative one.
+    ///
+    /// # Panics
+    ///
+    /// If `step` is zero.
+    pub fn inclusive(start: T, end: T, step: T) -> CRange<T> {
+        CRange::signed(start, end, step, true)
+    }
+
+    /// The values from `start` down by `step` until just before
+    /// `end`, like `for (i = start; i > end; i -= step)`.
+    ///
+    /// # Panics
+    ///
+    /// If `step` is zero or negative.
+    pub fn down(start: T, end: T, step: T) -> CRange<T> {
+        assert!(!step.is_negative(), "`CRange::down` needs a positive step");
+        CRange::with(start, end, step.to_bits(), true, false)
+    }
+
+    /// The values from `start` down by `step` to and including `end`,
+    /// like `for (i = start; i >= end; i -= step)`.
+    ///
+    /// # Panics
+    ///
+    /// If `step` is zero or negative.
+    pub fn down_inclusive(start: T, end: T, step: T) -> CRange<T> {
+        assert!(!step.is_negative(), "`CRange::down_inclusive` needs a positive step");
+        CRange::with(start, end, step.to_bits(), true, true)
+    }
+
+    /// The values from `start` by `step` for as long as they fit in
+    /// the type, like `for (i = start; ; i += step)` stopping when the
+    /// step overflows, rather than panicking or wrapping around. See
+    /// also `cfor_checked!`.
+    ///
+    /// ```rust
+    /// use cfor::CRange;
+    ///
+    /// let top: Vec<u8> = CRange::checked(200, 25).collect();
+    /// assert_eq!(top, [200, 225, 250]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `step` is zero.
+    pub fn checked(start: T, step: T) -> CRange<T> {
+        let (min, max) = T::bounds();
+        CRange::inclusive(start, if step.is_negative() { min } else { max }, step)
+    }
+
+    /// The values from `start` by `step`, and then the bound of the
+    /// type that the step would go past, like `for (i = start; ; i =
+    /// min(i + step, MAX))` stopping once `i` stops changing.
+    ///
+    /// ```rust
+    /// use cfor::CRange;
+    ///
+    /// let levels: Vec<u8> = CRange::saturating(200, 25).collect();
+    /// assert_eq!(levels, [200, 225, 250, 255]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `step` is zero.
+    pub fn saturating(start: T, step: T) -> CRange<T> {
+        let mut range = CRange::checked(start, step);
+        let (min, max) = T::bounds();
+        let bound = if step.is_negative() { min } else { max };
+        if let Some((first, last)) = range.remaining {
+            if range.value(last) != bound {
+                range.remaining = Some((first, last + 1));
+                range.clamp = Some((last + 1, bound));
+            }
+        }
+        range
+    }
+
+    /// The `n` values from `start` by `step`, wrapping around on
+    /// overflow, like `for (i = start, k = 0; k < n; i += step, k++)`
+    /// with C's unsigned arithmetic. It's the same as `CRange::new`
+    /// until it reaches the end of the type.
+    ///
+    /// ```rust
+    /// use cfor::CRange;
+    ///
+    /// let probes: Vec<u8> = CRange::wrapping(250, 3, 4).collect();
+    /// assert_eq!(probes, [250, 253, 0, 3]);
+    /// ```
+    pub fn wrapping(start: T, step: T, n: usize) -> CRange<T> {
+        CRange {
+            start,
+            step: step.to_bits(),
+            descending: false,
+            remaining: n.checked_sub(1).map(|last| (0, last as u128)),
+            clamp: None,
+        }
+    }
+
+    fn signed(start: T, end: T, step: T, inclusive: bool) -> CRange<T> {
+        if step.is_negative() {
+            CRange::with(start, end, step.to_bits().wrapping_neg(), true, inclusive)
+        } else {
+            CRange::with(start, end, step.to_bits(), false, inclusive)
+        }
+    }
+
+    fn with(start: T, end: T, step: u128, descending: bool, inclusive: bool) -> CRange<T> {
+        assert!(step != 0, "a `CRange` needs a non-zero step");
+
+        let (low, high) = if descending { (end, start) } else { (start, end) };
+        // the distance can't overflow as bits, even when it would as
+        // a `T`.
+        let remaining = if low < high {
+            let distance = high.to_bits().wrapping_sub(low.to_bits());
+            Some((0, if inclusive { distance / step } else { (distance - 1) / step }))
+        } else if low == high && inclusive {
+            Some((0, 0))
+        } else {
+            None
+        };
+
+        CRange {
+            start,
+            step,
+            descending,
+            remaining,
+            clamp: None,
+        }
+    }
+
+    fn value(&self, index: u128) -> T {
+        match self.clamp {
+            Some((clamp, value)) if clamp == index => return value,
+            _ => {}
+        }
+        let offset = index.wrapping_mul(self.step);
+        let bits = if self.descending {
+            self.start.to_bits().wrapping_sub(offset)
+        } else {
+            self.start.to_bits().wrapping_add(offset)
+        };
+        T::from_bits(bits)
+    }
+
+    fn count(&self) -> Option<u128> {
+        self.remaining.and_then(|(first, last)| (last - first).checked_add(1))
+    }
+}
+
+impl<T: Integer> Iterator for CRange<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let (first, last) = self.remaining?;
+        self.remaining = if first < last { Some((first + 1, last)) } else { None };
+        Some(self.value(first))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.remaining {
+            None => (0, Some(0)),
+            Some(_) => match self.count().and_then(|n| usize::try_from(n).ok()) {
+                Some(n) => (n, Some(n)),
+                None => (usize::MAX, None),
+            },
+        }
+    }
+
+    fn nth(&mut self, n: usize) -> Option<T> {
+        let (first, last) = self.remaining?;
+        match first.checked_add(n as u128) {
+            Some(index) if index <= last => {
+                self.remaining = Some((index, last));
+                self.next()
+            }
+            _ => {
+                self.remaining = None;
+                None
+            }
+        }
+    }
+
+    fn last(mut self) -> Option<T> {
+        self.next_back()
+    }
+}
+
+impl<T: Integer> DoubleEndedIterator for CRange<T> {
+    fn next_back(&mut self) -> Option<T> {
+        let (first, last) = self.remaining?;
+        self.remaining = if first < last { Some((first, last - 1)) } else { None };
+        Some(self.value(last))
+    }
+}
+
+impl<T: Integer> FusedIterator for CRange<T> {}
+
+macro_rules! impl_exact_size {
+    ($($t: ty),*) => {
+        $(impl ExactSizeIterator for CRange<$t> {})*
+    };
+}
+
+// the length of a range of a wider type might not fit in the `usize`
+// of some target, and an impl that depends on the target would let
+// code that compiles on one fail on another.
+impl_exact_size!(i8, u8, i16, u16);
+
+impl<T: Integer> fmt::Debug for CRange<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = f.debug_struct("CRange");
+        match self.remaining {
+            Some((first, last)) => s.field("next", &self.value(first)).field("last", &self.value(last)),
+            None => s.field("next", &None::<T>),
+        };
+        s.finish()
+    }
+}
+
+/// A floating point type that an [`FRange`] can count with.
+///
+/// This is implemented for `f32` and `f64`, and, with the `num`
+/// feature, for every type implementing
+/// [`num_traits::float::FloatCore`](https://docs.rs/num-traits/0.2/num_traits/float/trait.FloatCore.html)
+/// (and `Debug`). It can't be implemented for any other types.
+pub trait Float:
+    sealed::SealedFloat
+    + Copy
+    + PartialOrd + fmt::Debug + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self>
+{
+    /// `n` as `Self`, rounded if need be.
+    #[doc(hidden)]
+    fn from_usize(n: usize) -> Self;
+    /// `self` as a `usize`, truncated, and saturating (with NaN as
+    /// `0`).
+    #[doc(hidden)]
+    fn to_usize(self) -> usize;
+}
+
+#[cfg(not(feature = "num"))]
+macro_rules! impl_float {
+    ($($t: ty),*) => {
+        $(
+            impl sealed::SealedFloat for $t {}
+            impl Float for $t {
+                fn from_usize(n: usize) -> $t {
+                    n as $t
+                }
+                fn to_usize(self) -> usize {
+                    self as usize
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(not(feature = "num"))]
+impl_float!(f32, f64);
+
+#[cfg(feature = "num")]
+impl<T: num_traits::float::FloatCore + fmt::Debug> sealed::SealedFloat for T {}
+
+#[cfg(feature = "num")]
+impl<T: num_traits::float::FloatCore + fmt::Debug> Float for T {
+    fn from_usize(n: usize) -> T {
+        T::from(n).unwrap_or_else(T::infinity)
+    }
+    fn to_usize(self) -> usize {
+        match num_traits::ToPrimitive::to_usize(&self) {
+            Some(n) => n,
+            None if self > T::zero() => usize::MAX,
+            None => 0,
+        }
+    }
+}
+
+/// An iterator over evenly spaced floating point numbers.
+///
+/// Each value is computed directly, as `start + i * step` for the
+/// `i`th, rather than by adding `step` to the previous one, like the
+/// C loop `for (x = start; x < end; x += step)`. Those additions each
+/// round, and so the errors accumulate, often giving one value too
+/// many or too few at the end, or drifting far from the intended
+/// values over a long loop.
+///
+/// `FRange::new(start, end, step)` gives the values before `end` (or
+/// after, for a negative step), `FRange::with_count(start, step, n)`
+/// gives the first `n` values, and `FRange::linspace(start, end, n)`
+/// gives `n` values from `start` to exactly `end`, inclusive. Like
+/// [`CRange`], it can be iterated from both ends, and knows its exact
+/// length.
+///
+/// ```rust
+/// use cfor::FRange;
+///
+/// let times: Vec<f64> = FRange::new(0.0, 1.0, 0.1).collect();
+/// assert_eq!(times.len(), 10);
+/// assert_eq!(times[3], 0.30000000000000004);
+///
+/// // adding instead has drifted, and gets one more value.
+/// let mut added = vec![];
+/// let mut t = 0.0;
+/// while t < 1.0 {
+///     added.push(t);
+///     t += 0.1;
+/// }
+/// assert_eq!(added.len(), 11);
+///
+/// let edges: Vec<f32> = FRange::linspace(-1.0, 1.0, 5).collect();
+/// assert_eq!(edges, [-1.0, -0.5, 0.0, 0.5, 1.0]);
+/// ```
+#[derive(Clone)]
+pub struct FRange<T> {
+    start: T,
+    step: T,
+    // for `linspace`, the exact last value.
+    end: Option<T>,
+    len: usize,
+    front: usize,
+    back: usize,
+}
+
+impl<T: Float> FRange<T> {
+    /// The values `start + i * step` before `end`, like `for (x =
+    /// start; x < end; x += step)` for a positive step, or `x > end`
+    /// for a negative one.
+    ///
+    /// The number of values is at most `usize::MAX`, and is zero if
+    /// any of the arguments is NaN, or `step` is zero.
+    pub fn new(start: T, end: T, step: T) -> FRange<T> {
+        let zero = T::from_usize(0);
+        if step == zero {
+            return FRange::with_end(start, step, None, 0);
+        }
+        let before_end = |x: T| if step > zero { x < end } else { x > end };
+
+        // the estimate can be out by one either way, due to rounding,
+        // so it's corrected against the values themselves.
+        let mut len = ((end - start) / step).to_usize();
+        if len > 0 && !before_end(start + T::from_usize(len - 1) * step) {
+            len -= 1;
+        }
+        if len < usize::MAX && before_end(start + T::from_usize(len) * step) {
+            len += 1;
+        }
+        FRange::with_end(start, step, None, len)
+    }
+
+    /// The `n` values `start + i * step` for `i` in `0..n`.
+    pub fn with_count(start: T, step: T, n: usize) -> FRange<T> {
+        FRange::with_end(start, step, None, n)
+    }
+
+    /// `n` evenly spaced values from `start` to `end`, inclusive.
+    ///
+    /// When `n` is `1`, the only value is `start`.
+    pub fn linspace(start: T, end: T, n: usize) -> FRange<T> {
+        let step = if n > 1 { (end - start) / T::from_usize(n - 1) } else { T::from_usize(0) };
+        FRange::with_end(start, step, if n > 1 { Some(end) } else { None }, n)
+    }
+
+    fn with_end(start: T, step: T, end: Option<T>, len: usize) -> FRange<T> {
+        FRange {
+            start,
+            step,
+            end,
+            len,
+            front: 0,
+            back: len,
+        }
+    }
+
+    fn value(&self, index: usize) -> T {
+        match self.end {
+            Some(end) if index == self.len - 1 => end,
+            _ => self.start + T::from_usize(index) * self.step,
+        }
+    }
+}
+
+impl<T: Float> Iterator for FRange<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.front < self.back {
+            self.front += 1;
+            Some(self.value(self.front - 1))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.back - self.front;
+        (n, Some(n))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<T> {
+        self.front = self.front.saturating_add(n).min(self.back);
+        self.next()
+    }
+
+    fn last(mut self) -> Option<T> {
+        self.next_back()
+    }
+}
+
+impl<T: Float> DoubleEndedIterator for FRange<T> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.front < self.back {
+            self.back -= 1;
+            Some(self.value(self.back))
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: Float> ExactSizeIterator for FRange<T> {}
+
+impl<T: Float> FusedIterator for FRange<T> {}
+
+impl<T: Float> fmt::Debug for FRange<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = f.debug_struct("FRange");
+        if self.front < self.back {
+            s.field("next", &self.value(self.front)).field("last", &self.value(self.back - 1));
+        } else {
+            s.field("next", &None::<T>);
+        }
+        s.finish()
+    }
+}
@@ -0,0 +1,170 @@
+use core::ops::ControlFlow;
+
+/// What a loop body can return, to say whether the loop should go on.
+///
+/// `()` always goes on, and `ControlFlow::Break(value)` ends the loop
+/// with `value`, like `break value`.
+pub trait Flow {
+    /// The value the loop ends with, from a `ControlFlow::Break`.
+    type Break;
+
+    /// `self` as a `ControlFlow`.
+    fn into_control_flow(self) -> ControlFlow<Self::Break>;
+}
+
+impl Flow for () {
+    type Break = ();
+
+    fn into_control_flow(self) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+impl<B> Flow for ControlFlow<B> {
+    type Break = B;
+
+    fn into_control_flow(self) -> ControlFlow<B> {
+        self
+    }
+}
+
+/// Runs a C-style `for` loop, given as closures.
+///
+/// This is `for (s = init; cond(s); step(s)) { body(s) }`, where
+/// `body` returns a `ControlFlow`: `Continue(())` goes on to the step,
+/// like falling off the end of the body (or `continue`), while
+/// `Break(value)` ends the loop, and `run` returns `Some(value)`, like
+/// `break value` in `cfor_val!`. If the condition ends the loop
+/// instead, `run` returns `None`. A body that never breaks can also
+/// return `()`.
+///
+/// ```rust
+/// use std::ops::ControlFlow;
+///
+/// fn first_square_above(n: u64) -> Option<u64> {
+///     cfor::run(1, |i| *i <= n, |i| *i += 1, |i| {
+///         if *i * *i > n { ControlFlow::Break(*i) } else { ControlFlow::Continue(()) }
+///     })
+/// }
+///
+/// assert_eq!(first_square_above(50), Some(8));
+/// assert_eq!(first_square_above(0), None);
+/// ```
+///
+/// Since the body is a closure, a helper returning a `ControlFlow`
+/// can be shared between loops, and its result can be returned
+/// directly.
+///
+/// ```rust
+/// use std::ops::ControlFlow;
+///
+/// fn check(x: &i32) -> ControlFlow<String> {
+///     if *x < 0 { ControlFlow::Break(format!("{} is negative", x)) } else { ControlFlow::Continue(()) }
+/// }
+///
+/// let v = [3, 1, -4, 1];
+/// let error = cfor::run(0, |&i| i < v.len(), |i| *i += 1, |i| check(&v[*i]));
+/// assert_eq!(error.as_deref(), Some("-4 is negative"));
+/// ```
+pub fn run<S, R: Flow>(
+    init: S,
+    cond: impl FnMut(&S) -> bool,
+    step: impl FnMut(&mut S),
+    body: impl FnMut(&mut S) -> R,
+) -> Option<R::Break> {
+    Loop::new(init).while_(cond).step(step).run(body)
+}
+
+/// A C-style `for` loop built up as a value, rather than with a
+/// macro.
+///
+/// `Loop::new(init)` starts a loop with the given state, and
+/// `.while_(cond)` and `.step(step)` set its condition (by default,
+/// always true) and its step (by default, nothing). `.run(body)` then
+/// runs the loop, like `for (s = init; cond(s); step(s)) { body(s) }`,
+/// with the body ending the loop early by returning
+/// `ControlFlow::Break(value)`, which `run` returns as `Some(value)`.
+/// A body that returns `()` or `ControlFlow::Continue(())` goes on to
+/// the step, like `continue`. `.iter()` gives the states as an
+/// iterator instead, like [`iter`](crate::iter).
+///
+/// This is for loops whose parts are chosen at runtime, like from
+/// configuration, or are passed around.
+///
+/// ```rust
+/// use std::ops::ControlFlow;
+/// use cfor::Loop;
+///
+/// let mut seen = vec![];
+/// Loop::new(1).while_(|i| *i < 100).step(|i| *i *= 3).run(|i| seen.push(*i));
+/// assert_eq!(seen, [1, 3, 9, 27, 81]);
+///
+/// let doubling = Loop::new(1u64).step(|x| *x *= 2);
+/// let found = doubling.run(|x| {
+///     if *x > 1000 { ControlFlow::Break(*x) } else { ControlFlow::Continue(()) }
+/// });
+/// assert_eq!(found, Some(1024));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Loop<S, C = fn(&S) -> bool, F = fn(&mut S)> {
+    init: S,
+    cond: C,
+    step: F,
+}
+
+impl<S> Loop<S> {
+    /// A loop starting from `init`, with no condition or step yet.
+    pub fn new(init: S) -> Loop<S> {
+        Loop {
+            init,
+            cond: |_| true,
+            step: |_| {},
+        }
+    }
+}
+
+impl<S, C, F> Loop<S, C, F>
+where
+    C: FnMut(&S) -> bool,
+    F: FnMut(&mut S),
+{
+    /// Sets the condition, checked before each iteration.
+    pub fn while_<C2: FnMut(&S) -> bool>(self, cond: C2) -> Loop<S, C2, F> {
+        Loop {
+            init: self.init,
+            cond,
+            step: self.step,
+        }
+    }
+
+    /// Sets the step, run after each iteration.
+    pub fn step<F2: FnMut(&mut S)>(self, step: F2) -> Loop<S, C, F2> {
+        Loop {
+            init: self.init,
+            cond: self.cond,
+            step,
+        }
+    }
+
+    /// Runs the loop, with `body` run on the state for each
+    /// iteration, and returns the value the body ended the loop with,
+    /// if it did.
+    pub fn run<R: Flow>(mut self, mut body: impl FnMut(&mut S) -> R) -> Option<R::Break> {
+        let mut state = self.init;
+        while (self.cond)(&state) {
+            if let ControlFlow::Break(value) = body(&mut state).into_control_flow() {
+                return Some(value);
+            }
+            (self.step)(&mut state);
+        }
+        None
+    }
+
+    /// The states at the start of each iteration, as an iterator.
+    pub fn iter(self) -> crate::CFor<S, C, F>
+    where
+        S: Clone,
+    {
+        crate::iter(self.init, self.cond, self.step)
+    }
+}
@@ -0,0 +1,13 @@
+use core::time::Duration;
+
+/// A summary of how a loop ran, from `stats_cfor!`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LoopStats {
+    /// The number of iterations of the body that started.
+    pub iterations: u64,
+    /// Whether the body ended the loop with a `break`, rather than
+    /// the condition becoming false.
+    pub broke_early: bool,
+    /// How long the whole loop took.
+    pub elapsed: Duration,
+}
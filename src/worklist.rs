@@ -0,0 +1,49 @@
+#[cfg(feature = "std")]
+use std::collections::{BinaryHeap, VecDeque};
+
+/// A collection of work that a `worklist!` can take items from.
+///
+/// `pop_next` removes the next item to work on, or gives `None` once
+/// there's nothing left. The order is up to the collection: a
+/// `VecDeque` is a queue, taking from the front (for a breadth-first
+/// search), a `Vec` is a stack, taking from the back (depth-first),
+/// and a `BinaryHeap` takes the greatest item (like Dijkstra's
+/// algorithm, with `Reverse`).
+pub trait Worklist {
+    /// The items of work.
+    type Item;
+
+    /// Removes the next item to work on.
+    fn pop_next(&mut self) -> Option<Self::Item>;
+}
+
+#[cfg(feature = "std")]
+impl<T> Worklist for VecDeque<T> {
+    type Item = T;
+    fn pop_next(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> Worklist for Vec<T> {
+    type Item = T;
+    fn pop_next(&mut self) -> Option<T> {
+        self.pop()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Ord> Worklist for BinaryHeap<T> {
+    type Item = T;
+    fn pop_next(&mut self) -> Option<T> {
+        self.pop()
+    }
+}
+
+impl<W: Worklist + ?Sized> Worklist for &mut W {
+    type Item = W::Item;
+    fn pop_next(&mut self) -> Option<W::Item> {
+        (**self).pop_next()
+    }
+}
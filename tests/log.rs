@@ -0,0 +1,42 @@
+#![cfg(feature = "log")]
+
+use std::sync::Mutex;
+
+use cfor::logged_cfor;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+static MESSAGES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct Recorder;
+
+impl Log for Recorder {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Debug
+    }
+    fn log(&self, record: &Record) {
+        MESSAGES.lock().unwrap().push(format!("{} {}", record.level(), record.args()));
+    }
+    fn flush(&self) {}
+}
+
+#[test]
+fn logged_cfor() {
+    log::set_logger(&Recorder).unwrap();
+    log::set_max_level(LevelFilter::Debug);
+
+    let mut total = 0;
+    let line = line!() + 1;
+    logged_cfor!(vars = (i, total); 'outer: let mut i = 0; i < 10; i += 1; {
+        if i == 3 { continue 'outer }
+        total += i;
+    });
+    assert_eq!(total, 42);
+
+    let expected = [(0, 0, 0), (1, 1, 0), (2, 2, 1), (4, 4, 3), (8, 8, 25)]
+        .iter()
+        .map(|&(n, i, total)| {
+            format!("DEBUG cfor at {}:{}: iteration {}: i = {}, total = {}", file!(), line, n, i, total)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(*MESSAGES.lock().unwrap(), expected);
+}
@@ -0,0 +1,24 @@
+#![cfg(feature = "num")]
+
+use cfor::{CRange, FRange};
+
+// with `num`, the ranges go through `num_traits` instead, which
+// should give the same values.
+#[test]
+fn crange() {
+    assert_eq!(CRange::new(0u8, 10, 3).collect::<Vec<_>>(), [0, 3, 6, 9]);
+    assert_eq!(CRange::inclusive(i8::MAX, i8::MIN, i8::MIN).collect::<Vec<_>>(), [127, -1]);
+    let top: Vec<u128> = CRange::down_inclusive(u128::MAX, u128::MAX - 4, 2).collect();
+    assert_eq!(top, [u128::MAX, u128::MAX - 2, u128::MAX - 4]);
+    let all: Vec<i128> = CRange::inclusive(i128::MIN, i128::MAX, i128::MAX).collect();
+    assert_eq!(all, [i128::MIN, -1, i128::MAX - 1]);
+    assert_eq!(CRange::new(0usize, 100, 7).count(), 15);
+}
+
+#[test]
+fn frange() {
+    assert_eq!(FRange::new(0.0, 1.0, 0.1).len(), 10);
+    assert_eq!(FRange::new(0.0f32, f32::INFINITY, 1.0).len(), usize::MAX);
+    assert_eq!(FRange::new(0.0, f64::NAN, 1.0).len(), 0);
+    assert_eq!(FRange::linspace(-1.0f32, 1.0, 3).collect::<Vec<_>>(), [-1.0, 0.0, 1.0]);
+}
@@ -0,0 +1,34 @@
+#![cfg(all(feature = "paranoid", debug_assertions))]
+
+#[macro_use]
+extern crate cfor;
+
+#[test]
+#[should_panic(expected = "`i, j` unchanged for 1000000 iterations")]
+fn stagnant() {
+    let mut n = 0u64;
+    paranoid_cfor!{let mut i = 250u8, let mut j = vec![1]; n < 2_000_000; i = i.saturating_add(1), n += 1; {
+        j[0] = 1;
+    }}
+}
+
+#[test]
+fn changing() {
+    // the variables change, or can't be tracked
+    let mut n = 0u64;
+    paranoid_cfor!{let mut i = 0u64; i < 2_000_000; i += 1; { n += 1 }}
+    paranoid_cfor!{let mut x = 0u8, let mut it = (0..2_000_000).map(|x| x * 2); it.next().is_some(); (); {
+        x = 1;
+    }}
+    paranoid_cfor!{let mut i = 0u64, let stuck = 0; i < 2_000_000 + stuck; i += 1; {}}
+    assert_eq!(n, 2_000_000);
+}
+
+#[test]
+fn plain_unchecked() {
+    // only `paranoid_cfor!` is checked.
+    let mut n = 0u64;
+    cfor!{let mut i = 0u8; n < 2_000_000; i = i.saturating_add(0), n += 1; {
+        let _ = i;
+    }}
+}
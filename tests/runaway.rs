@@ -0,0 +1,25 @@
+// (in its own file, so that the limit set here is the one that's read
+// by the first loop.)
+#![cfg(all(feature = "runaway-detector", debug_assertions))]
+
+#[macro_use]
+extern crate cfor;
+
+#[test]
+#[should_panic(expected = "more than 1000 iterations of `let mut i = 0, let mut j = NoDebug; (i < 10); ()`, i = 0, j = ..")]
+fn runaway() {
+    struct NoDebug;
+
+    std::env::set_var("CFOR_RUNAWAY_LIMIT", "1000");
+
+    // fine
+    runaway_cfor!{let mut i = 0; i < 1000; i += 1; {}}
+
+    // plain loops aren't counted.
+    cfor!{let mut i = 0; i < 2000; i += 1; {}}
+
+    runaway_cfor!{let mut i = 0, let mut j = NoDebug; i < 10; (); {
+        if i > 10 { i += 1; j = NoDebug; }
+        let _ = &j;
+    }}
+}
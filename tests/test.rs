@@ -63,3 +63,2680 @@ fn multi_step() {
     assert_eq!(x, 10);
     assert_eq!(y, 100);
 }
+
+#[test]
+fn cdo_runs_once() {
+    let mut tick = 0;
+    cdo!({
+        tick += 1;
+    } while false);
+    assert_eq!(tick, 1);
+
+    let mut x = 0;
+    cdo!({
+        tick += 1;
+    } while x < 5; x += 1);
+    assert_eq!(tick, 6);
+    assert_eq!(x, 5);
+}
+
+#[test]
+#[should_panic]
+fn cdo_continue_updates() {
+    cdo!({
+        // we *should* hit the step expression.
+        continue
+    } while true; panic!())
+}
+
+#[test]
+fn cwhile() {
+    let mut i = 0;
+    let mut tick = 0;
+    cwhile!(i < 10; i += 1; {
+        if i % 2 == 0 { continue }
+        tick += 1;
+    });
+    assert_eq!(i, 10);
+    assert_eq!(tick, 5);
+
+    cwhile!(false; ; {
+        panic!()
+    });
+}
+
+#[test]
+fn cloop() {
+    let mut i = 0;
+    let mut tick = 0;
+    cloop!(i += 1; {
+        if i >= 10 { break }
+        if i % 2 == 0 { continue }
+        tick += 1;
+    });
+    assert_eq!(i, 10);
+    assert_eq!(tick, 5);
+}
+
+#[test]
+#[should_panic]
+fn cloop_continue_updates() {
+    cloop!(panic!(); {
+        // we *should* hit the step expression.
+        continue
+    })
+}
+
+#[test]
+fn labels() {
+    let mut tick = 0;
+    cfor!{'outer: let mut i = 0; i < 10; i += 1; {
+        cfor!{let mut j = 0; j < 10; j += 1; {
+            if j > i { continue 'outer }
+            if i == 5 { break 'outer }
+            tick += 1;
+        }}
+    }}
+    assert_eq!(tick, 1 + 2 + 3 + 4 + 5);
+
+    let mut x = 0;
+    cfor!{'a: ; x < 10; x += 1; {
+        continue 'a
+    }}
+    assert_eq!(x, 10);
+
+    let mut n = 0;
+    cfor!{'b: ;; n += 1; {
+        for j in 0.. {
+            if j == n { continue 'b }
+            if n == 3 { break 'b }
+        }
+    }}
+    assert_eq!(n, 3);
+}
+
+#[test]
+fn labelled_variants() {
+    let mut x = 0;
+    cdo!('a: {
+        for j in 0.. { if j > 0 { continue 'a } }
+    } while x < 10; x += 1);
+    assert_eq!(x, 10);
+
+    x = 0;
+    cwhile!('b: x < 10; x += 1; {
+        for j in 0.. { if j > 0 { continue 'b } }
+    });
+    assert_eq!(x, 10);
+
+    x = 0;
+    cloop!('c: x += 1; {
+        for j in 0.. {
+            if x == 10 { break 'c }
+            if j > 0 { continue 'c }
+        }
+    });
+    assert_eq!(x, 10);
+}
+
+#[test]
+#[should_panic]
+fn labelled_continue_updates() {
+    cfor!('outer: let i = 0; i < 10; panic!(); {
+        let mut j = 0;
+        while j < 10 {
+            j += 1;
+            // we *should* hit the outer step expression, despite
+            // the inner `while`.
+            if j > 1 { continue 'outer }
+        }
+    })
+}
+
+#[test]
+fn labelled_continue_from_nested_loops() {
+    let mut steps = 0;
+    let mut bodies = 0;
+    cfor!{'outer: let mut i = 0; i < 5; { i += 1; steps += 1 }; {
+        bodies += 1;
+        for _ in 0..3 {
+            let mut k = 0;
+            while k < 3 {
+                k += 1;
+                if k == 2 { continue 'outer }
+            }
+        }
+        unreachable!()
+    }}
+    assert_eq!(bodies, 5);
+    assert_eq!(steps, 5);
+}
+
+#[test]
+fn break_with_value() {
+    let found = cfor_val!{let mut i = 0; i < 10; i += 1; {
+        if i * i > 20 { break i }
+    }};
+    assert_eq!(found, Some(5));
+
+    let found = cfor_val!{let mut i = 0; i < 10; i += 1; {
+        if i > 20 { break i }
+    }};
+    assert_eq!(found, None);
+
+    let unit = cfor_val!{;; (); {
+        break
+    }};
+    assert_eq!(unit, Some(()));
+
+    // `continue` still runs the step, and labels work.
+    let mut steps = 0;
+    let found = cfor_val!{'outer: let mut i = 0; i < 10; { i += 1; steps += 1 }; {
+        for j in 0..i {
+            if j == 3 { break 'outer (i, j) }
+            if i < 4 && j > 0 { continue 'outer }
+        }
+        continue
+    }};
+    assert_eq!(found, Some((4, 3)));
+    assert_eq!(steps, 4);
+}
+
+#[test]
+fn else_clause() {
+    let mut hit_else = false;
+    cfor!{let mut i = 0; i < 10; i += 1; {
+        if i % 2 == 0 { continue }
+    } else {
+        assert_eq!(i, 10);
+        hit_else = true;
+    }}
+    assert!(hit_else);
+
+    cfor!{let mut i = 0; i < 10; i += 1; {
+        if i == 5 { break }
+    } else {
+        panic!()
+    }}
+
+    cfor!{'outer: let mut i = 0; i < 10; i += 1; {
+        for j in 0..i {
+            if j == 3 { break 'outer }
+        }
+    } else {
+        panic!()
+    }}
+
+    hit_else = false;
+    cfor!{; false; ; {
+        panic!()
+    } else {
+        hit_else = true;
+    }}
+    assert!(hit_else);
+}
+
+#[test]
+fn finally_clause() {
+    let mut finals = 0;
+    cfor!{let mut i = 0; i < 10; i += 1; {
+        if i == 5 { break }
+    } finally {
+        assert_eq!(i, 5);
+        finals += 1;
+    }}
+    cfor!{let mut i = 0; i < 10; i += 1; {
+    } finally {
+        assert_eq!(i, 10);
+        finals += 1;
+    }}
+    assert_eq!(finals, 2);
+
+    let mut order = vec![];
+    cfor!{; false; ; {
+    } else {
+        order.push("else");
+    } finally {
+        order.push("finally");
+    }}
+    cfor!{;; (); {
+        break
+    } else {
+        order.push("else");
+    } finally {
+        order.push("finally");
+    }}
+    assert_eq!(order, ["else", "finally", "finally"]);
+
+    let found = cfor_val!{let mut i = 0; i < 10; i += 1; {
+        if i == 3 { break i * 2 }
+    } finally {
+        finals += i;
+    }};
+    assert_eq!(found, Some(6));
+    assert_eq!(finals, 5);
+}
+
+#[test]
+fn hygiene() {
+    // the names used internally by the expansion don't interfere
+    // with the body.
+    let _first = 10;
+    let first = 20;
+    let _continue = 30;
+    let completed = 40;
+    let mut tick = 0;
+    cfor!{let mut i = 0; i < 3; i += 1; {
+        assert_eq!((_first, first, _continue, completed), (10, 20, 30, 40));
+        tick += 1;
+    } else {
+        assert_eq!(completed, 40);
+    }}
+    assert_eq!(tick, 3);
+
+    let value = cfor_val!{let mut first = 0; first < 3; first += 1; {
+        let value = first * 2;
+        if value == 4 { break value }
+    }};
+    assert_eq!(value, Some(4));
+
+    cdo!({
+        assert_eq!(first, 20);
+        tick += 1;
+    } while tick < 5);
+    assert_eq!(tick, 5);
+}
+
+#[test]
+fn block_step() {
+    let mut steps = vec![];
+    cfor!{let mut i = 0; i < 5; {
+        steps.push(i);
+        i += 1;
+    }; {
+        if i % 2 == 0 { continue }
+    }}
+    assert_eq!(steps, [0, 1, 2, 3, 4]);
+
+    // a block can be mixed with other steps.
+    let mut j = 0;
+    cfor!{let mut i = 0; i < 5; { i += 1; i += 1 }, j += 1; {}}
+    assert_eq!(j, 3);
+}
+
+#[test]
+fn block_init() {
+    let v = [1, 2, 3, 4, 5, 6, 7];
+    let mut seen = vec![];
+    cfor!{{ let n = v.len(); let mut i = n / 2; }; i < n; i += 1; {
+        seen.push(v[i]);
+    }}
+    assert_eq!(seen, [4, 5, 6, 7]);
+
+    // no trailing semicolon, and an empty block.
+    let mut tick = 0;
+    cfor!{{ let mut i = 0 }; i < 3; i += 1; { tick += 1; }}
+    cfor!{{}; tick < 5; tick += 1; {}}
+    assert_eq!(tick, 5);
+}
+
+#[test]
+fn block_condition() {
+    let v = [3, 1, 4, 0, 5];
+    let mut seen = vec![];
+    cfor!{let mut i = 0; { let x = v.get(i); matches!(x, Some(&x) if x != 0) }; i += 1; {
+        if i == 1 { continue }
+        seen.push(v[i]);
+    }}
+    assert_eq!(seen, [3, 4]);
+
+    let mut n = 0;
+    cwhile!({ let m = n * n; m < 50 }; n += 1; {});
+    assert_eq!(n, 8);
+}
+
+#[test]
+fn pattern_condition() {
+    let xs = [1, 2, 3, 4, 5];
+    let mut sum = 0;
+    let mut counter = 0;
+    cfor!{let mut it = xs.iter(); let Some(x) = it.next(); counter += 1; {
+        if x % 2 == 0 { continue }
+        sum += x;
+    }}
+    assert_eq!(sum, 9);
+    assert_eq!(counter, 5);
+
+    // the bindings are visible in the step.
+    let mut total = 0;
+    cfor!{let mut it = xs.iter(); let Some(&x) = it.next(); total += x; {}}
+    assert_eq!(total, 15);
+
+    let mut it = xs.iter();
+    let mut seen = vec![];
+    cwhile!(let Some(x) = it.next(); ; {
+        seen.push(*x);
+    });
+    assert_eq!(seen, xs);
+
+    let found = cfor_val!{let mut it = xs.iter(); let Some(x) = it.next(); ; {
+        if *x > 3 { break x }
+    }};
+    assert_eq!(found, Some(&4));
+
+    let mut hit_else = false;
+    cfor!{let mut it = xs.iter(); let Some(_) = it.next(); ; {
+    } else {
+        hit_else = true;
+    }}
+    assert!(hit_else);
+}
+
+#[test]
+fn let_chain_condition() {
+    let v = [Some(1), Some(3), Some(5), None, Some(2)];
+    let mut seen = vec![];
+    cfor!{let mut i = 0; let Some(&Some(x)) = v.get(i) && x < 4; i += 1; {
+        seen.push(x);
+    }}
+    assert_eq!(seen, [1, 3]);
+
+    // a `let` can come later in the chain, and see earlier bindings.
+    seen.clear();
+    cfor!{let mut i = 0; i < 10 && let Some(&Some(x)) = v.get(i) && let y = x * 2 && y > 0; i += 1; {
+        if y == 6 { continue }
+        seen.push(y);
+    }}
+    assert_eq!(seen, [2, 10]);
+
+    // `&&` and `||` without any `let`s keep their normal precedence.
+    let mut tick = 0;
+    cfor!{let mut i = 0; i < 3 || false && i < 10; i += 1; {
+        tick += 1;
+    }}
+    assert_eq!(tick, 3);
+}
+
+#[test]
+fn fallible() {
+    use std::num::ParseIntError;
+
+    fn parse(words: &[&str]) -> Result<Vec<i32>, ParseIntError> {
+        let mut out = vec![];
+        try_cfor!{<ParseIntError> let mut i = 0; i < words.len(); i += 1; {
+            if words[i].is_empty() { continue }
+            out.push(words[i].parse()?);
+        }}?;
+        Ok(out)
+    }
+    assert_eq!(parse(&["1", "", "3"]), Ok(vec![1, 3]));
+    assert!(parse(&["1", "x", "3"]).is_err());
+
+    // `?` in each part of the header.
+    let header = |init: &str, cond: &str, step: &str| {
+        let r: Result<(), ParseIntError> = try_cfor!{let mut i = init.parse::<i32>()?;
+                                                     i < cond.parse()?;
+                                                     i += step.parse::<i32>()?; {}};
+        r
+    };
+    assert!(header("0", "3", "1").is_ok());
+    assert!(header("x", "3", "1").is_err());
+    assert!(header("0", "x", "1").is_err());
+    assert!(header("0", "3", "x").is_err());
+
+    // `finally` runs on errors too.
+    let mut finals = 0;
+    let r: Result<(), ParseIntError> = try_cfor!{'outer: let mut i = 0; i < 3; i += 1; {
+        for j in 0..2 {
+            if i == 2 { "x".parse::<i32>()?; }
+            if j > 0 { continue 'outer }
+        }
+    } finally {
+        finals += 1;
+    }};
+    assert!(r.is_err());
+    assert_eq!(finals, 1);
+
+    let r: Result<(), ParseIntError> = try_cfor!{;; (); { break } finally { finals += 1; }};
+    assert!(r.is_ok());
+    assert_eq!(finals, 2);
+
+    let r: Result<(), ParseIntError> = try_cfor!{; false; ; {} else { finals += 1; }};
+    assert!(r.is_ok());
+    assert_eq!(finals, 3);
+}
+
+#[test]
+fn parenthesised_header() {
+    let mut tick = 0;
+    cfor!((let mut i = 1; i <= 0b1000_0000; i *= 2) {
+        tick += 1;
+    });
+    assert_eq!(tick, 8);
+
+    cfor!{'outer: (let mut i = 0; i < 10; i += 1) {
+        if i == 5 { break 'outer }
+        tick += 1;
+    } finally {
+        assert_eq!(i, 5);
+    }}
+    assert_eq!(tick, 13);
+
+    cfor!{(;;) { break }}
+    cfor!{(; false;) { panic!() }}
+
+    let found = cfor_val!((let mut i = 0; i < 10; i += 1) {
+        if i * i > 10 { break i }
+    });
+    assert_eq!(found, Some(4));
+
+    // the old unit initialiser isn't mistaken for a header.
+    cfor!{();; (); { break }}
+}
+
+#[test]
+fn trailing_separators() {
+    let mut v = vec![];
+    cfor!{let mut i = 0, let mut j = 10,; i < j; i += 1, j -= 1,; {
+        v.push((i, j));
+    };}
+    assert_eq!(v, [(0, 10), (1, 9), (2, 8), (3, 7), (4, 6)]);
+
+    let mut n = 0;
+    cfor!{let mut i = 0; i < 3; i += 1; {
+        n += i;
+    } else {
+        n += 10;
+    } finally {
+        n += 100;
+    };}
+    assert_eq!(n, 113);
+
+    let found = cfor_val!{let mut i = 0; i < 10; i += 1,; {
+        if i == 3 { break i }
+    };};
+    assert_eq!(found, Some(3));
+
+    let mut tick = 0;
+    cdo!{{ tick += 1 } while tick < 3;}
+    assert_eq!(tick, 3);
+    cdo!{{ tick += 1 } while tick < 10; tick += 1,}
+    assert_eq!(tick, 11);
+}
+
+#[test]
+fn crepeat() {
+    let mut count = 0;
+    crepeat!(5 { count += 1 });
+    assert_eq!(count, 5);
+
+    // evaluated once
+    let mut evals = 0;
+    crepeat!({ evals += 1; 4 } { count += 1 });
+    assert_eq!((evals, count), (1, 9));
+
+    crepeat!(0 { panic!() });
+
+    let mut v = vec![];
+    crepeat!(i in v.len() + 4 {
+        if i == 1 { continue }
+        if i == 3 { break }
+        v.push(i);
+    });
+    assert_eq!(v, [0, 2]);
+
+    // modifying the index doesn't affect the loop
+    let mut seen = vec![];
+    crepeat!(mut i in 3 {
+        i *= 10;
+        seen.push(i);
+    });
+    assert_eq!(seen, [0, 10, 20]);
+
+    let mut pairs = vec![];
+    crepeat!('outer: i in 3 {
+        crepeat!(j in 3 {
+            if j > i { continue 'outer }
+            pairs.push((i, j));
+        });
+    });
+    assert_eq!(pairs, [(0, 0), (1, 0), (1, 1), (2, 0), (2, 1), (2, 2)]);
+}
+
+#[test]
+fn countdown() {
+    let mut v = vec![];
+    countdown!(i from 5u32 {
+        if i == 3 { continue }
+        v.push(i);
+    });
+    assert_eq!(v, [4, 2, 1, 0]);
+
+    // no underflow at zero
+    countdown!(_i from 0usize { panic!() });
+
+    let mut v = vec![];
+    countdown!('outer: i from 3u8 {
+        countdown!(mut j from 3u8 {
+            if j == i { continue 'outer }
+            j *= 10;
+            v.push((i, j));
+        });
+    });
+    assert_eq!(v, [(1, 20), (0, 20), (0, 10)]);
+}
+
+#[test]
+fn repeat_until() {
+    // runs once even though the condition is true from the start
+    let mut runs = 0;
+    repeat_until!(true; { runs += 1 });
+    assert_eq!(runs, 1);
+
+    let mut i = 0;
+    let mut v = vec![];
+    repeat_until!(i >= 5; i += 1 {
+        if i % 2 == 1 { continue }
+        v.push(i);
+    });
+    assert_eq!(v, [0, 2, 4]);
+    assert_eq!(i, 5);
+
+    let mut n = 0;
+    repeat_until!('outer: n > 100; n += 1 {
+        repeat_until!(false; {
+            if n % 10 == 0 { n += 10; continue 'outer }
+            n += 1;
+        });
+    });
+    assert_eq!(n, 111);
+}
+
+#[test]
+fn retry() {
+    let mut calls = 0;
+    let r: Result<u32, u32> = retry!(attempts = 5; {
+        calls += 1;
+        if calls == 3 { Ok(30) } else { Err(calls) }
+    });
+    assert_eq!((r, calls), (Ok(30), 3));
+
+    let mut calls = 0;
+    let r: Result<(), u32> = retry!(attempts = 4; {
+        calls += 1;
+        Err(calls)
+    });
+    assert_eq!((r, calls), (Err(4), 4));
+
+    // at least one attempt
+    let mut calls = 0;
+    let r: Result<(), ()> = retry!(attempts = 0; { calls += 1; Err(()) });
+    assert_eq!((r, calls), (Err(()), 1));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn retry_backoff() {
+    use cfor::Backoff;
+    use std::time::{Duration, Instant};
+
+    let ms = Duration::from_millis;
+    let mut b = Backoff::new(ms(1)).cap(ms(5));
+    let delays: Vec<_> = (0..5).map(|_| b.next_delay()).collect();
+    assert_eq!(delays, [ms(1), ms(2), ms(4), ms(5), ms(5)]);
+
+    // no cap: this shouldn't overflow
+    let mut b = Backoff::new(Duration::from_secs(1)).multiplier(1e10);
+    for _ in 0..10 { b.next_delay(); }
+    assert_eq!(b.next_delay(), Duration::MAX);
+
+    let mut b = Backoff::new(ms(10)).jitter(true);
+    for i in 0..10 {
+        assert!(b.next_delay() <= ms(10) * (1 << i));
+    }
+
+    let mut calls = 0;
+    let start = Instant::now();
+    let r: Result<(), ()> = retry!(attempts = 4, backoff = Backoff::new(ms(5)); {
+        calls += 1;
+        Err(())
+    });
+    // 5 + 10 + 20, and not 40 more after the last attempt
+    let elapsed = start.elapsed();
+    assert_eq!((r, calls), (Err(()), 4));
+    assert!(elapsed >= ms(35), "{:?}", elapsed);
+}
+
+#[cfg(feature = "libc")]
+#[test]
+fn retry_eintr() {
+    let mut calls = 0;
+    let r = retry_eintr!({ calls += 1; unsafe { libc::close(-1) } });
+    // EBADF isn't retried
+    assert_eq!((r, calls), (-1, 1));
+
+    #[cfg(target_os = "linux")]
+    {
+        let mut calls = 0;
+        let r = retry_eintr!({
+            calls += 1;
+            if calls < 3 {
+                unsafe { *libc::__errno_location() = libc::EINTR }
+                -1
+            } else {
+                5
+            }
+        });
+        assert_eq!((r, calls), (5, 3));
+    }
+}
+
+#[test]
+fn spin_wait() {
+    let mut checks = 0;
+    spin_wait!({ checks += 1; checks == 10 });
+    assert_eq!(checks, 10);
+
+    let mut checks = 0;
+    spin_wait!({ checks += 1; checks == 10 }; 3);
+    assert_eq!(checks, 10);
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    let counter = Arc::new(AtomicUsize::new(0));
+    let threads: Vec<_> = (0..4).map(|_| {
+        let counter = counter.clone();
+        std::thread::spawn(move || { counter.fetch_add(1, Ordering::AcqRel); })
+    }).collect();
+    spin_wait!(counter.load(Ordering::Acquire) == 4; 10);
+    for t in threads { t.join().unwrap() }
+}
+
+#[test]
+fn busy_wait_for() {
+    use cfor::Timeout;
+
+    let mut polls = 0;
+    assert_eq!(busy_wait_for!({ polls += 1; polls == 3 }; 5), Ok(()));
+    assert_eq!(polls, 3);
+
+    let mut polls = 0;
+    assert_eq!(busy_wait_for!({ polls += 1; false }; 5), Err(Timeout));
+    assert_eq!(polls, 5);
+
+    assert_eq!(busy_wait_for!(true; 0), Err(Timeout));
+    assert_eq!(Timeout.to_string(), "timed out");
+}
+
+#[test]
+fn watchdog_cfor() {
+    let mut fed_at = vec![];
+    let mut v = vec![];
+    watchdog_cfor!(every 3 => fed_at.push(v.len()); let mut i = 0; i < 10; i += 1; {
+        if i == 4 { continue }
+        v.push(i);
+    });
+    assert_eq!(v, [0, 1, 2, 3, 5, 6, 7, 8, 9]);
+    // at the start of iterations 0, 3, 6 and 9
+    assert_eq!(fed_at, [0, 3, 5, 8]);
+
+    let mut feeds = 0;
+    let mut n = 0;
+    watchdog_cfor!(every 1 => feeds += 1; 'outer: { let mut i = 0; }; i < 3; { i += 1 }; {
+        crepeat!(3 {
+            n += 1;
+            continue 'outer;
+        });
+    } else {
+        n += 100;
+    } finally {
+        n += 1000;
+    });
+    assert_eq!((feeds, n), (3, 1103));
+}
+
+#[test]
+#[should_panic(expected = "`watchdog_cfor!` needs an `every` of at least 1")]
+fn watchdog_cfor_every_zero() {
+    let mut feeds = 0;
+    watchdog_cfor!(every 0 => feeds += 1; let mut i = 0; i < 10; i += 1; {});
+    assert_eq!(feeds, 0);
+}
+
+#[cfg(feature = "nb")]
+#[test]
+fn block_with_step() {
+    let mut polls = 0;
+    let mut steps = 0;
+    let r: nb::Result<u8, ()> = block_with_step!(;; steps += 1; {
+        polls += 1;
+        if polls < 5 { Err(nb::Error::WouldBlock) } else { Ok(7) }
+    });
+    assert_eq!((r, polls, steps), (Ok(7), 5, 4));
+
+    let r: nb::Result<u8, &str> = block_with_step!(let mut i = 0; i < 10; i += 1; {
+        if i == 2 { Err(nb::Error::Other("broken")) } else { Err(nb::Error::WouldBlock) }
+    });
+    assert_eq!(r, Err(nb::Error::Other("broken")));
+
+    let r: nb::Result<u8, ()> = block_with_step!(let mut i = 0; i < 10; i += 1; {
+        Err(nb::Error::WouldBlock)
+    });
+    assert_eq!(r, Err(nb::Error::WouldBlock));
+}
+
+#[test]
+fn fixed_timestep() {
+    let dt = 0.25;
+    let mut acc = 0.0;
+    let mut updates = vec![];
+
+    assert_eq!(fixed_timestep!(acc, 0.6, dt; { updates.push(1) }), 2);
+    assert_eq!(fixed_timestep!(acc, 0.1, dt; { updates.push(2) }), 0);
+    // the skipped update still uses up its time
+    let mut n = 0;
+    assert_eq!(fixed_timestep!(acc, 0.6, dt; {
+        n += 1;
+        if n == 2 { continue }
+        updates.push(3);
+    }), 3);
+    assert_eq!(updates, [1, 1, 3, 3]);
+    assert!((acc - 0.05f64).abs() < 1e-9);
+
+    struct World { acc: u32 }
+    let mut world = World { acc: 0 };
+    assert_eq!(fixed_timestep!(world.acc, 35, 10; {}), 3);
+    assert_eq!(world.acc, 5);
+}
+
+#[test]
+fn budgeted_cfor() {
+    use std::time::Duration;
+
+    let mut v = vec![];
+    let n = budgeted_cfor!(budget = Duration::from_secs(60); let mut i = 0; i < 5; i += 1; {
+        if i == 2 { continue }
+        v.push(i);
+    });
+    assert_eq!((n, v), (5, vec![0, 1, 3, 4]));
+
+    // the budget is checked before the condition, so nothing is lost
+    let mut queue = vec![1, 2, 3];
+    let n = budgeted_cfor!(budget = Duration::ZERO; ; let Some(_) = queue.pop(); (); {});
+    assert_eq!((n, queue.len()), (0, 3));
+
+    let mut iterations = 0;
+    let n = budgeted_cfor!(budget = Duration::from_millis(20); 'outer: ;; (); {
+        iterations += 1;
+        std::thread::sleep(Duration::from_millis(5));
+        crepeat!(2 { continue 'outer });
+    } else {
+        iterations += 1000;
+    });
+    assert!((1..=4).contains(&n), "{}", n);
+    assert_eq!(iterations, n + 1000);
+}
+
+#[test]
+fn cfor_timeout() {
+    use cfor::Timeout;
+    use std::time::Duration;
+
+    let mut n = 0;
+    let r = cfor_timeout!(timeout = Duration::from_secs(60); let mut i = 0; i < 5; i += 1; { n += i });
+    assert_eq!((r, n), (Ok(()), 10));
+
+    // a `break` isn't a timeout
+    let r = cfor_timeout!(timeout = Duration::from_secs(60);;; (); { break });
+    assert_eq!(r, Ok(()));
+
+    let mut iterations = 0;
+    let r = cfor_timeout!(timeout = Duration::from_millis(10); 'outer: ;; (); {
+        iterations += 1;
+        std::thread::sleep(Duration::from_millis(2));
+        crepeat!(2 { continue 'outer });
+    });
+    assert_eq!(r, Err(Timeout));
+    assert!(iterations >= 1);
+}
+
+#[test]
+fn cfor_bounded() {
+    use cfor::Exhausted;
+
+    // exactly at the limit isn't exhausted
+    let mut v = vec![];
+    let r = cfor_bounded!(max = 3; let mut i = 0; i < 3; i += 1; { v.push(i) });
+    assert_eq!((r, v), (Ok(()), vec![0, 1, 2]));
+
+    let mut v = vec![];
+    let r = cfor_bounded!(max = 3; let mut i = 0; i < 4; i += 1; {
+        if i == 1 { continue }
+        v.push(i);
+    });
+    assert_eq!((r, v), (Err(Exhausted), vec![0, 2]));
+
+    let mut n = 0;
+    let r = cfor_bounded!(max = 10; 'outer: ;; n += 1; {
+        crepeat!(2 { continue 'outer });
+    });
+    assert_eq!((r, n), (Err(Exhausted), 10));
+    assert_eq!(Exhausted.to_string(), "iteration limit reached");
+}
+
+#[test]
+fn counted_cfor() {
+    assert_eq!(counted_cfor!(let mut i = 0; i < 5; i += 1; {}), 5);
+    assert_eq!(counted_cfor!(let mut i = 0; i < 0; i += 1; {}), 0);
+
+    // partial iterations count, however they end
+    let n = counted_cfor!(let mut i = 0; i < 10; i += 1; {
+        if i % 2 == 0 { continue }
+        if i == 5 { break }
+    });
+    assert_eq!(n, 6);
+
+    let mut total = 0;
+    let n = counted_cfor!('outer: let mut i = 0; i < 3; i += 1; {
+        crepeat!(j in 3 {
+            if j > i { continue 'outer }
+            total += 1;
+        });
+    } else {
+        total *= 10;
+    });
+    assert_eq!((n, total), (3, 60));
+}
+
+#[test]
+fn timed_cfor() {
+    use std::thread;
+    use std::time::Duration;
+
+    let mut n = 0;
+    let elapsed = timed_cfor!('outer: let mut i = 0; i < 3; i += 1; {
+        n += 1;
+        if i == 1 { continue 'outer }
+        thread::sleep(Duration::from_millis(10));
+    } finally {
+        thread::sleep(Duration::from_millis(10));
+    });
+    assert_eq!(n, 3);
+    assert!(elapsed >= Duration::from_millis(30));
+}
+
+#[test]
+fn observed_cfor() {
+    use cfor::LoopObserver;
+
+    #[derive(Default)]
+    struct Log(Vec<String>);
+    impl LoopObserver for Log {
+        fn on_start(&mut self) {
+            self.0.push("start".to_string())
+        }
+        fn on_iteration(&mut self, iteration: u64) {
+            self.0.push(iteration.to_string())
+        }
+        fn on_break(&mut self) {
+            self.0.push("break".to_string())
+        }
+        fn on_finish(&mut self) {
+            self.0.push("finish".to_string())
+        }
+    }
+
+    let mut log = Log::default();
+    observed_cfor!(observer = &mut log; let mut i = 0; i < 3; i += 1; {
+        if i == 1 { continue }
+    });
+    assert_eq!(log.0, ["start", "0", "1", "2", "finish"]);
+
+    let mut log = Log::default();
+    observed_cfor!(observer = &mut log; 'outer: let mut i = 0; ; i += 1; {
+        crepeat!(2 {
+            if i == 1 { break 'outer }
+        });
+    });
+    assert_eq!(log.0, ["start", "0", "1", "break", "finish"]);
+
+    let mut log = Log::default();
+    observed_cfor!(observer = &mut log; let mut i = 0; i < 0; i += 1; {});
+    assert_eq!(log.0, ["start", "finish"]);
+
+    // the default hooks do nothing
+    struct Nothing;
+    impl LoopObserver for Nothing {}
+    let mut n = 0;
+    observed_cfor!(observer = Nothing; let mut i = 0; i < 3; i += 1; { n += i });
+    assert_eq!(n, 3);
+}
+
+#[cfg(feature = "indicatif")]
+#[test]
+fn progress_cfor() {
+    use indicatif::ProgressBar;
+
+    let lengths = |bar: &ProgressBar| (bar.position(), bar.length());
+
+    let bar = ProgressBar::hidden();
+    progress_cfor!(bar = bar; let mut i = 0; i < 10; i += 1; {});
+    assert_eq!(lengths(&bar), (10, Some(10)));
+    // the bar is shared, and so keeps going
+    progress_cfor!(bar = bar; 'outer: (let mut i = 5u8; i <= 9; i += 2) {
+        if i == 7 { continue 'outer }
+    });
+    assert_eq!(lengths(&bar), (13, Some(3)));
+
+    // headers that aren't simple ranges still tick
+    let bar = ProgressBar::hidden();
+    progress_cfor!(bar = bar; let mut i = 0, let mut j = 0; i < 4; i += 1, j += 2; {
+        assert_eq!(j, 2 * i);
+    });
+    progress_cfor!(bar = bar; let mut x = 1.0; x < 4.0; x += 1.0; {});
+    progress_cfor!(bar = bar; let mut i = 0; i < 4 && let Some(_) = Some(i); i += 1; {});
+    assert_eq!(lengths(&bar), (11, None));
+
+    // nor are ones where the `<` isn't the whole condition, or the
+    // variables differ.
+    let bar = ProgressBar::hidden();
+    let stop = false;
+    progress_cfor!(bar = bar; let mut i = 0; i < 10 && !stop; i += 1; {});
+    progress_cfor!(bar = bar; let mut i = 0; i < 3 || stop; i += 1; {});
+    let mut j = 0;
+    progress_cfor!(bar = bar; let mut i = 0; j < 2; i += 1; {
+        j += 1;
+        assert!(i < 2);
+    });
+    let mut k = 0;
+    progress_cfor!(bar = bar; let mut i = 0; i < 2; k += 1; {
+        i += 1;
+    });
+    assert_eq!((k, lengths(&bar)), (2, (17, None)));
+
+    let bar = ProgressBar::hidden();
+    progress_cfor!(bar = bar; let mut i = 10; i < 0; i += 1; {});
+    assert_eq!(lengths(&bar), (0, Some(0)));
+
+    let mut n = 0;
+    progress_cfor!(let mut i = 0; i < 3; i += 1; { n += i });
+    assert_eq!(n, 3);
+}
+
+#[test]
+fn stats_cfor() {
+    use std::thread;
+    use std::time::Duration;
+
+    let stats = stats_cfor!(let mut i = 0; i < 3; i += 1; {
+        if i == 1 { continue }
+        thread::sleep(Duration::from_millis(10));
+    });
+    assert_eq!((stats.iterations, stats.broke_early), (3, false));
+    assert!(stats.elapsed >= Duration::from_millis(20));
+
+    let stats = stats_cfor!('outer: let mut i = 0; ; i += 1; {
+        crepeat!(j in 2 {
+            if i + j == 3 { break 'outer }
+        });
+    } else {
+        unreachable!();
+    });
+    assert_eq!((stats.iterations, stats.broke_early), (3, true));
+
+    let stats = stats_cfor!(let mut i = 0; i < 0; i += 1; {});
+    assert_eq!((stats.iterations, stats.broke_early), (0, false));
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn par_cfor() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    let seen = Mutex::new(vec![]);
+    par_cfor!(i in 3..10 {
+        if i == 5 { return }
+        seen.lock().unwrap().push(i);
+    });
+    let mut seen = seen.into_inner().unwrap();
+    seen.sort();
+    assert_eq!(seen, [3, 4, 6, 7, 8, 9]);
+
+    let n = AtomicUsize::new(0);
+    par_cfor!(i in 0..=10_000u64 { n.fetch_add(i as usize, Ordering::Relaxed); });
+    par_cfor!(_i in 5..5 { unreachable!() });
+    assert_eq!(n.into_inner(), 10_000 * 10_001 / 2);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn scoped_par_cfor() {
+    use std::sync::Mutex;
+    use std::thread;
+
+    // each chunk runs on a different thread, in order
+    let seen = Mutex::new(vec![]);
+    scoped_par_cfor!(threads = 3; i in 2..9 {
+        if i == 5 { return }
+        seen.lock().unwrap().push((thread::current().id(), i));
+    });
+    let seen = seen.into_inner().unwrap();
+    let mut chunks: Vec<Vec<usize>> = vec![];
+    for &(id, _) in &seen {
+        let chunk: Vec<_> = seen.iter().filter(|s| s.0 == id).map(|s| s.1).collect();
+        if !chunks.contains(&chunk) {
+            chunks.push(chunk);
+        }
+    }
+    chunks.sort();
+    assert_eq!(chunks, [vec![2, 3, 4], vec![6, 7], vec![8]]);
+
+    // more threads than iterations, and none at all
+    let seen = Mutex::new(vec![]);
+    scoped_par_cfor!(threads = 10; i in 0..3 { seen.lock().unwrap().push(i) });
+    scoped_par_cfor!(threads = 0; i in 3..4 { seen.lock().unwrap().push(i) });
+    scoped_par_cfor!(threads = 2; _i in 4..4 { unreachable!() });
+    let mut seen = seen.into_inner().unwrap();
+    seen.sort();
+    assert_eq!(seen, [0, 1, 2, 3]);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn par_cfor_reduce() {
+    let sum = par_cfor_reduce!(reduce(0, |a, b| a + b); i in 0..=100u64 { i });
+    assert_eq!(sum, 5050);
+
+    let words = ["pear", "fig", "banana", "kiwi"];
+    let longest = par_cfor_reduce!(reduce("", |a: &str, b: &str| if b.len() > a.len() { b } else { a }); i in 0..words.len() {
+        words[i]
+    });
+    assert_eq!(longest, "banana");
+
+    let empty = par_cfor_reduce!(reduce(vec![], |mut a: Vec<u8>, b: Vec<u8>| { a.extend(b); a }); i in 0..0u8 { vec![i] });
+    assert!(empty.is_empty());
+
+    let collected = par_cfor_reduce!(reduce(vec![], |mut a: Vec<u8>, b| { a.extend(b); a }); i in 0..5u8 { vec![i] });
+    assert_eq!(collected, [0, 1, 2, 3, 4]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn dynamic_par_cfor() {
+    use std::sync::Mutex;
+    use std::thread;
+    use std::time::Duration;
+
+    let seen = Mutex::new(vec![]);
+    dynamic_par_cfor!(threads = 3, chunk = 4; i in 5..23 {
+        if i == 7 { return }
+        seen.lock().unwrap().push(i);
+    });
+    let mut seen = seen.into_inner().unwrap();
+    seen.sort();
+    assert_eq!(seen, (5..23).filter(|&i| i != 7).collect::<Vec<_>>());
+
+    // one slow iteration doesn't hold up the rest: the other thread
+    // does all of them while it runs
+    let seen = Mutex::new(vec![]);
+    dynamic_par_cfor!(threads = 2; i in 0..20 {
+        if i == 0 {
+            thread::sleep(Duration::from_millis(200));
+        }
+        seen.lock().unwrap().push(i);
+    });
+    assert_eq!(seen.into_inner().unwrap().last(), Some(&0));
+
+    dynamic_par_cfor!(threads = 4, chunk = 0; _i in 3..3 { unreachable!() });
+}
+
+#[test]
+fn simd_cfor() {
+    let mut chunks = vec![];
+    let mut tail = vec![];
+    simd_cfor!(lanes = 4; i in 3..14 {
+        if i == 7 { continue }
+        chunks.push(i);
+    } remainder {
+        tail.push(i);
+    });
+    assert_eq!((chunks, tail), (vec![3], vec![11, 12, 13]));
+
+    // exactly divisible, too short for a chunk, and empty
+    let mut seen = vec![];
+    simd_cfor!(lanes = 2; i in 0..4 { seen.push(('c', i)); break } remainder { seen.push(('t', i)) });
+    simd_cfor!(lanes = 8; i in 4..7 { seen.push(('c', i)) } remainder { seen.push(('t', i)) });
+    simd_cfor!(lanes = 1; i in 7..7 { seen.push(('c', i)) } remainder { seen.push(('t', i)) });
+    assert_eq!(seen, [('c', 0), ('t', 4), ('t', 5), ('t', 6)]);
+}
+
+#[test]
+fn unroll() {
+    let mut seen = vec![];
+    unroll!(4; i in 1..12 {
+        if i % 3 == 0 { continue }
+        seen.push(i);
+    });
+    assert_eq!(seen, [1, 2, 4, 5, 7, 8, 10, 11]);
+
+    // `break` stops everything, in a copy or the remainder
+    let mut seen = vec![];
+    unroll!(3; i in 0..10 {
+        if i == 4 { break }
+        seen.push(i);
+    });
+    unroll!(3; i in 0..5 {
+        if i == 4 { break }
+        seen.push(i);
+    });
+    assert_eq!(seen, [0, 1, 2, 3, 0, 1, 2, 3]);
+
+    let mut n = 0;
+    unroll!(16; i in 0..100 { n += i });
+    unroll!(1; i in 0..3 { n += i });
+    unroll!(8; _i in 7..7 { unreachable!() });
+    assert_eq!(n, 4953);
+}
+
+#[test]
+fn static_for() {
+    let mut seen = vec![];
+    static_for!(i in 2..5 { seen.push(i) });
+    static_for!(i in 0..=1 { seen.push(i * 10) });
+    static_for!(_i in 3..3 { unreachable!() });
+    static_for!(_i in 4..2 { unreachable!() });
+    static_for!(i in 60..=64 { seen.push(i) });
+    assert_eq!(seen, [2, 3, 4, 0, 10, 60, 61, 62, 63, 64]);
+
+    // the index is a constant
+    let mut total = 0;
+    static_for!(i in 1..4 {
+        let array = [i; i];
+        total += array.iter().sum::<usize>();
+    });
+    assert_eq!(total, 1 + 4 + 9);
+
+    // `break` and `continue` refer to the loop around it
+    let mut seen = vec![];
+    for j in 0..3 {
+        static_for!(i in 0..3 {
+            if i == j { continue }
+            if i + j == 3 { break }
+            seen.push((j, i));
+        });
+    }
+    assert_eq!(seen, [(1, 0), (2, 0)]);
+}
+
+macro_rules! seq_fields {
+    ([$($k: tt)*]) => {
+        #[derive(Default)]
+        struct Fields(
+            $(seq_fields!(@ty $k),)*
+        );
+        impl Fields {
+            fn sizes(&self) -> Vec<usize> {
+                vec![$(std::mem::size_of_val(&self.$k)),*]
+            }
+        }
+    };
+    (@ty $k: tt) => { [u8; $k] };
+}
+seq_cfor!(0..=3 => seq_fields!());
+
+#[test]
+fn seq_cfor() {
+    assert_eq!(Fields::default().sizes(), [0, 1, 2, 3]);
+
+    macro_rules! digits {
+        ($n: ident, [$($k: tt)*]) => {
+            $($n = $n * 10 + $k;)*
+        };
+    }
+    let mut n = 0;
+    seq_cfor!(5..8 => digits!(n,));
+    seq_cfor!(8..8 => digits!(n,));
+    assert_eq!(n, 567);
+}
+
+#[test]
+fn array_init_cfor() {
+    const POWERS: [u64; 5] = array_init_cfor![i => 1 << (i * 2); 5];
+    assert_eq!(POWERS, [1, 4, 16, 64, 256]);
+
+    const fn table<const N: usize>() -> [usize; N] {
+        array_init_cfor![i => N - i; N]
+    }
+    assert_eq!(table::<3>(), [3, 2, 1]);
+    assert_eq!(table::<0>(), []);
+
+    let strings = array_init_cfor![i => "ab".repeat(i); 3];
+    assert_eq!(strings, ["", "ab", "abab"]);
+}
+
+#[test]
+fn const_loops() {
+    const fn loops() -> [u32; 12] {
+        let mut out = [0; 12];
+
+        cfor!{let mut i = 0; i < 3; i += 1; {
+            if i == 1 { continue }
+            out[0] += 1;
+        } else {
+            out[0] += 10;
+        } finally {
+            out[0] += 100;
+        }}
+        cfor!{'outer: (let mut i = 0; i < 3; i += 1) {
+            cfor!{let mut j = 0; j < 3; j += 1; {
+                if j > i { continue 'outer }
+                out[1] += 1;
+            }}
+        }}
+        cfor!{; ; ; { break }}
+        cwhile!(out[2] < 5; out[2] += 1; {});
+        cloop!(out[3] += 1; { if out[3] == 4 { break } });
+        cdo!({ out[4] += 1 } while out[4] < 3);
+        crepeat!(i in 4 { out[5] += i });
+        countdown!(i from 4 { out[6] = out[6] * 10 + i });
+        repeat_until!(out[7] >= 6; out[7] += 2 {});
+        out[8] = match cfor_val!{let mut i = 0; i < 10; i += 1; { if i * i > 50 { break i } }} {
+            Some(i) => i,
+            None => 0,
+        };
+        out[9] = counted_cfor!(let mut i = 0; i < 10; i += 1; { if i == 4 { break } }) as u32;
+        if cfor_bounded!(max = 2; ; ; ; {}).is_err() {
+            out[10] = 1;
+        }
+        unroll!(2; i in 0..5 { out[11] += i as u32 });
+        simd_cfor!(lanes = 2; i in 0..5 { out[11] += i as u32 } remainder { out[11] += 100 * i as u32 });
+        static_for!(i in 0..2 { out[11] += 1000 * i as u32 });
+        out
+    }
+    const OUT: [u32; 12] = loops();
+    assert_eq!(OUT, [112, 6, 5, 4, 3, 6, 3210, 6, 8, 5, 1, 1412]);
+}
+
+#[test]
+fn const_empty_headers() {
+    const fn collatz_steps(mut x: u64) -> u32 {
+        let mut steps = 0;
+        cfor!{; x != 1; steps += 1; {
+            x = if x % 2 == 0 { x / 2 } else { 3 * x + 1 };
+        }}
+        steps
+    }
+    const fn first_square_over(n: u32) -> u32 {
+        let mut found = 0;
+        cfor!{'outer: let mut i = 0; ; i += 1; {
+            if i * i > n {
+                found = i;
+                break 'outer;
+            }
+        } else {
+            found = u32::MAX;
+        }}
+        found
+    }
+    const STEPS: u32 = collatz_steps(27);
+    const FOUND: u32 = first_square_over(50);
+    assert_eq!((STEPS, FOUND), (111, 8));
+}
+
+#[test]
+fn tile_cfor() {
+    let mut seen = vec![];
+    tile_cfor!(tile = 2; i in 0..3, j in 1..4 {
+        if (i, j) == (0, 3) { continue }
+        seen.push((i, j));
+    });
+    assert_eq!(seen, [
+        (0, 1), (0, 2), (1, 1), (1, 2),
+        (1, 3),
+        (2, 1), (2, 2),
+        (2, 3),
+    ]);
+
+    let mut seen = vec![];
+    tile_cfor!(tile = 2; i in 0..3, j in 0..2, k in 0..3 {
+        if (i, j, k) == (1, 1, 1) { break }
+        seen.push((i, j, k));
+    });
+    assert_eq!(seen, [
+        (0, 0, 0), (0, 0, 1), (0, 1, 0), (0, 1, 1), (1, 0, 0), (1, 0, 1), (1, 1, 0),
+    ]);
+
+    // big tiles, and empty ranges
+    let mut n = 0;
+    tile_cfor!(tile = 100; i in 0..10, j in 0..10 { n += i * j });
+    tile_cfor!(tile = 1; _i in 0..10, _j in 5..5 { unreachable!() });
+    assert_eq!(n, 45 * 45);
+}
+
+#[test]
+fn chunked_cfor() {
+    let data: Vec<u32> = (0..11).collect();
+    let mut seen = vec![];
+    chunked_cfor!(size = 3; chunk in &data {
+        if chunk[0] == 3 { continue }
+        seen.push(chunk.to_vec());
+    } remainder {
+        seen.push(chunk.to_vec());
+    });
+    assert_eq!(seen, [vec![0, 1, 2], vec![6, 7, 8], vec![9, 10]]);
+
+    // exactly divisible, so no remainder, and `break` only ends the
+    // main loop
+    let mut seen = vec![];
+    chunked_cfor!(size = 2; chunk in &data[..6] with index i {
+        if i == 2 { break }
+        seen.push((i, chunk.len()));
+    } remainder {
+        seen.push((i, chunk.len()));
+    });
+    chunked_cfor!(size = 20; chunk in &data[..] with index i {
+        seen.push((i, chunk.len()));
+    } remainder {
+        seen.push((i, chunk.len()));
+    });
+    assert_eq!(seen, [(0, 2), (0, 11)]);
+
+    let mut data = [0u8; 7];
+    chunked_cfor!(size = 3; chunk in &mut data with index i {
+        chunk.fill(i as u8);
+    } remainder {
+        chunk[0] = 9;
+    });
+    assert_eq!(data, [0, 0, 0, 3, 3, 3, 9]);
+
+    let empty: [u8; 0] = [];
+    chunked_cfor!(size = 3; _chunk in &empty { unreachable!() } remainder { unreachable!() });
+}
+
+#[cfg(feature = "prefetch")]
+#[test]
+fn prefetch_cfor() {
+    struct Node {
+        value: u32,
+        next: Option<Box<Node>>,
+    }
+    let mut list = None;
+    for value in 0..10 {
+        list = Some(Box::new(Node { value, next: list }));
+    }
+
+    let mut seen = vec![];
+    prefetch_cfor!(prefetch = node.next.as_deref().map_or(std::ptr::null(), |n| n as *const Node);
+                   let mut node = list.as_deref().unwrap(); ; node = node.next.as_deref().unwrap(); {
+        seen.push(node.value);
+        if node.next.is_none() { break }
+    });
+    assert_eq!(seen, (0..10).rev().collect::<Vec<_>>());
+
+    // far past the end is fine
+    let data = [1u8, 2, 3];
+    let mut total = 0;
+    prefetch_cfor!(prefetch = &data[i], ahead = 1 << 20; 'outer: let mut i = 0; i < data.len(); i += 1; {
+        if i == 1 { continue 'outer }
+        total += data[i];
+    });
+    assert_eq!(total, 4);
+}
+
+#[cfg(feature = "unchecked")]
+#[test]
+fn cfor_assume() {
+    use cfor::assume;
+
+    let data = [5u32, 6, 7, 8];
+    let mut total = 0;
+    cfor_assume!(unsafe { assume(i < data.len()); assume(total < 100) }; 'outer: let mut i = 0; i < 4; i += 1; {
+        if i == 2 { continue 'outer }
+        total += data[i];
+    });
+    assert_eq!(total, 19);
+}
+
+#[cfg(all(feature = "unchecked", debug_assertions))]
+#[test]
+#[should_panic(expected = "`cfor_assume!` invariant is false")]
+fn cfor_assume_checked() {
+    use cfor::assume;
+
+    let mut seen = vec![];
+    cfor_assume!(unsafe { assume(i < 3) }; let mut i = 0; i < 5; i += 1; { seen.push(i) });
+}
+
+#[test]
+fn slice_cfor() {
+    let data: Vec<u32> = (10..20).collect();
+    let mut seen = vec![];
+    slice_cfor!(x in &data[3..7] with index i {
+        if i == 4 { continue }
+        seen.push((i, *x));
+    });
+    assert_eq!(seen, [(3, 13), (5, 15), (6, 16)]);
+
+    let mut seen = vec![];
+    slice_cfor!(x in &data[..=1] { seen.push(*x) });
+    slice_cfor!(x in &data[8..] with index i { seen.push(*x + i as u32) });
+    slice_cfor!(x in &data {
+        if *x == 12 { break }
+        seen.push(*x);
+    });
+    slice_cfor!(_x in &data[5..5] { unreachable!() });
+    assert_eq!(seen, [10, 11, 26, 28, 10, 11]);
+
+    struct Wrapper { items: [u8; 4] }
+    let mut w = Wrapper { items: [1, 2, 3, 4] };
+    slice_cfor!(x in &mut w.items[1..] with index i { *x *= i as u8 });
+    assert_eq!(w.items, [1, 2, 6, 12]);
+}
+
+#[test]
+#[should_panic(expected = "range end index 4 out of range for slice of length 3")]
+fn slice_cfor_out_of_range() {
+    let data = [1, 2, 3];
+    let mut seen = vec![];
+    slice_cfor!(x in &data[1..4] { seen.push(*x) });
+}
+
+#[test]
+fn stride_cfor() {
+    let data: Vec<u32> = (0..10).collect();
+    let mut seen = vec![];
+    stride_cfor!(start = 0, step = 3; x in &data with index i {
+        if i == 3 { continue }
+        seen.push(*x);
+    });
+    assert_eq!(seen, [0, 6, 9]);
+
+    let mut seen = vec![];
+    stride_cfor!(start = 9, step = -2; x in &data[..] {
+        if *x < 4 { break }
+        seen.push(*x);
+    });
+    stride_cfor!(start = 10, step = 1; _x in &data { unreachable!() });
+    stride_cfor!(start = 2, step = isize::MAX; x in &data with index i { seen.push(*x + i as u32) });
+    assert_eq!(seen, [9, 7, 5, 4]);
+
+    let mut data = [1u8; 7];
+    stride_cfor!(start = 6, step = -3; x in &mut data with index i { *x = i as u8 });
+    assert_eq!(data, [0, 1, 1, 3, 1, 1, 6]);
+}
+
+#[test]
+#[should_panic(expected = "`stride_cfor!` needs a non-zero step")]
+fn stride_cfor_zero_step() {
+    let data = [1, 2, 3];
+    stride_cfor!(start = 0, step = 0; _x in &data {});
+}
+
+#[test]
+fn interleaved_cfor() {
+    let mut data: Vec<u32> = (0..11).collect();
+    let mut seen = vec![];
+    interleaved_cfor!((a, b, c) in &mut data with index frame {
+        if frame == 1 { continue }
+        ::std::mem::swap(a, c);
+        seen.push(*b);
+    });
+    assert_eq!(seen, [1, 7]);
+    assert_eq!(data, [2, 1, 0, 3, 4, 5, 8, 7, 6, 9, 10]);
+
+    let mut seen = vec![];
+    interleaved_cfor!((x,) in &data[..3] { seen.push(*x) });
+    interleaved_cfor!((_, y) in &data {
+        if *y == 5 { break }
+        seen.push(*y);
+    });
+    interleaved_cfor!((_x, _y) in &data[..1] { unreachable!() });
+    assert_eq!(seen, [2, 1, 0, 1, 3]);
+}
+
+#[test]
+fn two_ptr() {
+    let mut v = [1, 2, 3, 4, 5];
+    let mut visited = vec![];
+    two_ptr!(lo = 0, hi = v.len() - 1; lo += 1; hi -= 1; {
+        visited.push((lo, hi));
+        if lo == 0 { continue }
+        v.swap(lo, hi);
+    });
+    assert_eq!(visited, [(0, 4), (1, 3)]);
+    assert_eq!(v, [1, 4, 3, 2, 5]);
+
+    // only one end moving each time, and a labelled break.
+    let mut visited = vec![];
+    two_ptr!('outer: a = 0, b = 2; a += 1; (); {
+        two_ptr!(lo = 0, hi = 10; lo += 3; (); {
+            visited.push((a, lo));
+            if lo > 5 { continue 'outer }
+        });
+    });
+    assert_eq!(visited, [(0, 0), (0, 3), (0, 6), (1, 0), (1, 3), (1, 6)]);
+
+    two_ptr!(lo = 5, hi = 5; lo += 1; hi -= 1; { unreachable!() });
+}
+
+#[test]
+fn window_cfor() {
+    let data: Vec<u32> = (0..8).collect();
+    let mut seen = vec![];
+    window_cfor!(size = 3, hop = 2; w in &data with index i {
+        if i == 2 { continue }
+        seen.push(w.to_vec());
+    });
+    assert_eq!(seen, [vec![0, 1, 2], vec![4, 5, 6]]);
+
+    let mut seen = vec![];
+    window_cfor!(size = 2, hop = 5; w in &data { seen.push(w[0]) });
+    window_cfor!(size = 8, hop = usize::MAX; w in &data { seen.push(w.len() as u32) });
+    window_cfor!(size = 9, hop = 1; _w in &data { unreachable!() });
+    window_cfor!(size = 1, hop = 1; w in &data {
+        if w[0] == 2 { break }
+        seen.push(w[0]);
+    });
+    assert_eq!(seen, [0, 5, 8, 0, 1]);
+
+    let mut data = [0u8; 6];
+    window_cfor!(size = 3, hop = 1; w in &mut data[..] { w[2] += 1 });
+    assert_eq!(data, [0, 0, 1, 1, 1, 1]);
+}
+
+#[test]
+#[should_panic(expected = "`window_cfor!` needs a hop of at least 1")]
+fn window_cfor_zero_hop() {
+    let data = [1, 2, 3];
+    window_cfor!(size = 1, hop = 0; _w in &data {});
+}
+
+#[test]
+fn cfor_rev() {
+    let mut seen = vec![];
+    cfor_rev!(i in 0..5u32 {
+        if i == 3 { continue }
+        seen.push(i);
+    });
+    assert_eq!(seen, [4, 2, 1, 0]);
+
+    let mut seen = vec![];
+    cfor_rev!(i in 1..=u8::MAX step 100 { seen.push(i) });
+    cfor_rev!(i in -3..=3i8 step 3 { seen.push(i as u8) });
+    cfor_rev!(_i in 5..3usize { unreachable!() });
+    cfor_rev!(_i in 5..=4usize { unreachable!() });
+    let n = 2;
+    cfor_rev!(mut i in 0..n + 1 step 2 - 1 {
+        i *= 10;
+        seen.push(i);
+    });
+    assert_eq!(seen, [255, 155, 55, 3, 0, 253, 20, 10, 0]);
+
+    let mut seen = vec![];
+    cfor_rev!('outer: i in 0..3 {
+        cfor_rev!(j in 0..=i {
+            if j == 1 { continue 'outer }
+            seen.push((i, j));
+        });
+    });
+    assert_eq!(seen, [(2, 2), (0, 0)]);
+
+    // signed ranges that cross zero, without overflowing.
+    let mut seen = vec![];
+    cfor_rev!(i in -100i8..=100 step 50 { seen.push(i) });
+    assert_eq!(seen, [100, 50, 0, -50, -100]);
+    let mut seen = vec![];
+    cfor_rev!(i in i8::MIN..i8::MAX step 100 { seen.push(i) });
+    cfor_rev!(i in i64::MIN..=i64::MAX step (i64::MAX) { seen.push((i >> 56) as i8) });
+    assert_eq!(seen, [126, 26, -74, 127, 0, -128]);
+}
+
+#[test]
+#[should_panic(expected = "`cfor_rev!` needs a positive step")]
+fn cfor_rev_zero_step() {
+    cfor_rev!(_i in 0..3 step 0 {});
+}
+
+#[test]
+fn range_inclusive() {
+    let mut seen = vec![];
+    cfor!(i in 0..=10 step 4 { seen.push(i) });
+    cfor!(i in 3..=3 { seen.push(i) });
+    cfor!(_i in 4..=3 { unreachable!() });
+    assert_eq!(seen, [0, 4, 8, 3]);
+
+    // no overflow at either the top, or an exact stop.
+    let mut seen = vec![];
+    cfor!(i in 250..=u8::MAX step 2 { seen.push(i) });
+    cfor!(i in 251..=u8::MAX step 2 { seen.push(i) });
+    cfor!(i in i8::MIN..=-126 { seen.push(i as u8) });
+    assert_eq!(seen, [250, 252, 254, 251, 253, 255, 128, 129, 130]);
+
+    // nor when the range is wider than half the type.
+    let mut seen = vec![];
+    cfor!(i in -100i8..=100 step 50 { seen.push(i) });
+    cfor!(i in i8::MIN..=i8::MAX step 127 { seen.push(i) });
+    assert_eq!(seen, [-100, -50, 0, 50, 100, -128, -1, 126]);
+
+    // the body can change `i`, and the bounds are evaluated once.
+    let mut seen = vec![];
+    let mut n = 10;
+    let mut last = || { n -= 1; n };
+    cfor!('outer: i in 0..=last() step 3 * 2 + 1 {
+        if i == 3 {
+            i += 1;
+            continue 'outer
+        }
+        seen.push(i);
+    } else {
+        seen.push(100);
+    });
+    assert_eq!(n, 9);
+    assert_eq!(seen, [0, 7, 100]);
+
+    let found = cfor_val!(i in 1..=100u32 { if i * i > 50 { break i } });
+    assert_eq!(found, Some(8));
+}
+
+#[test]
+#[should_panic(expected = "`cfor!` needs a positive step")]
+fn range_inclusive_zero_step() {
+    cfor!(_i in 0..=10 step 0 {});
+}
+
+#[test]
+fn range_step() {
+    let mut seen = vec![];
+    cfor!(i in 0..10 step 4 { seen.push(i) });
+    cfor!(i in 3..4 { seen.push(i) });
+    cfor!(_i in 4..4 { unreachable!() });
+    cfor!(_i in 5..4 { unreachable!() });
+    cfor!(i in 250..u8::MAX step 10 { seen.push(i) });
+    cfor!(i in -3..3i8 step 3 { seen.push(i as u8) });
+    assert_eq!(seen, [0, 4, 8, 3, 250, 253, 0]);
+
+    let mut seen = vec![];
+    cfor!(i in -100i8..100 step 50 { seen.push(i) });
+    cfor!(i in i8::MIN..i8::MAX step 127 { seen.push(i) });
+    assert_eq!(seen, [-100, -50, 0, 50, -128, -1, 126]);
+
+    let v = [1, 2, 3, 4, 5, 6];
+    let mut seen = vec![];
+    cfor!('rows: i in 0..v.len() step 3 {
+        cfor!(j in i..i + 3 {
+            if v[j] % 3 == 2 { continue 'rows }
+            seen.push(v[j]);
+        });
+    } finally {
+        seen.push(0);
+    });
+    assert_eq!(seen, [1, 4, 0]);
+
+    // the body can move `i` past the end.
+    let mut n = 0;
+    cfor!(mut_i in 0..10usize step 3 {
+        n += 1;
+        mut_i += 20;
+    });
+    assert_eq!(n, 1);
+}
+
+#[test]
+#[should_panic(expected = "`cfor!` needs a positive step")]
+fn range_step_zero_step() {
+    cfor!(_i in 0..10 step 0 {});
+}
+
+#[test]
+fn range_downto() {
+    let mut seen = vec![];
+    cfor!(i in 5u8 downto 0 { seen.push(i) });
+    cfor!(i in 9u8 downto 1 step 4 { seen.push(i) });
+    cfor!(i in 3 downto 3 { seen.push(i) });
+    cfor!(_i in 2 downto 3 { unreachable!() });
+    cfor!(i in -125i8 downto i8::MIN step 2 { seen.push(i as u8) });
+    assert_eq!(seen, [5, 4, 3, 2, 1, 0, 9, 5, 1, 3, 131, 129]);
+
+    let mut seen = vec![];
+    cfor!(i in 100i8 downto -100 step 50 { seen.push(i) });
+    cfor!(i in i8::MAX downto i8::MIN step 127 { seen.push(i) });
+    assert_eq!(seen, [100, 50, 0, -50, -100, 127, 0, -127]);
+
+    let v = [1, 2, 3, 4];
+    let found = cfor_val!(i in v.len() - 1 downto 0 {
+        if v[i] % 2 == 1 { break i }
+    });
+    assert_eq!(found, Some(2));
+}
+
+#[test]
+#[should_panic(expected = "`cfor!` needs a positive step")]
+fn range_downto_zero_step() {
+    cfor!(_i in 10 downto 0 step 0 {});
+}
+
+#[test]
+fn crange() {
+    use cfor::CRange;
+
+    fn all<T: cfor::Integer>(r: CRange<T>) -> Vec<T> {
+        r.collect()
+    }
+
+    assert_eq!(all(CRange::new(0, 10, 3)), [0, 3, 6, 9]);
+    assert_eq!(all(CRange::new(0, 9, 3)), [0, 3, 6]);
+    assert_eq!(all(CRange::inclusive(0, 9, 3)), [0, 3, 6, 9]);
+    assert_eq!(all(CRange::new(9, 0, -3)), [9, 6, 3]);
+    assert_eq!(all(CRange::inclusive(9, 0, -3)), [9, 6, 3, 0]);
+    assert_eq!(all(CRange::down(9u32, 0, 3)), [9, 6, 3]);
+    assert_eq!(all(CRange::down_inclusive(9u32, 0, 4)), [9, 5, 1]);
+    assert_eq!(all(CRange::new(5, 5, 1)), []);
+    assert_eq!(all(CRange::inclusive(5, 5, 1)), [5]);
+    assert_eq!(all(CRange::new(5, 0, 1)), []);
+    assert_eq!(all(CRange::down(0, 5, 1)), []);
+
+    // the extremes of the types.
+    assert_eq!(CRange::inclusive(0, u8::MAX, 1).count(), 256);
+    assert_eq!(all(CRange::inclusive(i8::MIN, i8::MAX, 127)), [-128, -1, 126]);
+    assert_eq!(all(CRange::inclusive(i8::MAX, i8::MIN, i8::MIN)), [127, -1]);
+    assert_eq!(all(CRange::new(0, u64::MAX, u64::MAX / 2)), [0, u64::MAX / 2, u64::MAX - 1]);
+    assert_eq!(CRange::inclusive(i16::MIN, i16::MAX, 1).len(), 65536);
+    assert_eq!(CRange::inclusive(0, u128::MAX, 1).size_hint(), (usize::MAX, None));
+    assert_eq!(CRange::inclusive(i128::MIN, i128::MAX, 1).last(), Some(i128::MAX));
+
+    // both ends, and skipping.
+    let mut r = CRange::new(0u8, 100, 10);
+    assert_eq!(r.len(), 10);
+    assert_eq!(r.next_back(), Some(90));
+    assert_eq!(r.nth(2), Some(20));
+    assert_eq!(r.len(), 6);
+    assert_eq!(r.clone().rev().collect::<Vec<_>>(), [80, 70, 60, 50, 40, 30]);
+    assert_eq!(r.nth(6), None);
+    assert_eq!(r.next(), None);
+    assert_eq!(r.next_back(), None);
+    assert_eq!(r.len(), 0);
+
+    assert_eq!(format!("{:?}", CRange::new(1, 7, 2)), "CRange { next: 1, last: 5 }");
+}
+
+#[test]
+#[should_panic(expected = "`CRange` needs a non-zero step")]
+fn crange_zero_step() {
+    cfor::CRange::new(0, 10, 0);
+}
+
+#[test]
+fn frange() {
+    use cfor::FRange;
+
+    fn all(r: FRange<f64>) -> Vec<f64> {
+        r.collect()
+    }
+
+    assert_eq!(all(FRange::new(0.0, 2.0, 0.5)), [0.0, 0.5, 1.0, 1.5]);
+    assert_eq!(all(FRange::new(2.0, 0.0, -0.5)), [2.0, 1.5, 1.0, 0.5]);
+    assert_eq!(all(FRange::new(0.0, 2.1, 0.5)), [0.0, 0.5, 1.0, 1.5, 2.0]);
+    assert_eq!(all(FRange::new(0.0, 2.0, -0.5)), []);
+    assert_eq!(all(FRange::new(0.0, 2.0, 0.0)), []);
+    assert_eq!(all(FRange::new(1.0, 0.0, 0.0)), []);
+    assert_eq!(all(FRange::new(0.0, f64::NAN, 1.0)), []);
+    assert_eq!(FRange::new(0.0, 1.0, 0.1).len(), 10);
+    assert_eq!(FRange::new(0.0, 0.3, 0.1).len(), 3);
+    assert_eq!(FRange::new(0.0f32, 1.0, 0.01).len(), 100);
+    assert_eq!(FRange::new(0.0, 1e6, 0.001).len(), 1_000_000_000);
+
+    assert_eq!(all(FRange::with_count(1.0, -0.25, 3)), [1.0, 0.75, 0.5]);
+    assert_eq!(all(FRange::with_count(1.0, 0.25, 0)), []);
+    assert_eq!(all(FRange::linspace(0.0, 3.0, 5)), [0.0, 0.75, 1.5, 2.25, 3.0]);
+    assert_eq!(all(FRange::linspace(5.0, 7.0, 1)), [5.0]);
+    assert_eq!(all(FRange::linspace(5.0, 7.0, 0)), []);
+
+    let mut r = FRange::linspace(0.0, 1.0, 11);
+    assert_eq!(r.next_back(), Some(1.0));
+    assert_eq!(r.nth(3), Some(0.30000000000000004));
+    assert_eq!(r.len(), 6);
+    assert_eq!(r.nth(10), None);
+    assert_eq!(r.next_back(), None);
+    assert_eq!(format!("{:?}", FRange::with_count(0.0, 0.5, 3)), "FRange { next: 0.0, last: 1.0 }");
+}
+
+#[test]
+fn cfor_checked() {
+    let mut seen = vec![];
+    cfor_checked!(let mut x = 1u8; ; x *= 2; {
+        if x == 4 { continue }
+        seen.push(x);
+    });
+    assert_eq!(seen, [1, 2, 8, 16, 32, 64, 128]);
+
+    // each kind of step, and other steps alongside.
+    let mut seen = vec![];
+    let mut n = 0u32;
+    cfor_checked!((let mut x = 5u8; x > 0; x -= 2, n += 1) { seen.push(x) });
+    assert_eq!((n, &seen[..]), (3, &[5, 3, 1][..]));
+    cfor_checked!('outer: let mut x = i8::MIN + 1, let mut y = 100i8; ; x /= -1, y += 20; {
+        seen.push(x as u8);
+        seen.push(y as u8);
+        if y > 110 { continue 'outer }
+    } else {
+        seen.push(0);
+    } finally {
+        seen.push(1);
+    });
+    assert_eq!(seen, [5, 3, 1, 129, 100, 127, 120, 0, 1]);
+
+    // an empty step, and a condition that stops first.
+    let mut x = 0u32;
+    cfor_checked!(; x < 3; ; { x += 1 });
+    assert_eq!(x, 3);
+}
+
+#[test]
+fn crange_checked() {
+    use cfor::CRange;
+
+    assert_eq!(CRange::checked(250u8, 2).collect::<Vec<_>>(), [250, 252, 254]);
+    assert_eq!(CRange::checked(255u8, 1).collect::<Vec<_>>(), [255]);
+    assert_eq!(CRange::checked(-100i8, -14).collect::<Vec<_>>(), [-100, -114, -128]);
+    assert_eq!(CRange::checked(0u64, 1 << 62).count(), 4);
+    assert_eq!(CRange::checked(i128::MIN, 1).size_hint(), (usize::MAX, None));
+}
+
+#[test]
+fn cfor_wrapping() {
+    let mut seen = vec![];
+    cfor_wrapping!(let mut x = 250u8, let mut n = 0u32; n < 4; x += 3, n += 1; {
+        if n == 1 { continue }
+        seen.push(x);
+    });
+    cfor_wrapping!('outer: (let mut x = 1u8, let mut y = 2u8; x != 0; x -= 1, y *= 200) {
+        seen.push(y);
+        if y == 0 { continue 'outer }
+    } else {
+        seen.push(7);
+    });
+    assert_eq!(seen, [250, 0, 3, 2, 7]);
+
+    let mut probes = vec![];
+    cfor_wrapping!(let mut h = u32::MAX - 1, let mut i = 0u32; i < 3; i += 1, h += i; {
+        probes.push(h);
+    });
+    assert_eq!(probes, [u32::MAX - 1, u32::MAX, 1]);
+}
+
+#[test]
+fn crange_wrapping() {
+    use cfor::CRange;
+
+    assert_eq!(CRange::wrapping(254u8, 1, 4).collect::<Vec<_>>(), [254, 255, 0, 1]);
+    assert_eq!(CRange::wrapping(1i8, -100, 3).rev().collect::<Vec<_>>(), [57, -99, 1]);
+    assert_eq!(CRange::wrapping(0u16, 1, 0).count(), 0);
+    assert_eq!(CRange::wrapping(0u16, 5, 1000).len(), 1000);
+}
+
+#[test]
+fn cfor_saturating() {
+    let mut seen = vec![];
+    cfor_saturating!(let mut x = 240u8; ; x += 7; {
+        if x == 247 { continue }
+        seen.push(x);
+    } else {
+        seen.push(0);
+    });
+    assert_eq!(seen, [240, 254, 255, 0]);
+
+    // stops when the step stops changing anything, even away from
+    // the bounds, and only after the body.
+    let mut seen = vec![];
+    cfor_saturating!((let mut x = 3i8, let mut y = 0i8; x > -5; x -= 100, y += 0) {
+        seen.push((x, y));
+    });
+    assert_eq!(seen, [(3, 0)]);
+    let mut seen = vec![];
+    cfor_saturating!('outer: let mut x = -100i8; ; x *= 2; {
+        seen.push(x);
+        if seen.len() > 5 { break 'outer }
+    });
+    assert_eq!(seen, [-100, -128]);
+}
+
+#[test]
+fn crange_saturating() {
+    use cfor::CRange;
+
+    assert_eq!(CRange::saturating(250u8, 2).collect::<Vec<_>>(), [250, 252, 254, 255]);
+    assert_eq!(CRange::saturating(251u8, 2).collect::<Vec<_>>(), [251, 253, 255]);
+    assert_eq!(CRange::saturating(-100i8, -20).rev().collect::<Vec<_>>(), [-128, -120, -100]);
+    let mut r = CRange::saturating(0u16, 10_000);
+    assert_eq!(r.len(), 8);
+    assert_eq!(r.nth(6), Some(60_000));
+    assert_eq!(r.next(), Some(u16::MAX));
+    assert_eq!(r.next(), None);
+}
+
+#[test]
+fn iter() {
+    assert_eq!(cfor::iter(0, |i| *i < 5, |i| *i += 2).collect::<Vec<_>>(), [0, 2, 4]);
+    assert_eq!(cfor::iter(0, |_| false, |_| unreachable!()).count(), 0);
+
+    // the step only runs when the next value is needed.
+    let mut steps = 0;
+    {
+        let mut it = cfor::iter(64u8, |x| *x >= 64, |x| {
+            steps += 1;
+            *x = x.wrapping_mul(2);
+        });
+        assert_eq!(it.next(), Some(64));
+        assert_eq!(it.next(), Some(128));
+        assert_eq!(it.next(), None);
+        assert_eq!(it.next(), None);
+    }
+    assert_eq!(steps, 2);
+
+    let words: Vec<String> = cfor::iter(String::from("a"), |s| s.len() <= 3, |s| s.push('b')).collect();
+    assert_eq!(words, ["a", "ab", "abb"]);
+}
+
+#[test]
+fn cfor_struct() {
+    fn evens_below(n: u32) -> cfor::CFor<u32, impl FnMut(&u32) -> bool + Clone, fn(&mut u32)> {
+        cfor::iter(0, move |i| *i < n, |i| *i += 2)
+    }
+
+    let mut it = evens_below(7);
+    assert_eq!(it.next(), Some(0));
+    let fork = it.clone();
+    assert_eq!(it.collect::<Vec<_>>(), [2, 4, 6]);
+    assert_eq!(fork.collect::<Vec<_>>(), [2, 4, 6]);
+
+    fn fused<I: std::iter::FusedIterator>(_: &I) {}
+    let mut it = evens_below(1);
+    fused(&it);
+    assert_eq!(format!("{:?}", it), "CFor { state: Some(0), .. }");
+    assert_eq!((it.next(), it.next(), it.next()), (Some(0), None, None));
+    assert_eq!(format!("{:?}", it), "CFor { state: None, .. }");
+}
+
+#[test]
+fn loop_builder() {
+    use cfor::Loop;
+    use std::ops::ControlFlow;
+
+    let mut seen = vec![];
+    let ended = Loop::new(0).while_(|i| *i < 10).step(|i| *i += 3).run(|i| seen.push(*i));
+    assert_eq!(ended, None);
+    assert_eq!(seen, [0, 3, 6, 9]);
+
+    // the body can end the loop with a value.
+    let mut seen = vec![];
+    let ended = Loop::new((0, 1)).step(|s| *s = (s.1, s.0 + s.1)).run(|s| {
+        if s.0 > 20 {
+            return ControlFlow::Break(s.0);
+        }
+        seen.push(s.0);
+        ControlFlow::Continue(())
+    });
+    assert_eq!(ended, Some(21));
+    assert_eq!(seen, [0, 1, 1, 2, 3, 5, 8, 13]);
+
+    // the parts are values, and can come from anywhere.
+    let step: Box<dyn Fn(&mut u32)> = if seen.len() > 5 { Box::new(|x| *x *= 2) } else { Box::new(|x| *x += 1) };
+    let halfway = Loop::new(1).while_(|x| *x < 50).step(step);
+    assert_eq!(halfway.iter().collect::<Vec<_>>(), [1, 2, 4, 8, 16, 32]);
+    assert_eq!(Loop::new(()).while_(|_| false).run(|_| -> () { unreachable!() }), None);
+}
+
+#[test]
+fn run() {
+    use std::ops::ControlFlow;
+
+    let mut seen = vec![];
+    let ended = cfor::run(0, |i| *i < 5, |i| *i += 1, |i| {
+        if *i == 2 {
+            return ControlFlow::Continue(());
+        }
+        seen.push(*i);
+        ControlFlow::<()>::Continue(())
+    });
+    assert_eq!(ended, None);
+    assert_eq!(seen, [0, 1, 3, 4]);
+
+    let ended = cfor::run(10u8, |_| true, |x| *x -= 1, |x| {
+        if *x % 7 == 0 { ControlFlow::Break((*x, "seven")) } else { ControlFlow::Continue(()) }
+    });
+    assert_eq!(ended, Some((7, "seven")));
+
+    let mut n = 0;
+    assert_eq!(cfor::run(0, |i| *i < 3, |i| *i += 1, |_| n += 1), None);
+    assert_eq!(n, 3);
+}
+
+#[test]
+fn cfor_ext() {
+    use cfor::CForExt;
+
+    let v: Vec<u32> = 1u32.c_to(1 << 20).step_with(|x| x * 3).collect();
+    assert_eq!(v.len(), 13);
+    assert_eq!(v.last(), Some(&531441));
+
+    // the step only runs when the next value is needed, so it can stop
+    // before overflowing.
+    let v: Vec<u8> = 1u8.c_to(255).step_with(|x| x * 2).take(8).collect();
+    assert_eq!(v, [1, 2, 4, 8, 16, 32, 64, 128]);
+
+    assert_eq!(5.c_to(5).step_with(|x| x + 1).next(), None);
+    assert_eq!(10i8.c_to(-10).step(-7).collect::<Vec<_>>(), [10, 3, -4]);
+    assert_eq!((-2i64).c_to(2).into_iter().collect::<Vec<_>>(), [-2, -1, 0, 1]);
+}
+
+#[test]
+fn lazy() {
+    let v: Vec<u32> = cfor!(let mut x = 1; x < 100; x *= 3 => x).collect();
+    assert_eq!(v, [1, 3, 9, 27, 81]);
+
+    // the step only runs when the next value is needed, so this
+    // doesn't overflow.
+    let mut it = cfor!(let mut x = 1u8; ; x *= 2 => x);
+    assert_eq!(it.nth(7), Some(128));
+
+    // multiple variables, a pattern condition, and an empty step.
+    let data = [Some(1), Some(2), None, Some(4)];
+    let data = &data;
+    let v: Vec<_> = cfor!(let mut i = 0, let mut sum = 0;
+                          let Some(&Some(x)) = data.get(i);
+                          => { i += 1; sum += x; (x, sum) })
+        .collect();
+    assert_eq!(v, [(1, 1), (2, 3)]);
+
+    let mut calls = 0;
+    {
+        let calls = &mut calls;
+        let mut empty = cfor!(let mut i = 10; i < 5; { *calls += 1; i += 1 } => i);
+        assert_eq!(empty.next(), None);
+        assert_eq!(empty.next(), None);
+    }
+    assert_eq!(calls, 0);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn collect() {
+    use std::collections::{BTreeMap, HashSet};
+
+    let n = 20;
+    let v = cfor_collect!(let mut a = 0, let mut b = 1; a < n; b += a, a = b - a => a);
+    assert_eq!(v, vec![0, 1, 1, 2, 3, 5, 8, 13]);
+
+    let set = cfor_collect!(::<HashSet<_>> let mut i = 0; i < 10; i += 1 => i % 3);
+    assert_eq!(set.len(), 3);
+
+    let nested = cfor_collect!(::<Vec<Vec<u8>>> let mut i = 0; i < 3; i += 1 => vec![i; i as usize]);
+    assert_eq!(nested, [vec![], vec![1], vec![2, 2]]);
+
+    let map = cfor_collect!(::<BTreeMap<_, _>> (let mut c = b'a'; c <= b'c'; c += 1) => (c as char, c));
+    assert_eq!(map[&'b'], b'b');
+
+    let none: Vec<i32> = cfor_collect!(let mut i = 0; i < 0; i += 1 => i);
+    assert!(none.is_empty());
+}
+
+#[test]
+fn fold() {
+    let v = [3, 1, 4, 1, 5, 9, 2, 6];
+    let max = cfold!(acc = v[0]; let mut i = 1; i < v.len(); i += 1 => acc.max(v[i]));
+    assert_eq!(max, 9);
+
+    // the step sees the accumulator as of the end of the iteration.
+    let (steps, total) = cfold!(acc: (u32, u32) = (0, 0); let mut i = 0; acc.1 < 10; i += acc.0 => (acc.0 + 1, acc.1 + i));
+    assert_eq!((steps, total), (4, 10));
+
+    let none = cfold!(acc = 7; let mut i = 0; i < 0; i += 1 => acc + i);
+    assert_eq!(none, 7);
+
+    let mut seen = vec![];
+    let evens = cfold!(acc = 0; (let mut i = 0; i < 10; i += 2) => {
+        seen.push(i);
+        acc + 1
+    });
+    assert_eq!(evens, 5);
+    assert_eq!(seen, [0, 2, 4, 6, 8]);
+}
+
+#[test]
+fn sum_product() {
+    use std::time::Duration;
+
+    let n = 100u64;
+    assert_eq!(cfor_sum!(let mut i = 1; i <= n; i += 1 => i), 5050);
+    assert_eq!(cfor_sum!((let mut x = 1; x < 1000; x *= 2) => x), 1023);
+    assert_eq!(cfor_product!(let mut i = 1; i <= 5; i += 1 => i as f64 / 2.0), 3.75);
+
+    let waits = cfor_sum!(let mut i = 0; i < 4; i += 1 => Duration::from_millis(1 << i));
+    assert_eq!(waits, Duration::from_millis(15));
+
+    assert_eq!(cfor_sum!(let mut i = 0; i < 0; i += 1 => i as f32), 0.0);
+    assert_eq!(cfor_product!(let mut i = 0u8; ; i += 1 => {
+        if i == 3 { break }
+        i + 2
+    }), 24);
+}
+
+#[test]
+fn scan() {
+    let v: Vec<u64> = cfor_scan!(acc = 1; let mut i = 1; i <= 5; i += 1 => acc * i).collect();
+    assert_eq!(v, [1, 2, 6, 24, 120]);
+
+    let words = ["a", "b", "c"];
+    let words = &words;
+    let v: Vec<String> = cfor_scan!(acc: String = String::new(); (let mut i = 0; i < words.len(); i += 1) => acc + words[i])
+        .collect();
+    assert_eq!(v, ["a", "ab", "abc"]);
+
+    // lazy: the running total can be infinite.
+    let triangles: Vec<u32> = cfor_scan!(acc = 0; let mut i = 1; ; i += 1 => acc + i).take(4).collect();
+    assert_eq!(triangles, [1, 3, 6, 10]);
+
+    assert_eq!(cfor_scan!(acc = 0; let mut i = 0; i < 0; i += 1 => acc + i).count(), 0);
+}
+
+#[test]
+fn find() {
+    let grid = [[0, 0, 1], [0, 2, 0]];
+    let found = cfor_find!(let mut k = 0; k < 6; k += 1 => grid[k / 3][k % 3] == 2 => (k / 3, k % 3));
+    assert_eq!(found, Some((1, 1)));
+
+    // the search stops at the first match.
+    let mut checked = 0;
+    let found = cfor_find!(let mut i = 0; i < 100; i += 1 => { checked += 1; i * i > 50 } => i);
+    assert_eq!((found, checked), (Some(8), 9));
+
+    let collatz = cfor_find!(let mut n = 27u64, let mut steps = 0; ; n = if n % 2 == 0 { n / 2 } else { 3 * n + 1 }, steps += 1
+                             => (n == 1).then_some(steps));
+    assert_eq!(collatz, Some(111));
+
+    assert_eq!(cfor_find!(let mut i = 0; i < 0; i += 1 => Some(i)), None);
+}
+
+#[test]
+fn any_all() {
+    let v = [2, 7, 1, 8, 2, 8];
+
+    let mut checked = 0;
+    assert!(cfor_any!(let mut i = 0; i < v.len(); i += 1 => { checked += 1; v[i] > 7 }));
+    assert_eq!(checked, 4);
+
+    checked = 0;
+    assert!(!cfor_all!(let mut i = 0; i < v.len(); i += 1 => { checked += 1; v[i] % 2 == 0 }));
+    assert_eq!(checked, 2);
+
+    assert!(cfor_all!((let mut i = 1; i < v.len(); i += 2) => v[i] > 6));
+    assert!(!cfor_any!(let mut i = 0; i < 0; i += 1 => true));
+}
+
+#[test]
+fn enumerate() {
+    let mut seen = vec![];
+    cfor!(enumerate k; let mut i = 10; i > 0; i /= 2; {
+        if k == 1 {
+            continue;
+        }
+        seen.push((k, i));
+    });
+    assert_eq!(seen, [(0, 10), (2, 2), (3, 1)]);
+
+    // changing the counter doesn't change the next one.
+    let mut seen = vec![];
+    cfor!('outer: enumerate k; i in 0..3 {
+        seen.push(k);
+        k += 10;
+        seen.push(k);
+        if i == 5 {
+            break 'outer;
+        }
+    });
+    assert_eq!(seen, [0, 10, 1, 11, 2, 12]);
+
+    let mut count = 0;
+    cfor!(enumerate n; (let mut x = 5; x < 3; x += 1) {
+        count += n + x;
+    });
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn zip() {
+    // stops as soon as either condition fails, and both steps run
+    // after `continue`.
+    let mut seen = vec![];
+    cfor_zip!((let mut i = 0; i < 10; i += 1), (let mut x = 1u32; x < 100; x *= 3) {
+        if i == 1 {
+            continue;
+        }
+        seen.push((i, x));
+    });
+    assert_eq!(seen, [(0, 1), (2, 9), (3, 27), (4, 81)]);
+
+    let mut seen = vec![];
+    for _ in 0..2 {
+        cfor_zip!('zip: (let mut i = 0, let mut j = 5; ; i += 1, j += 1), (; seen.len() < 7; ) {
+            for _ in 0..3 {
+                if i == 1 {
+                    continue 'zip;
+                }
+            }
+            seen.push(i + j);
+        });
+    }
+    assert_eq!(seen, [5, 9, 11, 13, 15, 17, 19]);
+
+    let mut completed = false;
+    cfor_zip!((let mut i = 0; i < 3; i += 1), (let mut j = 0; j > 0; j -= 1) {
+        let _ = (i, j);
+    } else {
+        completed = true;
+    });
+    assert!(completed);
+}
+
+#[test]
+fn nested() {
+    let mut seen = vec![];
+    nested_cfor!((i: 0..3) (j: i..=2) (k: 2 downto j) {
+        if k == 1 {
+            continue;
+        }
+        seen.push((i, j, k));
+    });
+    assert_eq!(seen, [(0, 0, 2), (0, 0, 0), (0, 1, 2), (0, 2, 2), (1, 1, 2), (1, 2, 2), (2, 2, 2)]);
+
+    // a labelled `continue` goes on to the next row, after its step.
+    let mut seen = vec![];
+    nested_cfor!('rows: (let mut i = 1; i < 100; i *= 10) (j: 0..5 step 2) {
+        if j == 2 {
+            continue 'rows;
+        }
+        seen.push(i + j);
+    });
+    assert_eq!(seen, [1, 10]);
+
+    let mut count = 0;
+    nested_cfor!((i: 0..4) (j: 0..4) (k: 0..4) {
+        if i + j + k == 5 {
+            break;
+        }
+        count += 1;
+    });
+    assert_eq!(count, 36);
+}
+
+#[test]
+fn triangular() {
+    let mut pairs = vec![];
+    triangular_cfor!(i, j in 1..4 {
+        pairs.push((i, j));
+    });
+    assert_eq!(pairs, [(1, 2), (1, 3), (2, 3)]);
+
+    // the end is evaluated once, and the inner start is the outer
+    // index at the time.
+    let mut v = vec![3, 1, 2];
+    let mut calls = 0;
+    triangular_cfor!('outer: i, j in 0..{ calls += 1; v.len() } {
+        if v[j] < v[i] {
+            v.swap(i, j);
+            continue 'outer;
+        }
+    });
+    assert_eq!(calls, 1);
+    assert_eq!(v, [1, 2, 3]);
+
+    let mut count = 0u32;
+    triangular_cfor!(i, j in 5..5 {
+        count += i + j;
+    });
+    triangular_cfor!(_i, _j in 0u8..255 {
+        count += 1;
+    });
+    assert_eq!(count, 255 * 254 / 2);
+}
+
+#[test]
+fn matrix() {
+    let mut m = [0; 6];
+    matrix_cfor!(width = 3; (r, c, x) in &mut m with index i {
+        assert_eq!(i, r * 3 + c);
+        *x = r * 10 + c;
+    });
+    assert_eq!(m, [0, 1, 2, 10, 11, 12]);
+
+    // a ragged last row, `continue` and `break`.
+    let v = [1, 2, 3, 4, 5, 6, 7];
+    let mut seen = vec![];
+    matrix_cfor!(width = 2; (r, c, &x) in &v[..] {
+        if c == 0 {
+            continue;
+        }
+        if x > 6 {
+            break;
+        }
+        seen.push((r, x));
+    });
+    assert_eq!(seen, [(0, 2), (1, 4), (2, 6)]);
+
+    let mut last = None;
+    matrix_cfor!(width = 4; (r, c, _) in &v {
+        last = Some((r, c));
+    });
+    assert_eq!(last, Some((1, 2)));
+}
+
+#[test]
+#[should_panic(expected = "`matrix_cfor!` needs a width of at least 1")]
+fn matrix_zero_width() {
+    matrix_cfor!(width = 0; (_, _, _) in &[1, 2] {});
+}
+
+#[test]
+fn break_all() {
+    let mut seen = vec![];
+    nested_cfor!((i: 0..3) (j: 0..3) {
+        // even from a loop in the body.
+        cfor!(let mut k = 0; k < 3; k += 1; {
+            if i == 1 && j == 1 && k == 1 {
+                break_all!();
+            }
+            seen.push((i, j, k));
+        });
+    });
+    assert_eq!(seen.len(), 9 + 3 + 1);
+    assert_eq!(seen.last(), Some(&(1, 1, 0)));
+
+    // an inner nest's `break_all!()` only exits that nest.
+    let mut count = 0;
+    nested_cfor!((i: 0..4) (j: 0..2) {
+        nested_cfor!((a: 0..2) {
+            if a == 1 {
+                break_all!();
+            }
+            count += 1;
+        });
+        if i == 2 && j == 1 {
+            break_all!();
+        }
+        count += 100;
+    });
+    assert_eq!(count, 5 * 101 + 1);
+
+    let mut pairs = 0;
+    triangular_cfor!(i, j in 0..10 {
+        if i * j > 20 {
+            break_all!();
+        }
+        pairs += 1;
+    });
+    assert_eq!(pairs, 9 + 8 + 7 + 3);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn worklist() {
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    // a stack is depth-first, and the step runs after `continue`.
+    let mut stack = vec![(0, 1)];
+    let mut order = vec![];
+    let mut steps = 0;
+    worklist!((depth, n) from stack; steps += 1 {
+        order.push(n);
+        if depth == 2 {
+            continue;
+        }
+        stack.push((depth + 1, 2 * n + 1));
+        stack.push((depth + 1, 2 * n));
+    });
+    assert_eq!(order, [1, 2, 4, 5, 3, 6, 7]);
+    assert_eq!(steps, 7);
+
+    // a heap, through a reference, with a labelled `break`.
+    let mut heap = BinaryHeap::from([Reverse(5), Reverse(1), Reverse(3)]);
+    let heap_ref = &mut heap;
+    let mut taken = vec![];
+    worklist!('work: Reverse(x) from *heap_ref {
+        for _ in 0..1 {
+            if x > 3 {
+                break 'work;
+            }
+        }
+        taken.push(x);
+    });
+    assert_eq!(taken, [1, 3]);
+    assert_eq!(heap.len(), 0);
+
+    let mut empty: Vec<u8> = vec![];
+    worklist!(_ from empty { unreachable!() });
+}
+
+#[test]
+fn until_stable() {
+    use cfor::Exhausted;
+
+    let mut evaluations = 0;
+    let x = until_stable!(x = 100 => { evaluations += 1; x / 2 + 3 });
+    assert_eq!(x, 6);
+    assert_eq!(evaluations, 8);
+
+    // already stable: one evaluation.
+    evaluations = 0;
+    assert_eq!(until_stable!(x: i8 = 0 => { evaluations += 1; x * 5 }), 0);
+    assert_eq!(evaluations, 1);
+
+    let words = until_stable!(s = String::from("aaaabbb") => s.replace("aa", "a"));
+    assert_eq!(words, "abbb");
+
+    assert_eq!(until_stable!(max = 8; x = 100 => x / 2 + 3), Ok(6));
+    assert_eq!(until_stable!(max = 7; x = 100 => x / 2 + 3), Err(Exhausted));
+    assert_eq!(until_stable!(max = 0; x = 0 => x), Err(Exhausted));
+}
+
+#[test]
+fn converge() {
+    use cfor::DidNotConverge;
+
+    // the body runs once per iteration, up to the limit.
+    let mut runs = 0;
+    let r = converge!(tol = 0.5, max = 10; x = 100.0; {
+        runs += 1;
+        x /= 2.0;
+    } error: x);
+    assert_eq!(r, Ok(0.390625));
+    assert_eq!(runs, 8);
+
+    runs = 0;
+    let r = converge!(tol = 0.5, max = 7; x = 100.0; {
+        runs += 1;
+        x /= 2.0;
+    } error: x);
+    assert_eq!(r, Err(DidNotConverge));
+    assert_eq!(runs, 7);
+
+    // a NaN error never converges, and the values can be a tuple.
+    let r = converge!(tol = 1.0, max = 3; pair: (f64, u32) = (f64::NAN, 0); {
+        pair.1 += 1;
+    } error: pair.0);
+    assert_eq!(r, Err(DidNotConverge));
+
+    let r = converge!(tol = 1, max = 0; x = 0; {
+        x += 1;
+    } error: x);
+    assert_eq!(r, Err(DidNotConverge));
+}
+
+#[test]
+fn divisors() {
+    let mut pairs = vec![];
+    divisors!(d, q in 60 {
+        if d == 4 {
+            continue;
+        }
+        pairs.push((d, q));
+    });
+    assert_eq!(pairs, [(1, 60), (2, 30), (3, 20), (5, 12), (6, 10)]);
+
+    let mut pairs = vec![];
+    divisors!(d, q in 49u8 {
+        pairs.push((d, q));
+    });
+    assert_eq!(pairs, [(1, 49), (7, 7)]);
+
+    // no overflow near the top of the type, where `d * d` would.
+    let mut seen = vec![];
+    divisors!('all: d, q in u8::MAX {
+        for _ in 0..2 {
+            if d == 3 {
+                continue 'all;
+            }
+        }
+        seen.push((d, q));
+    });
+    assert_eq!(seen, [(1, 255), (5, 51), (15, 17)]);
+
+    divisors!(_d, _q in 0 { unreachable!() });
+}
+
+#[test]
+fn digits() {
+    let mut seen = vec![];
+    digits!(d in 1203 {
+        if d == 0 {
+            continue;
+        }
+        seen.push(d);
+    });
+    assert_eq!(seen, [3, 2, 1]);
+
+    let mut bits = vec![];
+    digits!('bits: b in u64::MAX; base = 2 {
+        bits.push(b);
+        for _ in 0..1 {
+            if bits.len() == 64 {
+                break 'bits;
+            }
+        }
+    });
+    assert_eq!(bits, [1; 64]);
+
+    digits!(_d in 0 { unreachable!() });
+}
+
+#[test]
+#[should_panic(expected = "`digits!` needs a base of at least 2")]
+fn digits_base_one() {
+    // even with no digits to loop over.
+    digits!(_d in 0u32; base = 1 {});
+}
+
+#[test]
+fn bitloop() {
+    let mut seen = vec![];
+    bitloop!(b in 0x8000_0001u32 {
+        seen.push(b);
+    });
+    assert_eq!(seen, [1, 0x8000_0000]);
+
+    let mut indices = vec![];
+    bitloop!('bits: _b, i in -1i8 {
+        if i % 2 == 0 {
+            continue;
+        }
+        for _ in 0..1 {
+            if i == 7 {
+                break 'bits;
+            }
+        }
+        indices.push(i);
+    });
+    assert_eq!(indices, [1, 3, 5]);
+
+    bitloop!(_b in 0u64 { unreachable!() });
+}
+
+#[test]
+fn submasks() {
+    let mut all = vec![];
+    submasks!(s in 0b1011u8; with_empty {
+        all.push(s);
+    });
+    assert_eq!(all, [0b1011, 0b1010, 0b1001, 0b1000, 0b0011, 0b0010, 0b0001, 0]);
+
+    let mut full = 0;
+    submasks!(_s in u16::MAX {
+        full += 1;
+    });
+    assert_eq!(full, 65535);
+
+    let mut odd = vec![];
+    submasks!('masks: s in 0b111i32; with_empty {
+        if s % 2 == 0 {
+            continue;
+        }
+        for _ in 0..1 {
+            if s == 1 {
+                break 'masks;
+            }
+        }
+        odd.push(s);
+    });
+    assert_eq!(odd, [0b111, 0b101, 0b011]);
+
+    submasks!(_s in 0u8 { unreachable!() });
+    let mut empty = vec![];
+    submasks!(s in 0u8; with_empty {
+        empty.push(s);
+    });
+    assert_eq!(empty, [0]);
+}
+
+#[test]
+fn powers() {
+    let mut all = vec![];
+    powers!(p in 2u64 {
+        all.push(p);
+    });
+    assert_eq!(all.len(), 64);
+    assert_eq!(all[63], 1 << 63);
+
+    let mut below = vec![];
+    powers!(p in 10i32; p < 5000 {
+        below.push(p);
+    });
+    assert_eq!(below, [1, 10, 100, 1000]);
+
+    let mut odd = vec![];
+    powers!('powers: p in 3i8 {
+        if p == 3 {
+            continue;
+        }
+        for _ in 0..1 {
+            if p == 81 {
+                break 'powers;
+            }
+        }
+        odd.push(p);
+    });
+    assert_eq!(odd, [1, 9, 27]);
+
+    let mut all_u128 = 0;
+    powers!(_p in 7u128 {
+        all_u128 += 1;
+    });
+    assert_eq!(all_u128, 46);
+}
+
+#[test]
+#[should_panic(expected = "`powers!` needs a base of at least 2")]
+fn powers_base_one() {
+    // even if the condition is false from the start.
+    powers!(p in 1u32; p > 1 {});
+}
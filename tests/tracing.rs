@@ -0,0 +1,72 @@
+#![cfg(feature = "tracing")]
+
+use std::fmt::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use cfor::traced_cfor;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Records the spans and events as strings.
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+struct Fields<'a>(&'a mut String);
+
+impl Visit for Fields<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        write!(self.0, " {}={:?}", field.name(), value).unwrap();
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+    fn new_span(&self, span: &Attributes) -> Id {
+        let mut s = format!("span {}", span.metadata().name());
+        span.record(&mut Fields(&mut s));
+        self.0.lock().unwrap().push(s);
+        Id::from_u64(1)
+    }
+    fn record(&self, _: &Id, _: &Record) {}
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+    fn event(&self, event: &Event) {
+        let mut s = "event".to_string();
+        event.record(&mut Fields(&mut s));
+        self.0.lock().unwrap().push(s);
+    }
+    fn enter(&self, _: &Id) {}
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn traced_cfor() {
+    let recorder = Recorder::default();
+    let mut lines = (0, 0);
+    tracing::subscriber::with_default(recorder.clone(), || {
+        let mut total = 0;
+        lines.0 = line!() + 1;
+        traced_cfor!(vars = (i, total); let mut i = 0; i < 3; i += 1; {
+            total += i;
+        });
+        lines.1 = line!() + 1;
+        traced_cfor!('outer: let mut i = 0; ; i += 1; {
+            if i == 1 { continue 'outer }
+            if i == 2 { break }
+        });
+    });
+
+    let log = recorder.0.lock().unwrap();
+    assert_eq!(*log, [
+        format!("span cfor location=\"{}:{}\"", file!(), lines.0),
+        "event iteration=0 i=0 total=0".to_string(),
+        "event iteration=1 i=1 total=0".to_string(),
+        "event iteration=2 i=2 total=1".to_string(),
+        format!("span cfor location=\"{}:{}\"", file!(), lines.1),
+        "event iteration=0".to_string(),
+        "event iteration=1".to_string(),
+        "event iteration=2".to_string(),
+    ]);
+}